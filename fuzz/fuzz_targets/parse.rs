@@ -0,0 +1,70 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use quack::{demangle, elf::{self, parse::{ElfParse, Sym}}, macho};
+
+/// Goes through everything that reads the file, ignoring the errors.
+macro_rules! read_all {
+    ($elf: expr) => {{
+        let elf = $elf;
+        let _ = elf.dynamic();
+        let _ = elf.build_id();
+        elf.notes().count();
+        elf.relocations().count();
+        if let Ok(Some(versions)) = elf.versions() {
+            for i in 0..elf.dynsym.map_or(0, |dynsym| dynsym.len()) {
+                let _ = versions.version(i);
+            }
+        }
+        let _ = elf.lookup(b"main");
+        let _ = elf.lookup_versioned(b"puts", b"GLIBC_2.2.5");
+        for (i, sym) in elf.symtab.unwrap_or(&[]).iter().enumerate() {
+            let _ = elf.sym_section(i);
+            let _ = elf.sym_offset(i);
+            if let Some(Ok(name)) = elf.sym_names.as_ref().map(|names| sym.name(names)) {
+                let _ = demangle::demangle(name).map(|demangled| demangled.to_string());
+            }
+        }
+    }};
+}
+
+fuzz_target!(|data: &[u8]| {
+    // The structures are read in place, so the input has to be aligned like a mapped file
+    let mut aligned = vec![0u8; data.len() + 8];
+    let offset = aligned.as_ptr().align_offset(8);
+    aligned[offset..offset + data.len()].copy_from_slice(data);
+    let buf = &aligned[offset..offset + data.len()];
+    if let Ok(fat) = macho::parse::with_fat(buf) {
+        for slice in fat.slices().flatten() {
+            let _ = slice.parse();
+        }
+    }
+    if let Ok(macho) = macho::parse::with(buf) {
+        for segment in macho.segments().flatten() {
+            let _ = segment.command.data(buf);
+            for sect in segment.sections {
+                let _ = sect.data(buf);
+            }
+        }
+        let _ = macho.entry();
+        let _ = macho.uuid();
+        let _ = macho.build_version();
+        macho.dylibs().count();
+        if let Some(sym_names) = &macho.sym_names {
+            for sym in macho.symbols() {
+                let _ = (sym.name(sym_names), sym.binding(), sym.st_type(), sym.section());
+            }
+        }
+        let _ = (macho.local_symbols(), macho.extdef_symbols(), macho.undef_symbols());
+        if let Ok(fixups) = macho.fixups() {
+            fixups.take(10000).count();
+        }
+    }
+    match elf::parse::with(buf) {
+        Ok(ElfParse::Elf32(elf)) => read_all!(elf),
+        Ok(ElfParse::Elf64(elf)) => read_all!(elf),
+        Ok(ElfParse::Elf32Be(elf)) => read_all!(elf),
+        Ok(ElfParse::Elf64Be(elf)) => read_all!(elf),
+        Err(_) => (),
+    }
+});
@@ -0,0 +1,71 @@
+//! Demangling of symbol names for printing. The demanglers don't allocate: they check a name once up front
+//! and then write the demangled form straight to the formatter.
+
+use core::fmt::{self, Write};
+
+pub mod itanium;
+pub mod rust;
+
+/// A symbol name in one of the mangling schemes that can be demangled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Demangled<'a> {
+    Rust(rust::Demangle<'a>),
+    Cpp(itanium::Demangle<'a>),
+}
+
+/// Recognizes a Rust or C++ symbol name. Rust's legacy names are also valid C++ names, and are tried first
+/// because of their hash.
+pub fn demangle(name: &[u8]) -> Option<Demangled<'_>> {
+    rust::demangle(name).map(Demangled::Rust).or_else(|| itanium::demangle(name).map(Demangled::Cpp))
+}
+
+impl fmt::Display for Demangled<'_> {
+    /// The alternate form of Rust names hides their hashes, C++ names print the same either way.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rust(demangle) => fmt::Display::fmt(demangle, f),
+            Self::Cpp(demangle) => fmt::Display::fmt(demangle, f),
+        }
+    }
+}
+
+/// The longest demangled name that is printed, longer ones are left mangled.
+const MAX_LEN: usize = 1 << 20;
+
+/// Counts the bytes written, so that backreferences can't blow a short name up into a huge one.
+struct Limited<'w, W: Write> {
+    out: &'w mut W,
+    written: usize,
+}
+
+impl<W: Write> Write for Limited<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.written += s.len();
+        if self.written > MAX_LEN {
+            return Err(fmt::Error);
+        }
+        self.out.write_str(s)
+    }
+}
+
+/// Discards the output, for checking whether a name demangles at all.
+struct Sink;
+
+impl Write for Sink {
+    fn write_str(&mut self, _: &str) -> fmt::Result {
+        Ok(())
+    }
+}
+
+#[test]
+fn schemes() {
+    let check = |mangled: &str, expected: &str| assert_eq!(format!("{:#}", demangle(mangled.as_bytes()).unwrap()), expected);
+    check("_ZN4core3fmt5write17h0123456789abcdefE", "core::fmt::write");
+    check("_RNvC6_123foo3bar", "123foo::bar");
+    // Without a hash, names in the legacy Rust scheme are C++ names
+    check("_ZN4core3fmt5writeE", "core::fmt::write");
+    check("_ZN3FooC2ERKS_", "Foo::Foo(Foo const&)");
+    assert!(matches!(demangle(b"_ZN3foo17h0123456789abcdefE"), Some(Demangled::Rust(_))));
+    assert!(matches!(demangle(b"_ZN3foo3barE"), Some(Demangled::Cpp(_))));
+    assert_eq!(demangle(b"main"), None);
+}
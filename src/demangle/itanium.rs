@@ -0,0 +1,1471 @@
+//! C++ symbol names in the Itanium ABI's `_Z` scheme, printed the way binutils' c++filt prints them.
+//!
+//! The mangling refers back to earlier parts of a name through substitutions and template parameters. Instead of
+//! building a tree of the name, the demangler remembers where those parts start and parses them again to print
+//! them. Expressions, like in `decltype`, aren't supported, and the names that use them don't demangle.
+
+use core::{
+    fmt::{self, Write},
+    mem, str,
+};
+
+use super::{Limited, Sink};
+
+/// A C++ symbol name that can be displayed demangled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Demangle<'a> {
+    /// Everything after `_Z`, which is where the positions of substitutions count from.
+    sym: &'a [u8],
+}
+
+/// Recognizes a C++ symbol name, or returns `None` for anything that doesn't demangle.
+/// Mach-O's extra leading underscore is accepted.
+pub fn demangle(name: &[u8]) -> Option<Demangle<'_>> {
+    let name = name.strip_prefix(b"_")?;
+    let sym = name.strip_prefix(b"_").unwrap_or(name).strip_prefix(b"Z")?;
+    let demangle = Demangle { sym };
+    write!(Sink, "{}", demangle).ok()?;
+    Some(demangle)
+}
+
+impl fmt::Display for Demangle<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut printer = Printer {
+            sym: self.sym,
+            next: 0,
+            out: Limited { out: f, written: 0 },
+            last: 0,
+            separator: false,
+            expansion: Expansion::Outside,
+            skipping: false,
+            replaying: false,
+            depth: 0,
+            steps: 0,
+            subs: [Sub::Type(0); MAX_SUBS],
+            sub_count: 0,
+            template_args: None,
+            type_depth: 0,
+            last_name: "",
+        };
+        printer.mangled_name()
+    }
+}
+
+/// How deep names and types can nest, including through substitutions.
+const MAX_DEPTH: u32 = 256;
+
+/// How many names and types a name can be made of, including their repetitions through substitutions.
+const MAX_STEPS: u32 = 1 << 16;
+
+/// The most substitution candidates that a name can have.
+const MAX_SUBS: usize = 256;
+
+/// The most pointers, references and qualifiers that a single type can wrap around its base.
+const MAX_MODIFIERS: usize = 16;
+
+/// A part of the name that a substitution can refer to, by the position where it starts.
+#[derive(Debug, Clone, Copy)]
+enum Sub {
+    Type(u32),
+    /// The components of a nested name up to the end position, like the `std::vector` of `std::vector<int>::size`.
+    Prefix(u32, u32),
+}
+
+/// What a name tells about the function it names.
+#[derive(Debug, Default, Clone, Copy)]
+struct NameInfo {
+    /// The name ends with template arguments, so the function's return type is mangled too.
+    template: bool,
+    /// Constructors, destructors and conversion operators don't have return types even as templates.
+    ctor_dtor_conv: bool,
+    /// The qualifiers of member functions, like the `const` of `size() const`.
+    cv: u8,
+    ref_qualifier: Option<&'static str>,
+}
+
+const CONST: u8 = 4;
+const VOLATILE: u8 = 2;
+const RESTRICT: u8 = 1;
+
+/// The pointers, references and qualifiers around the base of a type, printed after it.
+#[derive(Debug, Clone, Copy)]
+enum Modifier {
+    Pointer,
+    Reference,
+    RvalueReference,
+    Cv(u8),
+    Complex,
+    Imaginary,
+    /// A pointer to a member of the class type at this position.
+    Member(usize),
+}
+
+/// How template parameters that refer to argument packs are resolved.
+#[derive(Debug, Clone, Copy)]
+enum Expansion {
+    /// Outside of pack expansions they refer to the whole pack.
+    Outside,
+    /// Parsing the pattern of a pack expansion to find out the length of the first pack it refers to.
+    Measuring(Option<usize>),
+    /// Printing the pattern for one of the elements.
+    Element(usize),
+}
+
+/// The standard library's abbreviations, which print the same as `std::` names but aren't substitution candidates.
+struct StdName {
+    name: &'static str,
+    /// The name that constructors and destructors repeat.
+    last: &'static str,
+}
+
+fn std_name(tag: u8) -> Option<StdName> {
+    let (name, last) = match tag {
+        b'a' => ("std::allocator", "allocator"),
+        b'b' => ("std::basic_string", "basic_string"),
+        b's' => ("std::basic_string<char, std::char_traits<char>, std::allocator<char> >", "basic_string"),
+        b'i' => ("std::basic_istream<char, std::char_traits<char> >", "basic_istream"),
+        b'o' => ("std::basic_ostream<char, std::char_traits<char> >", "basic_ostream"),
+        b'd' => ("std::basic_iostream<char, std::char_traits<char> >", "basic_iostream"),
+        _ => return None,
+    };
+    Some(StdName { name, last })
+}
+
+fn builtin_type(tag: u8) -> Option<&'static str> {
+    Some(match tag {
+        b'v' => "void",
+        b'w' => "wchar_t",
+        b'b' => "bool",
+        b'c' => "char",
+        b'a' => "signed char",
+        b'h' => "unsigned char",
+        b's' => "short",
+        b't' => "unsigned short",
+        b'i' => "int",
+        b'j' => "unsigned int",
+        b'l' => "long",
+        b'm' => "unsigned long",
+        b'x' => "long long",
+        b'y' => "unsigned long long",
+        b'n' => "__int128",
+        b'o' => "unsigned __int128",
+        b'f' => "float",
+        b'd' => "double",
+        b'e' => "long double",
+        b'g' => "__float128",
+        b'z' => "...",
+        _ => return None,
+    })
+}
+
+/// The builtin types that start with `D`, by their second letter.
+fn builtin_d_type(tag: u8) -> Option<&'static str> {
+    Some(match tag {
+        b'd' => "decimal64",
+        b'e' => "decimal128",
+        b'f' => "decimal32",
+        b'h' => "half",
+        b'i' => "char32_t",
+        b's' => "char16_t",
+        b'u' => "char8_t",
+        b'a' => "auto",
+        b'c' => "decltype(auto)",
+        b'n' => "decltype(nullptr)",
+        _ => return None,
+    })
+}
+
+fn operator(code: &[u8]) -> Option<&'static str> {
+    Some(match code {
+        b"nw" => "new",
+        b"na" => "new[]",
+        b"dl" => "delete",
+        b"da" => "delete[]",
+        b"ps" | b"pl" => "+",
+        b"ng" | b"mi" => "-",
+        b"ad" | b"an" => "&",
+        b"de" | b"ml" => "*",
+        b"co" => "~",
+        b"dv" => "/",
+        b"rm" => "%",
+        b"or" => "|",
+        b"eo" => "^",
+        b"aS" => "=",
+        b"pL" => "+=",
+        b"mI" => "-=",
+        b"mL" => "*=",
+        b"dV" => "/=",
+        b"rM" => "%=",
+        b"aN" => "&=",
+        b"oR" => "|=",
+        b"eO" => "^=",
+        b"ls" => "<<",
+        b"rs" => ">>",
+        b"lS" => "<<=",
+        b"rS" => ">>=",
+        b"eq" => "==",
+        b"ne" => "!=",
+        b"lt" => "<",
+        b"gt" => ">",
+        b"le" => "<=",
+        b"ge" => ">=",
+        b"ss" => "<=>",
+        b"nt" => "!",
+        b"aa" => "&&",
+        b"oo" => "||",
+        b"pp" => "++",
+        b"mm" => "--",
+        b"cm" => ",",
+        b"pm" => "->*",
+        b"pt" => "->",
+        b"cl" => "()",
+        b"ix" => "[]",
+        b"qu" => "?",
+        b"aw" => "co_await",
+        _ => return None,
+    })
+}
+
+/// Parses a name and prints it at the same time. All of the parse errors are `fmt::Error`s,
+/// which `demangle` turns into `None` by printing every name into a `Sink` first.
+struct Printer<'s, 'w, W: Write> {
+    sym: &'s [u8],
+    next: usize,
+    out: Limited<'w, W>,
+    /// The last byte printed, as c++filt separates the `>`s of nested template arguments.
+    last: u8,
+    /// Which element of their packs template parameters stand for.
+    expansion: Expansion,
+    /// A `, ` to print before whatever is printed next, which is dropped if a list item prints nothing.
+    separator: bool,
+    /// Set while going over parts that are printed somewhere else, or only need to be parsed for their length.
+    skipping: bool,
+    /// Set while parsing a part again, which doesn't add substitution candidates the second time.
+    replaying: bool,
+    depth: u32,
+    steps: u32,
+    subs: [Sub; MAX_SUBS],
+    sub_count: usize,
+    /// Where the template arguments that the template parameters refer to start.
+    template_args: Option<usize>,
+    /// How many types the parser is in, as only the template arguments of the outermost name are referred to.
+    type_depth: u32,
+    /// The last name of a nested name so far, which constructors and destructors repeat.
+    last_name: &'s str,
+}
+
+type Parsed<T = ()> = Result<T, fmt::Error>;
+
+impl<'s, W: Write> Printer<'s, '_, W> {
+    fn peek(&self) -> Option<u8> {
+        self.sym.get(self.next).copied()
+    }
+
+    fn peek_second(&self) -> Option<u8> {
+        self.sym.get(self.next + 1).copied()
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        let eaten = self.peek() == Some(b);
+        self.next += eaten as usize;
+        eaten
+    }
+
+    fn expect(&mut self, b: u8) -> fmt::Result {
+        match self.eat(b) {
+            true => Ok(()),
+            false => Err(fmt::Error),
+        }
+    }
+
+    fn next_byte(&mut self) -> Parsed<u8> {
+        let b = self.peek().ok_or(fmt::Error)?;
+        self.next += 1;
+        Ok(b)
+    }
+
+    fn print(&mut self, s: &str) -> fmt::Result {
+        if self.skipping || s.is_empty() {
+            return Ok(());
+        }
+        if mem::take(&mut self.separator) {
+            self.out.write_str(", ")?;
+        }
+        self.last = s.as_bytes()[s.len() - 1];
+        self.out.write_str(s)
+    }
+
+    fn print_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        if self.skipping {
+            return Ok(());
+        }
+        if mem::take(&mut self.separator) {
+            self.out.write_str(", ")?;
+        }
+        self.last = 0;
+        self.out.write_fmt(args)
+    }
+
+    /// Prints items separated by commas, leaving out those that print nothing, like empty packs.
+    fn list(&mut self, mut end: impl FnMut(&mut Self) -> bool, mut item: impl FnMut(&mut Self) -> fmt::Result) -> fmt::Result {
+        let mut first = true;
+        while !end(self) {
+            let written = self.out.written;
+            let separated = !first && !self.skipping;
+            self.separator |= separated;
+            item(self)?;
+            if separated && mem::take(&mut self.separator) {
+                // c++filt takes back the separator before an empty item, but still remembers printing it
+                self.last = b' ';
+            }
+            first &= self.out.written == written;
+        }
+        Ok(())
+    }
+
+    fn print_cv(&mut self, cv: u8) -> fmt::Result {
+        if cv & CONST != 0 {
+            self.print(" const")?;
+        }
+        if cv & VOLATILE != 0 {
+            self.print(" volatile")?;
+        }
+        if cv & RESTRICT != 0 {
+            self.print(" restrict")?;
+        }
+        Ok(())
+    }
+
+    /// Counts the work done, as parts that refer to each other could otherwise be parsed over and over again.
+    fn step(&mut self) -> fmt::Result {
+        self.steps += 1;
+        match self.steps > MAX_STEPS {
+            true => Err(fmt::Error),
+            false => Ok(()),
+        }
+    }
+
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Parsed<T>) -> Parsed<T> {
+        self.depth += 1;
+        self.step()?;
+        if self.depth > MAX_DEPTH {
+            return Err(fmt::Error);
+        }
+        let parsed = f(self);
+        self.depth -= 1;
+        parsed
+    }
+
+    /// Parses without printing, but still adds the substitution candidates.
+    fn skip<T>(&mut self, f: impl FnOnce(&mut Self) -> Parsed<T>) -> Parsed<T> {
+        let skipping = mem::replace(&mut self.skipping, true);
+        let parsed = f(self);
+        self.skipping = skipping;
+        parsed
+    }
+
+    /// Parses the part at `position` again, coming back to where the parser was.
+    fn replay<T>(&mut self, position: usize, f: impl FnOnce(&mut Self) -> Parsed<T>) -> Parsed<T> {
+        let next = mem::replace(&mut self.next, position);
+        let replaying = mem::replace(&mut self.replaying, true);
+        let parsed = f(self);
+        self.next = next;
+        self.replaying = replaying;
+        parsed
+    }
+
+    fn add_sub(&mut self, sub: Sub) -> fmt::Result {
+        if self.replaying {
+            return Ok(());
+        }
+        *self.subs.get_mut(self.sub_count).ok_or(fmt::Error)? = sub;
+        self.sub_count += 1;
+        Ok(())
+    }
+
+    fn position(position: usize) -> Parsed<u32> {
+        position.try_into().map_err(|_| fmt::Error)
+    }
+
+    /// A decimal number, which the ABI writes with an `n` for the minus sign.
+    fn number(&mut self) -> Parsed<(bool, &'s str)> {
+        let negative = self.eat(b'n');
+        let start = self.next;
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.next += 1;
+        }
+        match self.next == start {
+            true => Err(fmt::Error),
+            false => Ok((negative, str::from_utf8(&self.sym[start..self.next]).map_err(|_| fmt::Error)?)),
+        }
+    }
+
+    fn small_number(&mut self) -> Parsed<usize> {
+        match self.number()? {
+            (false, digits) => digits.parse().map_err(|_| fmt::Error),
+            (true, _) => Err(fmt::Error),
+        }
+    }
+
+    /// The base 36 number of substitutions, where the plain `_` is 0 and the digits encode one less.
+    fn seq_id(&mut self) -> Parsed<usize> {
+        if self.eat(b'_') {
+            return Ok(0);
+        }
+        let mut id = 0usize;
+        while !self.eat(b'_') {
+            let digit = match self.next_byte()? {
+                b @ b'0'..=b'9' => b - b'0',
+                b @ b'A'..=b'Z' => b - b'A' + 10,
+                _ => return Err(fmt::Error),
+            };
+            id = id.checked_mul(36).and_then(|id| id.checked_add(digit as usize)).ok_or(fmt::Error)?;
+        }
+        id.checked_add(1).ok_or(fmt::Error)
+    }
+
+    fn cv_qualifiers(&mut self) -> u8 {
+        let mut cv = 0;
+        if self.eat(b'r') {
+            cv |= RESTRICT;
+        }
+        if self.eat(b'V') {
+            cv |= VOLATILE;
+        }
+        if self.eat(b'K') {
+            cv |= CONST;
+        }
+        cv
+    }
+
+    fn mangled_name(&mut self) -> fmt::Result {
+        self.encoding(false)?;
+        // The copies of functions that optimizations make, like `.constprop.0` or `.cold`
+        while self.eat(b'.') {
+            let start = self.next - 1;
+            while self.peek().is_some_and(|b| b.is_ascii_alphabetic() || b == b'_') {
+                self.next += 1;
+            }
+            while self.peek() == Some(b'.') && self.peek_second().is_some_and(|b| b.is_ascii_digit()) {
+                self.next += 1;
+                while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                    self.next += 1;
+                }
+            }
+            if self.next == start + 1 {
+                return Err(fmt::Error);
+            }
+            let clone = str::from_utf8(&self.sym[start..self.next]).map_err(|_| fmt::Error)?;
+            self.print_fmt(format_args!(" [clone {}]", clone))?;
+        }
+        match self.next == self.sym.len() {
+            true => Ok(()),
+            false => Err(fmt::Error),
+        }
+    }
+
+    /// Whether the parameters of a function end here, at the end of the name or of its encoding,
+    /// or at the reference qualifier of a function type.
+    fn at_params_end(&self) -> bool {
+        match self.peek() {
+            None | Some(b'.' | b'E') => true,
+            Some(b'R' | b'O') => self.peek_second() == Some(b'E'),
+            _ => false,
+        }
+    }
+
+    fn params(&mut self) -> fmt::Result {
+        self.print("(")?;
+        if self.peek() == Some(b'v') {
+            self.next += 1;
+            if !self.at_params_end() {
+                return Err(fmt::Error);
+            }
+        }
+        self.list(|p| p.at_params_end(), Self::print_type)?;
+        self.print(")")
+    }
+
+    /// Functions and data, with or without their return types. Those of local names and template arguments
+    /// are printed without, and end at an `E`.
+    fn encoding(&mut self, inner: bool) -> fmt::Result {
+        self.nested(|p| p.encoding_inner(inner))
+    }
+
+    fn encoding_inner(&mut self, inner: bool) -> fmt::Result {
+        if matches!(self.peek(), Some(b'T' | b'G')) {
+            return self.special_name();
+        }
+        // The return type goes before the name, but is mangled after it
+        let start = self.next;
+        let info = self.skip(Self::name)?;
+        if self.at_params_end() {
+            return self.replay(start, Self::name).map(drop);
+        }
+        if info.template && !info.ctor_dtor_conv {
+            match inner {
+                true => self.skip(Self::print_type)?,
+                false => {
+                    self.print_type()?;
+                    self.print(" ")?;
+                }
+            }
+        }
+        self.replay(start, Self::name)?;
+        self.params()?;
+        self.print_cv(info.cv)?;
+        if let Some(ref_qualifier) = info.ref_qualifier {
+            self.print(ref_qualifier)?;
+        }
+        Ok(())
+    }
+
+    /// A number of bytes to adjust `this` by, and where the virtual ones are in the vtable.
+    fn call_offset(&mut self) -> Parsed<bool> {
+        match self.next_byte()? {
+            b'h' => {
+                self.number()?;
+                self.expect(b'_')?;
+                Ok(false)
+            }
+            b'v' => {
+                self.number()?;
+                self.expect(b'_')?;
+                self.number()?;
+                self.expect(b'_')?;
+                Ok(true)
+            }
+            _ => Err(fmt::Error),
+        }
+    }
+
+    fn special_name(&mut self) -> fmt::Result {
+        match (self.next_byte()?, self.next_byte()?) {
+            (b'T', tag @ (b'V' | b'T' | b'I' | b'S')) => {
+                self.print(match tag {
+                    b'V' => "vtable for ",
+                    b'T' => "VTT for ",
+                    b'I' => "typeinfo for ",
+                    _ => "typeinfo name for ",
+                })?;
+                self.print_type()
+            }
+            (b'T', b'h' | b'v') => {
+                self.next -= 1;
+                let virtual_thunk = self.call_offset()?;
+                self.print(if virtual_thunk { "virtual thunk to " } else { "non-virtual thunk to " })?;
+                self.encoding(false)
+            }
+            (b'T', b'c') => {
+                self.call_offset()?;
+                self.call_offset()?;
+                self.print("covariant return thunk to ")?;
+                self.encoding(false)
+            }
+            (b'T', b'C') => {
+                let derived = self.next;
+                self.skip(Self::print_type)?;
+                self.number()?;
+                self.expect(b'_')?;
+                self.print("construction vtable for ")?;
+                self.print_type()?;
+                self.print("-in-")?;
+                self.replay(derived, Self::print_type)
+            }
+            (b'T', tag @ (b'H' | b'W')) => {
+                self.print(if tag == b'H' { "TLS init function for " } else { "TLS wrapper function for " })?;
+                self.name().map(drop)
+            }
+            (b'G', b'V') => {
+                self.print("guard variable for ")?;
+                self.name().map(drop)
+            }
+            (b'G', b'T') => {
+                let transaction = match self.next_byte()? {
+                    b't' => "transaction clone for ",
+                    b'n' => "non-transaction clone for ",
+                    _ => return Err(fmt::Error),
+                };
+                self.print(transaction)?;
+                self.encoding(false)
+            }
+            _ => Err(fmt::Error),
+        }
+    }
+
+    fn name(&mut self) -> Parsed<NameInfo> {
+        self.nested(Self::name_inner)
+    }
+
+    fn name_inner(&mut self) -> Parsed<NameInfo> {
+        let mut info = NameInfo::default();
+        match self.peek() {
+            Some(b'N') => {
+                self.next += 1;
+                info.cv = self.cv_qualifiers();
+                if self.eat(b'R') {
+                    info.ref_qualifier = Some(" &");
+                } else if self.eat(b'O') {
+                    info.ref_qualifier = Some(" &&");
+                }
+                let start = self.next;
+                self.prefix(start, None, &mut info)?;
+            }
+            Some(b'Z') => {
+                self.next += 1;
+                self.own_template_args(|p| {
+                    p.encoding(true)?;
+                    p.expect(b'E')?;
+                    p.print("::")?;
+                    if p.eat(b's') {
+                        p.print("string literal")
+                    } else {
+                        info = p.name()?;
+                        Ok(())
+                    }
+                })?;
+                // The discriminators of entities with the same name in a function aren't printed
+                if self.eat(b'_') {
+                    if self.eat(b'_') {
+                        self.small_number()?;
+                        self.expect(b'_')?;
+                    } else {
+                        self.next_byte()?;
+                    }
+                }
+            }
+            Some(b'S') if self.peek_second() != Some(b't') => {
+                // Only template names can be substitutions on their own
+                self.substitution()?;
+                if self.peek() != Some(b'I') {
+                    return Err(fmt::Error);
+                }
+                self.template_args()?;
+                info.template = true;
+            }
+            _ => {
+                let start = self.next;
+                if self.eat(b'S') {
+                    self.expect(b't')?;
+                    self.print("std::")?;
+                }
+                self.unqualified_name(&mut info)?;
+                if self.peek() == Some(b'I') {
+                    self.add_sub(Sub::Prefix(Self::position(start)?, Self::position(self.next)?))?;
+                    self.template_args()?;
+                    info.template = true;
+                }
+            }
+        }
+        Ok(info)
+    }
+
+    /// The components of a nested name, up to its `E` or to `end` when printing a prefix substitution.
+    fn prefix(&mut self, start: usize, end: Option<usize>, info: &mut NameInfo) -> fmt::Result {
+        let mut first = true;
+        loop {
+            match end {
+                Some(end) if self.next >= end => return if self.next == end { Ok(()) } else { Err(fmt::Error) },
+                None if self.eat(b'E') => return if first { Err(fmt::Error) } else { Ok(()) },
+                _ => (),
+            }
+            info.template = false;
+            match (self.peek(), self.peek_second()) {
+                (Some(b'S'), Some(b't')) if first => {
+                    self.next += 2;
+                    self.print("std")?;
+                    first = false;
+                    continue;
+                }
+                (Some(b'S'), _) if first => {
+                    self.substitution()?;
+                    first = false;
+                    continue;
+                }
+                (Some(b'I'), _) if !first => {
+                    self.template_args()?;
+                    info.template = true;
+                }
+                (Some(b'T'), _) if first => self.template_param()?,
+                (Some(b'S' | b'I' | b'T'), _) => return Err(fmt::Error),
+                _ => {
+                    if !first {
+                        self.print("::")?;
+                    }
+                    info.ctor_dtor_conv = false;
+                    self.unqualified_name(info)?;
+                }
+            }
+            first = false;
+            // The prefixes are substitution candidates, but the whole name isn't
+            if self.peek() != Some(b'E') {
+                self.add_sub(Sub::Prefix(Self::position(start)?, Self::position(self.next)?))?;
+            }
+        }
+    }
+
+    fn source_name(&mut self) -> Parsed<&'s str> {
+        let len = self.small_number()?;
+        let end = self.next.checked_add(len).filter(|&end| end <= self.sym.len()).ok_or(fmt::Error)?;
+        let name = str::from_utf8(&self.sym[self.next..end]).map_err(|_| fmt::Error)?;
+        self.next = end;
+        Ok(name)
+    }
+
+    fn unqualified_name(&mut self, info: &mut NameInfo) -> fmt::Result {
+        match self.peek().ok_or(fmt::Error)? {
+            b'0'..=b'9' => {
+                let name = self.source_name()?;
+                self.last_name = name;
+                // GCC's names for anonymous namespaces are `_GLOBAL_` and one of `._$`, then `N`
+                match name.as_bytes() {
+                    [b'_', b'G', b'L', b'O', b'B', b'A', b'L', b'_', b'.' | b'_' | b'$', b'N', ..] => {
+                        self.print("(anonymous namespace)")?
+                    }
+                    _ => self.print(name)?,
+                }
+            }
+            b'L' => {
+                // Internal linkage
+                self.next += 1;
+                return self.unqualified_name(info);
+            }
+            b'C' => {
+                self.next += 1;
+                match self.next_byte()? {
+                    b'1'..=b'5' => (),
+                    b'I' => {
+                        // The constructors that `using` inherits from a base class mangle it
+                        if !matches!(self.next_byte()?, b'1' | b'2') {
+                            return Err(fmt::Error);
+                        }
+                        self.skip(Self::print_type)?;
+                    }
+                    _ => return Err(fmt::Error),
+                }
+                info.ctor_dtor_conv = true;
+                self.print(self.last_name)?;
+            }
+            b'D' => {
+                self.next += 1;
+                if !matches!(self.next_byte()?, b'0'..=b'5') {
+                    return Err(fmt::Error);
+                }
+                info.ctor_dtor_conv = true;
+                self.print("~")?;
+                self.print(self.last_name)?;
+            }
+            b'U' => {
+                self.next += 1;
+                match self.next_byte()? {
+                    b't' => self.print("{unnamed type#")?,
+                    b'l' => {
+                        self.print("{lambda")?;
+                        let last_name = self.last_name;
+                        self.params()?;
+                        self.last_name = last_name;
+                        self.expect(b'E')?;
+                        self.print("#")?;
+                    }
+                    _ => return Err(fmt::Error),
+                }
+                let n = match self.eat(b'_') {
+                    true => 1,
+                    false => {
+                        let n = self.small_number()?;
+                        self.expect(b'_')?;
+                        n.checked_add(2).ok_or(fmt::Error)?
+                    }
+                };
+                self.print_fmt(format_args!("{}}}", n))?;
+            }
+            b'a'..=b'z' => {
+                let code = self.sym.get(self.next..self.next + 2).ok_or(fmt::Error)?;
+                self.next += 2;
+                match code {
+                    b"cv" => {
+                        self.print("operator ")?;
+                        info.ctor_dtor_conv = true;
+                        let last_name = self.last_name;
+                        self.print_type()?;
+                        self.last_name = last_name;
+                    }
+                    b"li" => {
+                        let name = self.source_name()?;
+                        self.print_fmt(format_args!("operator\"\" {}", name))?;
+                    }
+                    [b'v', b'0'..=b'9'] => {
+                        let name = self.source_name()?;
+                        self.print_fmt(format_args!("operator {}", name))?;
+                    }
+                    _ => {
+                        let operator = operator(code).ok_or(fmt::Error)?;
+                        // A space keeps the words and the angle brackets of templates apart
+                        let space = operator.as_bytes()[0].is_ascii_alphabetic();
+                        self.print("operator")?;
+                        self.print(if space { " " } else { "" })?;
+                        self.print(operator)?;
+                    }
+                }
+            }
+            _ => return Err(fmt::Error),
+        }
+        while self.eat(b'B') {
+            let tag = self.source_name()?;
+            self.print_fmt(format_args!("[abi:{}]", tag))?;
+        }
+        Ok(())
+    }
+
+    /// Prints a substitution after its `S`, which is either a standard abbreviation or a part that came before.
+    fn substitution(&mut self) -> Parsed<Option<Sub>> {
+        self.expect(b'S')?;
+        if let Some(std) = self.peek().and_then(std_name) {
+            self.next += 1;
+            self.print(std.name)?;
+            self.last_name = std.last;
+            return Ok(None);
+        }
+        let id = self.seq_id()?;
+        let sub = *self.subs[..self.sub_count].get(id).ok_or(fmt::Error)?;
+        if !self.skipping {
+            self.print_sub(sub)?;
+        }
+        Ok(Some(sub))
+    }
+
+    fn print_sub(&mut self, sub: Sub) -> fmt::Result {
+        match sub {
+            Sub::Type(start) => self.replay(start as usize, Self::print_type),
+            Sub::Prefix(start, end) => {
+                self.replay(start as usize, |p| p.prefix(start as usize, Some(end as usize), &mut NameInfo::default()))
+            }
+        }
+    }
+
+    /// Parses a function that is named inside a type, like the function of a local name in a template argument,
+    /// whose template parameters refer to its own template arguments until the type ends.
+    fn own_template_args<T>(&mut self, f: impl FnOnce(&mut Self) -> Parsed<T>) -> Parsed<T> {
+        let type_depth = mem::replace(&mut self.type_depth, 0);
+        let template_args = self.template_args;
+        let parsed = f(self);
+        self.type_depth = type_depth;
+        if type_depth > 0 {
+            self.template_args = template_args;
+        }
+        parsed
+    }
+
+    fn template_args(&mut self) -> fmt::Result {
+        self.expect(b'I')?;
+        if self.type_depth == 0 {
+            self.template_args = Some(self.next);
+        }
+        let last_name = self.last_name;
+        self.print(if self.last == b'<' { " <" } else { "<" })?;
+        self.template_arg_list()?;
+        self.print(if self.last == b'>' { " >" } else { ">" })?;
+        self.last_name = last_name;
+        Ok(())
+    }
+
+    fn template_arg_list(&mut self) -> fmt::Result {
+        self.list(|p| p.eat(b'E'), Self::template_arg)
+    }
+
+    fn template_arg(&mut self) -> fmt::Result {
+        match self.peek().ok_or(fmt::Error)? {
+            b'L' => self.expr_primary(),
+            // Argument packs
+            b'J' => {
+                self.next += 1;
+                self.template_arg_list()
+            }
+            b'X' => Err(fmt::Error),
+            _ => self.print_type(),
+        }
+    }
+
+    /// A literal template argument, or the name of a function or an object.
+    fn expr_primary(&mut self) -> fmt::Result {
+        self.expect(b'L')?;
+        if self.eat(b'_') {
+            self.expect(b'Z')?;
+            self.own_template_args(|p| p.encoding(true))?;
+            return self.expect(b'E');
+        }
+        let suffix = match self.peek().ok_or(fmt::Error)? {
+            b'b' => {
+                self.next += 1;
+                let value = match self.next_byte()? {
+                    b'0' => "false",
+                    b'1' => "true",
+                    _ => return Err(fmt::Error),
+                };
+                self.print(value)?;
+                return self.expect(b'E');
+            }
+            b'i' => "",
+            b'j' => "u",
+            b'l' => "l",
+            b'm' => "ul",
+            b'x' => "ll",
+            b'y' => "ull",
+            b'e' | b'f' | b'd' => {
+                // Floating point values are mangled as the hex of their bytes
+                self.print("(")?;
+                self.print_type()?;
+                self.print(")[")?;
+                let start = self.next;
+                while self.peek().is_some_and(|b| b.is_ascii_hexdigit()) {
+                    self.next += 1;
+                }
+                self.print(str::from_utf8(&self.sym[start..self.next]).map_err(|_| fmt::Error)?)?;
+                self.print("]")?;
+                return self.expect(b'E');
+            }
+            b'D' => return Err(fmt::Error),
+            _ => {
+                self.print("(")?;
+                self.print_type()?;
+                self.print(")")?;
+                let (negative, digits) = self.number()?;
+                self.print(if negative { "-" } else { "" })?;
+                self.print(digits)?;
+                return self.expect(b'E');
+            }
+        };
+        self.next += 1;
+        let (negative, digits) = self.number()?;
+        self.print(if negative { "-" } else { "" })?;
+        self.print(digits)?;
+        self.print(suffix)?;
+        self.expect(b'E')
+    }
+
+    /// Parses a template parameter after its `T`, returning its index.
+    fn template_param_index(&mut self) -> Parsed<usize> {
+        if self.eat(b'_') {
+            return Ok(0);
+        }
+        let n = self.small_number()?;
+        self.expect(b'_')?;
+        n.checked_add(1).ok_or(fmt::Error)
+    }
+
+    /// Where the template argument that parameter `n` refers to starts.
+    fn template_arg_position(&mut self, n: usize) -> Parsed<usize> {
+        let args = self.template_args.ok_or(fmt::Error)?;
+        let skipping = mem::replace(&mut self.skipping, true);
+        let position = self.replay(args, |p| {
+            for _ in 0..n {
+                if p.peek() == Some(b'E') {
+                    return Err(fmt::Error);
+                }
+                p.template_arg()?;
+            }
+            match p.peek() {
+                Some(b'E') | None => Err(fmt::Error),
+                Some(b'J') => match p.expansion {
+                    Expansion::Element(i) => {
+                        p.next += 1;
+                        for _ in 0..i {
+                            if p.peek() == Some(b'E') {
+                                return Err(fmt::Error);
+                            }
+                            p.template_arg()?;
+                        }
+                        match p.peek() {
+                            Some(b'E') | None => Err(fmt::Error),
+                            _ => Ok(p.next),
+                        }
+                    }
+                    _ => Ok(p.next),
+                },
+                _ => Ok(p.next),
+            }
+        });
+        self.skipping = skipping;
+        position
+    }
+
+    fn template_param(&mut self) -> fmt::Result {
+        self.expect(b'T')?;
+        let n = self.template_param_index()?;
+        if let Expansion::Measuring(None) = self.expansion {
+            let position = self.template_arg_position(n)?;
+            if self.sym[position] == b'J' {
+                let len = self.replay(position + 1, |p| {
+                    let mut len = 0;
+                    while !p.eat(b'E') {
+                        p.skip(Self::template_arg)?;
+                        len += 1;
+                    }
+                    Ok(len)
+                })?;
+                self.expansion = Expansion::Measuring(Some(len));
+            }
+        }
+        if self.skipping {
+            return Ok(());
+        }
+        let position = self.template_arg_position(n)?;
+        self.replay(position, Self::template_arg)
+    }
+
+    fn print_type(&mut self) -> fmt::Result {
+        self.nested(|p| {
+            p.type_depth += 1;
+            let printed = p.ty();
+            p.type_depth -= 1;
+            printed
+        })
+    }
+
+    /// Resolves the template parameter or substitution at the parser to the type that it stands for, so that
+    /// the modifiers around it can be printed into the type. Doesn't move the parser if it's anything else.
+    fn referred_type(&mut self) -> Parsed<Option<usize>> {
+        let start = self.next;
+        let target = match self.peek() {
+            Some(b'T') => {
+                self.next += 1;
+                let n = self.template_param_index()?;
+                let mut position = self.template_arg_position(n)?;
+                // A pack of one is as good as its element
+                while self.sym[position] == b'J' && self.sym.get(position + 1) != Some(&b'E') {
+                    let only = self.replay(position + 1, |p| {
+                        p.skip(Self::template_arg)?;
+                        Ok(p.peek() == Some(b'E'))
+                    })?;
+                    if !only {
+                        break;
+                    }
+                    position += 1;
+                }
+                match self.sym[position] {
+                    b'L' | b'X' | b'J' => None,
+                    _ => Some(position),
+                }
+            }
+            Some(b'S') if !matches!(self.peek_second(), Some(b't' | b'a' | b'b' | b's' | b'i' | b'o' | b'd')) => {
+                self.next += 1;
+                let id = self.seq_id()?;
+                match self.subs[..self.sub_count].get(id) {
+                    Some(Sub::Type(position)) => Some(*position as usize),
+                    Some(Sub::Prefix(..)) => None,
+                    None => return Err(fmt::Error),
+                }
+            }
+            _ => None,
+        };
+        // Template template parameters and substitutions with template arguments are types of their own
+        if target.is_none() || self.peek() == Some(b'I') {
+            self.next = start;
+            return Ok(None);
+        }
+        Ok(target)
+    }
+
+    fn ty(&mut self) -> fmt::Result {
+        if self.peek() == Some(b'D') && self.peek_second() == Some(b'p') {
+            return self.pack_expansion();
+        }
+        let mut modifiers = [(Modifier::Pointer, 0); MAX_MODIFIERS];
+        let mut count = 0;
+        // Where to come back to after following template parameters and substitutions into the types they stand for
+        let mut resumed: Option<(usize, bool)> = None;
+        // The modifiers that were parsed before following any, which are the only new substitution candidates
+        let mut own = 0;
+        let mut noexcept = false;
+        loop {
+            let position = self.next;
+            let modifier = match self.peek().ok_or(fmt::Error)? {
+                b'P' => Modifier::Pointer,
+                b'R' => Modifier::Reference,
+                b'O' => Modifier::RvalueReference,
+                b'C' => Modifier::Complex,
+                b'G' => Modifier::Imaginary,
+                b'r' | b'V' | b'K' => {
+                    let cv = self.cv_qualifiers();
+                    self.next -= 1;
+                    Modifier::Cv(cv)
+                }
+                b'D' if self.peek_second() == Some(b'o') && self.sym.get(self.next + 2) == Some(&b'F') => {
+                    self.next += 2;
+                    noexcept = true;
+                    break;
+                }
+                b'M' => {
+                    self.next += 1;
+                    let class = self.next;
+                    self.skip(Self::print_type)?;
+                    self.next -= 1;
+                    Modifier::Member(class)
+                }
+                b'T' | b'S' if !self.skipping => {
+                    self.step()?;
+                    let Some(target) = self.referred_type()? else { break };
+                    if resumed.is_none() {
+                        resumed = Some((self.next, self.replaying));
+                        own = count;
+                        // Unlike substitutions, template parameters are substitution candidates
+                        if self.sym[position] == b'T' {
+                            self.add_sub(Sub::Type(Self::position(position)?))?;
+                        }
+                    }
+                    self.next = target;
+                    self.replaying = true;
+                    continue;
+                }
+                _ => break,
+            };
+            self.next += 1;
+            // References to references that template parameters and substitutions make collapse into one,
+            // which is an rvalue reference only if both are
+            if let Some((outer @ (Modifier::Reference | Modifier::RvalueReference), _)) = modifiers[..count].last_mut() {
+                if matches!(modifier, Modifier::Reference | Modifier::RvalueReference) {
+                    if let Modifier::Reference = modifier {
+                        *outer = Modifier::Reference;
+                    }
+                    continue;
+                }
+            }
+            // So do the qualifiers of a qualified template argument and the ones added to it
+            if let (Some((Modifier::Cv(outer), _)), Modifier::Cv(cv)) = (modifiers[..count].last_mut(), modifier) {
+                *outer |= cv;
+                continue;
+            }
+            *modifiers.get_mut(count).ok_or(fmt::Error)? = (modifier, position);
+            count += 1;
+        }
+        if resumed.is_none() {
+            own = count;
+        }
+        let modifiers = &modifiers[..count];
+
+        let mut base = if noexcept { self.next - 2 } else { self.next };
+        let candidate = match self.peek().ok_or(fmt::Error)? {
+            b'F' => {
+                // Qualifiers added to a function type through a template parameter or a substitution
+                // don't make it a member function type, and it's unclear how they print
+                if resumed.is_some() && own == count && matches!(modifiers.last(), Some((Modifier::Cv(_), _))) {
+                    return Err(fmt::Error);
+                }
+                self.function_type(modifiers, noexcept)?;
+                // The qualifiers of a member function are part of its type, which is a single candidate with them
+                if resumed.is_none() {
+                    while let Some(&(Modifier::Cv(_), position)) = modifiers[..own].last() {
+                        base = position;
+                        own -= 1;
+                    }
+                }
+                true
+            }
+            b'A' => {
+                self.array_type(modifiers)?;
+                true
+            }
+            _ => {
+                let candidate = self.base_type()?;
+                self.print_modifiers(modifiers, false)?;
+                candidate
+            }
+        };
+
+        if let Some((next, replaying)) = resumed {
+            self.next = next;
+            self.replaying = replaying;
+        } else if candidate {
+            self.add_sub(Sub::Type(Self::position(base)?))?;
+        }
+        for &(_, position) in modifiers[..own].iter().rev() {
+            self.add_sub(Sub::Type(Self::position(position)?))?;
+        }
+        Ok(())
+    }
+
+    /// Prints the pattern after `Dp` once for every element of the pack that it refers to.
+    fn pack_expansion(&mut self) -> fmt::Result {
+        let position = self.next;
+        self.next += 2;
+        let pattern = self.next;
+        if !self.skipping {
+            let expansion = mem::replace(&mut self.expansion, Expansion::Measuring(None));
+            self.replay(pattern, |p| p.skip(Self::print_type))?;
+            let Expansion::Measuring(len) = self.expansion else { return Err(fmt::Error) };
+            // Patterns without packs print as they are
+            for i in 0..len.unwrap_or(1) {
+                if i > 0 {
+                    self.print(", ")?;
+                }
+                self.expansion = if len.is_some() { Expansion::Element(i) } else { Expansion::Outside };
+                self.replay(pattern, Self::print_type)?;
+            }
+            self.expansion = expansion;
+        }
+        self.skip(Self::print_type)?;
+        self.add_sub(Sub::Type(Self::position(position)?))
+    }
+
+    /// Prints the types that modifiers can go after, returning whether they're substitution candidates.
+    fn base_type(&mut self) -> Parsed<bool> {
+        let tag = self.peek().ok_or(fmt::Error)?;
+        if let Some(builtin) = builtin_type(tag) {
+            self.next += 1;
+            self.print(builtin)?;
+            return Ok(false);
+        }
+        match tag {
+            b'u' => {
+                // A vendor extended type
+                self.next += 1;
+                let name = self.source_name()?;
+                self.print(name)?;
+                Ok(true)
+            }
+            b'D' => match self.peek_second() {
+                Some(b'F') => {
+                    self.next += 2;
+                    let bits = self.small_number()?;
+                    match self.next_byte()? {
+                        b'_' => self.print_fmt(format_args!("_Float{}", bits))?,
+                        b'x' => self.print_fmt(format_args!("_Float{}x", bits))?,
+                        b'b' if bits == 16 => self.print("std::bfloat16_t")?,
+                        _ => return Err(fmt::Error),
+                    }
+                    Ok(false)
+                }
+                Some(tag) => {
+                    let builtin = builtin_d_type(tag).ok_or(fmt::Error)?;
+                    self.next += 2;
+                    self.print(builtin)?;
+                    Ok(false)
+                }
+                None => Err(fmt::Error),
+            },
+            b'S' if self.peek_second() != Some(b't') => {
+                self.substitution()?;
+                if self.peek() != Some(b'I') {
+                    return Ok(false);
+                }
+                self.template_args()?;
+                Ok(true)
+            }
+            b'T' => {
+                let start = self.next;
+                self.template_param()?;
+                if self.peek() == Some(b'I') {
+                    self.add_sub(Sub::Type(Self::position(start)?))?;
+                    self.template_args()?;
+                }
+                Ok(true)
+            }
+            b'N' | b'Z' | b'S' | b'0'..=b'9' => {
+                self.name()?;
+                Ok(true)
+            }
+            _ => Err(fmt::Error),
+        }
+    }
+
+    /// Prints the modifiers from the innermost outwards. Inside the parentheses of function and array
+    /// types the first one doesn't need a space before it.
+    fn print_modifiers(&mut self, modifiers: &[(Modifier, usize)], parenthesized: bool) -> fmt::Result {
+        for (i, &(modifier, _)) in modifiers.iter().rev().enumerate() {
+            match modifier {
+                Modifier::Pointer => self.print("*")?,
+                Modifier::Reference => self.print("&")?,
+                Modifier::RvalueReference => self.print("&&")?,
+                Modifier::Cv(cv) => self.print_cv(cv)?,
+                Modifier::Complex => self.print(" _Complex")?,
+                Modifier::Imaginary => self.print(" _Imaginary")?,
+                Modifier::Member(class) => {
+                    if !(parenthesized && i == 0) {
+                        self.print(" ")?;
+                    }
+                    self.replay(class, Self::print_type)?;
+                    self.print("::*")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn function_type(&mut self, modifiers: &[(Modifier, usize)], noexcept: bool) -> fmt::Result {
+        self.expect(b'F')?;
+        // extern "C"
+        self.eat(b'Y');
+        // The qualifiers right around a function type are those of a member function
+        let mut cv = 0;
+        let mut outer = modifiers;
+        while let Some(((Modifier::Cv(qualifiers), _), rest)) = outer.split_last() {
+            cv |= *qualifiers;
+            outer = rest;
+        }
+        let last_name = self.last_name;
+        self.print_type()?;
+        if outer.is_empty() {
+            self.print(" ")?;
+        } else {
+            self.print(" (")?;
+            self.print_modifiers(outer, true)?;
+            self.print(")")?;
+        }
+        self.params()?;
+        self.last_name = last_name;
+        self.print_cv(cv)?;
+        if self.eat(b'R') {
+            self.print(" &")?;
+        } else if self.eat(b'O') {
+            self.print(" &&")?;
+        }
+        if noexcept {
+            self.print(" noexcept")?;
+        }
+        self.expect(b'E')
+    }
+
+    /// The number of elements of an array type, which can be left out or be a template parameter.
+    fn dimension(&mut self) -> fmt::Result {
+        match self.peek().ok_or(fmt::Error)? {
+            b'_' => (),
+            b'T' => self.template_param()?,
+            _ => {
+                let digits = self.number()?.1;
+                self.print(digits)?;
+            }
+        }
+        self.expect(b'_')
+    }
+
+    fn array_type(&mut self, modifiers: &[(Modifier, usize)]) -> fmt::Result {
+        // The dimensions of arrays of arrays print together after the element type
+        let mut dimensions = [0; MAX_MODIFIERS];
+        let mut count = 0;
+        while self.peek() == Some(b'A') {
+            *dimensions.get_mut(count).ok_or(fmt::Error)? = self.next;
+            count += 1;
+            self.next += 1;
+            self.skip(Self::dimension)?;
+        }
+        // Qualified arrays are arrays of qualified elements
+        let mut cv = 0;
+        let mut outer = modifiers;
+        while let Some(((Modifier::Cv(qualifiers), _), rest)) = outer.split_last() {
+            cv |= *qualifiers;
+            outer = rest;
+        }
+        self.print_type()?;
+        self.print_cv(cv)?;
+        if !outer.is_empty() {
+            self.print(" (")?;
+            self.print_modifiers(outer, true)?;
+            self.print(")")?;
+        }
+        self.print(" ")?;
+        for &position in &dimensions[..count] {
+            self.print("[")?;
+            self.replay(position + 1, Self::dimension)?;
+            self.print("]")?;
+        }
+        // The outermost array is added by the caller
+        for &position in dimensions[1..count].iter().rev() {
+            self.add_sub(Sub::Type(Self::position(position)?))?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn names() {
+    let check = |mangled: &str, expected: &str| assert_eq!(format!("{}", demangle(mangled.as_bytes()).unwrap()), expected);
+    check("_ZNSt6vectorIiSaIiEE9push_backERKi", "std::vector<int, std::allocator<int> >::push_back(int const&)");
+    check(
+        "_ZNKSt7__cxx1112basic_stringIcSt11char_traitsIcESaIcEE4findEPKcmm",
+        "std::__cxx11::basic_string<char, std::char_traits<char>, std::allocator<char> >::find(char const*, unsigned long, unsigned long) const",
+    );
+    check(
+        "_ZN9__gnu_cxx17__normal_iteratorIPcSsEC1ERKS1_",
+        "__gnu_cxx::__normal_iterator<char*, std::basic_string<char, std::char_traits<char>, std::allocator<char> > >::__normal_iterator(char* const&)",
+    );
+    check("_ZN3FooC2ERKS_", "Foo::Foo(Foo const&)");
+    check("_ZN3FooD0Ev", "Foo::~Foo()");
+    check("_ZN12_GLOBAL__N_14Anon1fEi", "(anonymous namespace)::Anon::f(int)");
+    check("_ZN6Tagged1fB5cxx11Ev", "Tagged::f[abi:cxx11]()");
+    // Operators
+    check("_ZltI3FooEbRKT_S3_", "bool operator< <Foo>(Foo const&, Foo const&)");
+    check("_ZNK3FoocvbEv", "Foo::operator bool() const");
+    check("_ZN3FooaSEOS_", "Foo::operator=(Foo&&)");
+    check("_ZN2OpclEi", "Op::operator()(int)");
+    check("_ZN2OpnwEm", "Op::operator new(unsigned long)");
+    check("_ZN2OppmEi", "Op::operator->*(int)");
+    // Templates, whose return types are mangled
+    check("_ZN1a1b1CIiE1dIlEEvT_i", "void a::b::C<int>::d<long>(long, int)");
+    check("_Z4litsILb1ELln3ELc97ELy7EEvv", "void lits<true, -3l, (char)97, 7ull>()");
+    check("_Z2ttI1WEvS0_IiE", "void tt<W>(W<int>)");
+    check("_Z1fISaIcEEvv", "void f<std::allocator<char> >()");
+    check("_Z4packIJiRcEEvDpOT_", "void pack<int, char&>(int&&, char&)");
+    check("_Z4packIJEEvDpOT_", "void pack<>()");
+    // Local names
+    check("_ZZ1fvENKUlvE_clEv", "f()::{lambda()#1}::operator()() const");
+    check("_ZZ1fIiEvvE1x", "f<int>()::x");
+    check("_ZZ5localvEN1L1fEv", "local()::L::f()");
+    // Template parameters in a local name in template arguments refer to its own function's
+    check(
+        "_Z4sortIZ8legalizeIPiEv3RefIT_EEUlS1_E_EvS3_",
+        "void sort<legalize<int*>(Ref<int*>)::{lambda(int*)#1}>(legalize<int*>(Ref<int*>)::{lambda(int*)#1})",
+    );
+    // The copies that optimizations make, and Mach-O's extra underscore
+    check("_Z1fIiEvT_.constprop.0.isra.0", "void f<int>(int) [clone .constprop.0] [clone .isra.0]");
+    check("__Z1fv.cold", "f() [clone .cold]");
+    check("_Z3foo", "foo");
+
+    assert_eq!(demangle(b"_Z"), None);
+    assert_eq!(demangle(b"_ZNE"), None);
+    assert_eq!(demangle(b"_Z1fS_"), None);
+    assert_eq!(demangle(b"_Z1fIT_EvT_"), None);
+    assert_eq!(demangle(b"_Z1f3"), None);
+    assert_eq!(demangle(b"_Z1fv.."), None);
+    assert_eq!(demangle(b"main"), None);
+}
+
+#[test]
+fn types() {
+    let check = |mangled: &str, expected: &str| assert_eq!(format!("{}", demangle(mangled.as_bytes()).unwrap()), expected);
+    check("_Z1fKPFviE", "f(void (* const)(int))");
+    check("_Z1fM3FooKFviE", "f(void (Foo::*)(int) const)");
+    check("_Z1fM3FooFivEPS1_", "f(int (Foo::*)(), int (Foo::**)())");
+    check(
+        "_Z2faPA4_iPA3_iPFvizEM3FooiMS5_KFidRE",
+        "fa(int (*) [4], int (*) [3], void (*)(int, ...), int Foo::*, int (Foo::*)(double) const &)",
+    );
+    check("_Z3arrILi3EEiRAT__i", "int arr<3>(int (&) [3])");
+    check("_Z3noxPDoFvvE", "nox(void (*)() noexcept)");
+    check("_Z1fDF16_DF32xDF16b", "f(_Float16, _Float32x, std::bfloat16_t)");
+    // References to references collapse, and so do qualifiers
+    check("_Z1fIRiEvOT_", "void f<int&>(int&)");
+    check("_Z1fIK1LEvRKT_", "void f<L const>(L const&)");
+    // The qualifiers of arrays are those of their elements
+    check("_Z1fKA3_i", "f(int const [3])");
+    check("_Z5applyIA17_cEvRKT_", "void apply<char [17]>(char const (&) [17])");
+    // Qualified function types are a single substitution candidate
+    check("_Z1fM1SKFbvES0_S1_", "f(bool (S::*)() const, bool () const, bool (S::*)() const)");
+    check("_Z3memI1SKFbvEEvPT_MS0_T0_S3_", "void mem<S, bool () const>(S*, bool (S::*)() const, S*)");
+    assert_eq!(demangle(b"_Z1fIFvvEEvPKT_"), None);
+}
+
+#[test]
+fn special_names() {
+    let check = |mangled: &str, expected: &str| assert_eq!(format!("{}", demangle(mangled.as_bytes()).unwrap()), expected);
+    check("_ZTV3Foo", "vtable for Foo");
+    check("_ZTT3Bar", "VTT for Bar");
+    check("_ZTI3Bar", "typeinfo for Bar");
+    check("_ZTS3Bar", "typeinfo name for Bar");
+    check("_ZThn8_N3Bar1hEv", "non-virtual thunk to Bar::h()");
+    check("_ZTv0_n24_N3FooD1Ev", "virtual thunk to Foo::~Foo()");
+    check("_ZTC3Foo0_3Bar", "construction vtable for Bar-in-Foo");
+    check("_ZGVZ3lamvE3cnt", "guard variable for lam()::cnt");
+    check("_ZTH2tl", "TLS init function for tl");
+    check("_ZTW2tl", "TLS wrapper function for tl");
+    check("_ZGTtnam", "transaction clone for operator new[](unsigned long)");
+}
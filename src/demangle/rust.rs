@@ -0,0 +1,837 @@
+//! Rust symbol names, both in the legacy scheme that borrows C++'s `_ZN...E` and in the v0 scheme that starts
+//! with `_R`. Like rustc's own demangler, the alternate form `{:#}` leaves out the hashes, the crate
+//! disambiguators and the types of constants. Legacy names are told apart from C++ names by the hash that
+//! rustc always ends them with.
+
+use core::{
+    fmt::{self, Write},
+    mem, str,
+};
+
+use super::{Limited, Sink, MAX_LEN};
+
+/// A Rust symbol name that can be displayed demangled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Demangle<'a> {
+    scheme: Scheme<'a>,
+    /// What was appended to the name after mangling, like the `.0` of promoted constants.
+    suffix: &'a str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scheme<'a> {
+    /// The `<length><element>` pairs between `_ZN` and `E`.
+    Legacy(&'a [u8]),
+    /// Everything after `_R`, which is where the positions of backreferences count from.
+    V0(&'a [u8]),
+}
+
+/// Recognizes a Rust symbol name, or returns `None` for anything that doesn't demangle.
+/// Mach-O's extra leading underscore is accepted.
+pub fn demangle(name: &[u8]) -> Option<Demangle<'_>> {
+    let name = name.strip_prefix(b"_")?;
+    let name = name.strip_prefix(b"_").unwrap_or(name);
+    let (scheme, suffix) = if let Some(rest) = name.strip_prefix(b"ZN") {
+        let end = legacy_end(rest)?;
+        (Scheme::Legacy(&rest[..end]), &rest[end + 1..])
+    } else if let Some(rest) = name.strip_prefix(b"R") {
+        // v0 names are made of only letters, digits and underscores
+        let end = rest.iter().position(|&b| b == b'.').unwrap_or(rest.len());
+        (Scheme::V0(&rest[..end]), &rest[end..])
+    } else {
+        return None;
+    };
+    let suffix = match suffix {
+        // LLVM's suffixes for the copies of functions in other codegen units mean nothing to readers
+        _ if suffix.starts_with(b".llvm.") => "",
+        [] | [b'.', ..] => str::from_utf8(suffix).ok()?,
+        _ => return None,
+    };
+    let demangle = Demangle { scheme, suffix };
+    write!(Sink, "{}", demangle).ok()?;
+    Some(demangle)
+}
+
+impl fmt::Display for Demangle<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let alternate = f.alternate();
+        let mut out = Limited { out: f, written: 0 };
+        match self.scheme {
+            Scheme::Legacy(elements) => legacy(elements, alternate, &mut out)?,
+            Scheme::V0(sym) => {
+                let mut printer = Printer { sym, next: 0, out, alternate, skipping: false, depth: 0, bound_lifetimes: 0 };
+                printer.symbol()?;
+                out = printer.out;
+            }
+        }
+        out.write_str(self.suffix)
+    }
+}
+
+/// Splits a decimal length off the front of `bytes`.
+fn decimal(bytes: &[u8]) -> Option<(usize, &[u8])> {
+    let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    let len = bytes[..digits].iter().try_fold(0usize, |len, b| len.checked_mul(10)?.checked_add((b - b'0') as usize))?;
+    Some((len, &bytes[digits..]))
+}
+
+/// The offset of the `E` that ends the legacy elements in `rest`, which have to be a path and a hash.
+fn legacy_end(rest: &[u8]) -> Option<usize> {
+    let mut tail = rest;
+    let mut count = 0;
+    let mut last: &[u8] = &[];
+    while *tail.first()? != b'E' {
+        let (len, element) = decimal(tail)?;
+        if len > element.len() || !element[..len].is_ascii() {
+            return None;
+        }
+        (last, tail) = element.split_at(len);
+        count += 1;
+    }
+    Some(rest.len() - tail.len()).filter(|_| count > 1 && is_hash(last))
+}
+
+struct Elements<'a>(&'a [u8]);
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let (len, rest) = decimal(self.0)?;
+        let (element, rest) = rest.split_at(len);
+        self.0 = rest;
+        Some(element)
+    }
+}
+
+/// The `h` and 16 hex digits that the legacy scheme appends as the last element.
+fn is_hash(element: &[u8]) -> bool {
+    element.len() == 17 && element[0] == b'h' && element[1..].iter().all(u8::is_ascii_hexdigit)
+}
+
+fn legacy(elements: &[u8], alternate: bool, out: &mut impl Write) -> fmt::Result {
+    let count = Elements(elements).count();
+    for (i, element) in Elements(elements).enumerate() {
+        if alternate && i + 1 == count {
+            break;
+        }
+        if i > 0 {
+            out.write_str("::")?;
+        }
+        legacy_element(element, out)?;
+    }
+    Ok(())
+}
+
+/// Writes an element with its `$...$` escapes and `..` separators replaced.
+fn legacy_element(element: &[u8], out: &mut impl Write) -> fmt::Result {
+    // An underscore is prepended to elements that would start with an escape
+    let mut rest = if element.starts_with(b"_$") { &element[1..] } else { element };
+    while let Some(&b) = rest.first() {
+        rest = match b {
+            b'.' if rest.get(1) == Some(&b'.') => {
+                out.write_str("::")?;
+                &rest[2..]
+            }
+            b'$' => {
+                let end = rest[1..].iter().position(|&b| b == b'$').ok_or(fmt::Error)? + 1;
+                let c = match &rest[1..end] {
+                    b"SP" => '@',
+                    b"BP" => '*',
+                    b"RF" => '&',
+                    b"LT" => '<',
+                    b"GT" => '>',
+                    b"LP" => '(',
+                    b"RP" => ')',
+                    b"C" => ',',
+                    [b'u', hex @ ..] => hex_value(hex)
+                        .and_then(|c| char::from_u32(c.try_into().ok()?))
+                        .filter(|c| !c.is_control())
+                        .ok_or(fmt::Error)?,
+                    _ => return Err(fmt::Error),
+                };
+                out.write_char(c)?;
+                &rest[end + 1..]
+            }
+            _ => {
+                let end = rest[1..].iter().position(|&b| b == b'$' || b == b'.').map_or(rest.len(), |end| end + 1);
+                out.write_str(str::from_utf8(&rest[..end]).map_err(|_| fmt::Error)?)?;
+                &rest[end..]
+            }
+        };
+    }
+    Ok(())
+}
+
+/// The value of up to 16 lowercase hex digits.
+fn hex_value(hex: &[u8]) -> Option<u64> {
+    if hex.is_empty() || hex.len() > 16 {
+        return None;
+    }
+    hex.iter().try_fold(0, |value, &b| {
+        let digit = match b {
+            b'0'..=b'9' => b - b'0',
+            b'a'..=b'f' => b - b'a' + 10,
+            _ => return None,
+        };
+        Some(value << 4 | digit as u64)
+    })
+}
+
+/// How deep paths and types can nest in a v0 name, including through backreferences.
+const MAX_DEPTH: u32 = 500;
+
+/// The most characters that a punycode identifier can decode to.
+const MAX_PUNYCODE: usize = 128;
+
+/// An identifier of a v0 name, with the non-ASCII characters punycode encoded.
+#[derive(Debug, Clone, Copy)]
+struct Ident<'s> {
+    ascii: &'s str,
+    punycode: &'s str,
+}
+
+impl Ident<'_> {
+    fn is_empty(&self) -> bool {
+        self.ascii.is_empty() && self.punycode.is_empty()
+    }
+}
+
+impl fmt::Display for Ident<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.punycode.is_empty() {
+            return f.write_str(self.ascii);
+        }
+        let mut chars = ['\0'; MAX_PUNYCODE];
+        match punycode(self.ascii, self.punycode, &mut chars) {
+            Some(len) => chars[..len].iter().try_for_each(|&c| f.write_char(c)),
+            None if self.ascii.is_empty() => write!(f, "punycode{{{}}}", self.punycode),
+            None => write!(f, "punycode{{{}-{}}}", self.ascii, self.punycode),
+        }
+    }
+}
+
+/// Decodes RFC 3492 punycode into `out`, returning the number of characters.
+fn punycode(ascii: &str, punycode: &str, out: &mut [char]) -> Option<usize> {
+    let mut len = 0;
+    for c in ascii.chars() {
+        *out.get_mut(len)? = c;
+        len += 1;
+    }
+    let (mut i, mut n, mut bias) = (0usize, 0x80u32, 72usize);
+    let mut digits = punycode.bytes().peekable();
+    let mut first = true;
+    while digits.peek().is_some() {
+        let (mut delta, mut w, mut k) = (0usize, 1usize, 0usize);
+        loop {
+            k += 36;
+            let t = k.saturating_sub(bias).clamp(1, 26);
+            let digit = match digits.next()? {
+                d @ b'a'..=b'z' => d - b'a',
+                d @ b'0'..=b'9' => d - b'0' + 26,
+                _ => return None,
+            } as usize;
+            delta = delta.checked_add(digit.checked_mul(w)?)?;
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(36 - t)?;
+        }
+
+        len += 1;
+        if len > out.len() {
+            return None;
+        }
+        i = i.checked_add(delta)?;
+        n = n.checked_add((i / len).try_into().ok()?)?;
+        i %= len;
+        out.copy_within(i..len - 1, i + 1);
+        out[i] = char::from_u32(n)?;
+        i += 1;
+
+        delta /= if first { 700 } else { 2 };
+        first = false;
+        delta += delta / len;
+        k = 0;
+        while delta > 35 * 26 / 2 {
+            delta /= 35;
+            k += 36;
+        }
+        bias = k + 36 * delta / (delta + 38);
+    }
+    Some(len)
+}
+
+fn basic_type(tag: u8) -> Option<&'static str> {
+    Some(match tag {
+        b'a' => "i8",
+        b'b' => "bool",
+        b'c' => "char",
+        b'd' => "f64",
+        b'e' => "str",
+        b'f' => "f32",
+        b'h' => "u8",
+        b'i' => "isize",
+        b'j' => "usize",
+        b'l' => "i32",
+        b'm' => "u32",
+        b'n' => "i128",
+        b'o' => "u128",
+        b'p' => "_",
+        b's' => "i16",
+        b't' => "u16",
+        b'u' => "()",
+        b'v' => "...",
+        b'x' => "i64",
+        b'y' => "u64",
+        b'z' => "!",
+        _ => return None,
+    })
+}
+
+/// Parses a v0 name and prints it at the same time. All of the parse errors are `fmt::Error`s,
+/// which `demangle` turns into `None` by printing every name into a `Sink` first.
+struct Printer<'s, 'w, W: Write> {
+    sym: &'s [u8],
+    next: usize,
+    out: Limited<'w, W>,
+    alternate: bool,
+    /// Set while going over the parts that aren't printed, like the paths of impls.
+    skipping: bool,
+    depth: u32,
+    /// The lifetimes bound by `for<...>` around the current type.
+    bound_lifetimes: u64,
+}
+
+type Parsed<T = ()> = Result<T, fmt::Error>;
+
+impl<'s, W: Write> Printer<'s, '_, W> {
+    fn peek(&self) -> Option<u8> {
+        self.sym.get(self.next).copied()
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        let eaten = self.peek() == Some(b);
+        self.next += eaten as usize;
+        eaten
+    }
+
+    fn next_byte(&mut self) -> Parsed<u8> {
+        let b = self.peek().ok_or(fmt::Error)?;
+        self.next += 1;
+        Ok(b)
+    }
+
+    fn print(&mut self, s: impl fmt::Display) -> fmt::Result {
+        match self.skipping {
+            true => Ok(()),
+            false => write!(self.out, "{}", s),
+        }
+    }
+
+    /// A base 62 number terminated by `_`, where the plain `_` is 0 and the digits encode one less.
+    fn integer_62(&mut self) -> Parsed<u64> {
+        if self.eat(b'_') {
+            return Ok(0);
+        }
+        let mut x = 0u64;
+        while !self.eat(b'_') {
+            let digit = match self.next_byte()? {
+                b @ b'0'..=b'9' => b - b'0',
+                b @ b'a'..=b'z' => b - b'a' + 10,
+                b @ b'A'..=b'Z' => b - b'A' + 36,
+                _ => return Err(fmt::Error),
+            };
+            x = x.checked_mul(62).and_then(|x| x.checked_add(digit as u64)).ok_or(fmt::Error)?;
+        }
+        x.checked_add(1).ok_or(fmt::Error)
+    }
+
+    /// An optional `integer_62` after `tag`, where its absence is 0.
+    fn opt_integer_62(&mut self, tag: u8) -> Parsed<u64> {
+        if !self.eat(tag) {
+            return Ok(0);
+        }
+        self.integer_62()?.checked_add(1).ok_or(fmt::Error)
+    }
+
+    fn disambiguator(&mut self) -> Parsed<u64> {
+        self.opt_integer_62(b's')
+    }
+
+    fn hex_nibbles(&mut self) -> Parsed<&'s [u8]> {
+        let start = self.next;
+        while !self.eat(b'_') {
+            if !matches!(self.next_byte()?, b'0'..=b'9' | b'a'..=b'f') {
+                return Err(fmt::Error);
+            }
+        }
+        Ok(&self.sym[start..self.next - 1])
+    }
+
+    fn ident(&mut self) -> Parsed<Ident<'s>> {
+        let is_punycode = self.eat(b'u');
+        // Empty identifiers are a single 0, which the next identifier's length may follow right away
+        let len = match self.eat(b'0') {
+            true => 0,
+            false => {
+                let (len, rest) = decimal(&self.sym[self.next..]).ok_or(fmt::Error)?;
+                self.next = self.sym.len() - rest.len();
+                len
+            }
+        };
+        // Separates the length from identifiers that start with a digit or an underscore
+        self.eat(b'_');
+        let end = self.next.checked_add(len).filter(|&end| end <= self.sym.len()).ok_or(fmt::Error)?;
+        let ident = str::from_utf8(&self.sym[self.next..end]).map_err(|_| fmt::Error)?;
+        self.next = end;
+        if !is_punycode {
+            return Ok(Ident { ascii: ident, punycode: "" });
+        }
+        let ident = match ident.rfind('_') {
+            Some(i) => Ident { ascii: &ident[..i], punycode: &ident[i + 1..] },
+            None => Ident { ascii: "", punycode: ident },
+        };
+        match ident.punycode.is_empty() {
+            true => Err(fmt::Error),
+            false => Ok(ident),
+        }
+    }
+
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Parsed<T>) -> Parsed<T> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(fmt::Error);
+        }
+        let parsed = f(self);
+        self.depth -= 1;
+        parsed
+    }
+
+    fn skip<T>(&mut self, f: impl FnOnce(&mut Self) -> Parsed<T>) -> Parsed<T> {
+        let skipping = mem::replace(&mut self.skipping, true);
+        let parsed = f(self);
+        self.skipping = skipping;
+        parsed
+    }
+
+    /// Follows the backreference after a just eaten `B` to an earlier position of the name.
+    fn backref<T: Default>(&mut self, f: impl FnOnce(&mut Self) -> Parsed<T>) -> Parsed<T> {
+        let start = self.next - 1;
+        let target = self.integer_62()?;
+        if target >= start as u64 {
+            return Err(fmt::Error);
+        }
+        if self.skipping {
+            return Ok(T::default());
+        }
+        let next = mem::replace(&mut self.next, target as usize);
+        let parsed = f(self);
+        self.next = next;
+        parsed
+    }
+
+    /// Prints the items until the `E` that ends the list, returning their number.
+    fn list(&mut self, f: impl Fn(&mut Self) -> Parsed, separator: &str) -> Parsed<usize> {
+        let mut n = 0;
+        while !self.eat(b'E') {
+            if n > 0 {
+                self.print(separator)?;
+            }
+            f(self)?;
+            n += 1;
+        }
+        Ok(n)
+    }
+
+    fn symbol(&mut self) -> fmt::Result {
+        // The encoding version would come first as a decimal number, but only the one without it exists
+        if self.peek().is_none_or(|b| b.is_ascii_digit()) {
+            return Err(fmt::Error);
+        }
+        self.print_path(true)?;
+        // The crate that instantiated the item is only of interest to the linker
+        if self.peek().is_some_and(|b| b.is_ascii_uppercase()) {
+            self.skip(|p| p.print_path(false))?;
+        }
+        match self.next == self.sym.len() {
+            true => Ok(()),
+            false => Err(fmt::Error),
+        }
+    }
+
+    /// Values, unlike types, get their generic arguments after a `::`.
+    fn print_path(&mut self, in_value: bool) -> fmt::Result {
+        self.nested(|p| p.path(in_value))
+    }
+
+    fn path(&mut self, in_value: bool) -> fmt::Result {
+        match self.next_byte()? {
+            b'C' => {
+                let disambiguator = self.disambiguator()?;
+                let name = self.ident()?;
+                self.print(name)?;
+                if !self.alternate && disambiguator != 0 {
+                    self.print(format_args!("[{:x}]", disambiguator))?;
+                }
+            }
+            b'N' => {
+                let namespace = self.next_byte()?;
+                if !namespace.is_ascii_alphabetic() {
+                    return Err(fmt::Error);
+                }
+                self.print_path(in_value)?;
+                let disambiguator = self.disambiguator()?;
+                let name = self.ident()?;
+                if namespace.is_ascii_uppercase() {
+                    // The special namespaces, which print even without a name
+                    match namespace {
+                        b'C' => self.print("::{closure")?,
+                        b'S' => self.print("::{shim")?,
+                        _ => self.print(format_args!("::{{{}", namespace as char))?,
+                    }
+                    if !name.is_empty() {
+                        self.print(format_args!(":{}", name))?;
+                    }
+                    self.print(format_args!("#{}}}", disambiguator))?;
+                } else if !name.is_empty() {
+                    self.print(format_args!("::{}", name))?;
+                }
+            }
+            tag @ (b'M' | b'X' | b'Y') => {
+                if tag != b'Y' {
+                    self.disambiguator()?;
+                    self.skip(|p| p.print_path(false))?;
+                }
+                self.print("<")?;
+                self.print_type()?;
+                if tag != b'M' {
+                    self.print(" as ")?;
+                    self.print_path(false)?;
+                }
+                self.print(">")?;
+            }
+            b'I' => {
+                self.print_path(in_value)?;
+                self.print(if in_value { "::<" } else { "<" })?;
+                self.list(Self::print_generic_arg, ", ")?;
+                self.print(">")?;
+            }
+            b'B' => self.backref(|p| p.print_path(in_value))?,
+            _ => return Err(fmt::Error),
+        }
+        Ok(())
+    }
+
+    fn print_generic_arg(&mut self) -> fmt::Result {
+        if self.eat(b'L') {
+            let lifetime = self.integer_62()?;
+            self.print_lifetime(lifetime)
+        } else if self.eat(b'K') {
+            self.print_const()
+        } else {
+            self.print_type()
+        }
+    }
+
+    /// Prints a lifetime by its De Bruijn index, counting outwards from the innermost bound one.
+    fn print_lifetime(&mut self, lifetime: u64) -> fmt::Result {
+        if lifetime == 0 {
+            return self.print("'_");
+        }
+        let depth = self.bound_lifetimes.checked_sub(lifetime).ok_or(fmt::Error)?;
+        match depth {
+            0..=25 => self.print(format_args!("'{}", (b'a' + depth as u8) as char)),
+            _ => self.print(format_args!("'_{}", depth)),
+        }
+    }
+
+    /// Prints the `for<...>` of an optional binder before `f`, with its lifetimes bound while in `f`.
+    fn in_binder(&mut self, f: impl FnOnce(&mut Self) -> fmt::Result) -> fmt::Result {
+        let bound = self.opt_integer_62(b'G')?;
+        if bound > MAX_LEN as u64 {
+            return Err(fmt::Error);
+        }
+        if bound > 0 {
+            self.print("for<")?;
+            for i in 0..bound {
+                if i > 0 {
+                    self.print(", ")?;
+                }
+                self.bound_lifetimes += 1;
+                self.print_lifetime(1)?;
+            }
+            self.print("> ")?;
+        }
+        let printed = f(self);
+        self.bound_lifetimes -= bound;
+        printed
+    }
+
+    fn print_type(&mut self) -> fmt::Result {
+        self.nested(Self::ty)
+    }
+
+    fn ty(&mut self) -> fmt::Result {
+        let tag = self.next_byte()?;
+        if let Some(ty) = basic_type(tag) {
+            return self.print(ty);
+        }
+        match tag {
+            b'R' | b'Q' => {
+                self.print("&")?;
+                if self.eat(b'L') {
+                    let lifetime = self.integer_62()?;
+                    if lifetime != 0 {
+                        self.print_lifetime(lifetime)?;
+                        self.print(" ")?;
+                    }
+                }
+                if tag == b'Q' {
+                    self.print("mut ")?;
+                }
+                self.print_type()
+            }
+            b'P' => {
+                self.print("*const ")?;
+                self.print_type()
+            }
+            b'O' => {
+                self.print("*mut ")?;
+                self.print_type()
+            }
+            b'A' | b'S' => {
+                self.print("[")?;
+                self.print_type()?;
+                if tag == b'A' {
+                    self.print("; ")?;
+                    self.print_const()?;
+                }
+                self.print("]")
+            }
+            b'T' => {
+                self.print("(")?;
+                if self.list(Self::print_type, ", ")? == 1 {
+                    self.print(",")?;
+                }
+                self.print(")")
+            }
+            b'F' => self.in_binder(|p| {
+                let is_unsafe = p.eat(b'U');
+                let abi = match p.eat(b'K') {
+                    true if p.eat(b'C') => Some("C"),
+                    true => match p.ident()? {
+                        Ident { ascii, punycode: "" } => Some(ascii),
+                        _ => return Err(fmt::Error),
+                    },
+                    false => None,
+                };
+                if is_unsafe {
+                    p.print("unsafe ")?;
+                }
+                if let Some(abi) = abi {
+                    // Dashes, like in `extern "C-unwind"`, are mangled as underscores
+                    p.print("extern \"")?;
+                    for (i, part) in abi.split('_').enumerate() {
+                        p.print(if i > 0 { "-" } else { "" })?;
+                        p.print(part)?;
+                    }
+                    p.print("\" ")?;
+                }
+                p.print("fn(")?;
+                p.list(Self::print_type, ", ")?;
+                p.print(")")?;
+                if !p.eat(b'u') {
+                    p.print(" -> ")?;
+                    p.print_type()?;
+                }
+                Ok(())
+            }),
+            b'D' => {
+                self.print("dyn ")?;
+                self.in_binder(|p| p.list(Self::print_dyn_trait, " + ").map(drop))?;
+                if !self.eat(b'L') {
+                    return Err(fmt::Error);
+                }
+                let lifetime = self.integer_62()?;
+                if lifetime != 0 {
+                    self.print(" + ")?;
+                    self.print_lifetime(lifetime)?;
+                }
+                Ok(())
+            }
+            b'B' => self.backref(Self::print_type),
+            _ => {
+                self.next -= 1;
+                self.print_path(false)
+            }
+        }
+    }
+
+    /// Prints a trait of a `dyn` type, along with its associated type bindings like `Output = ()`.
+    fn print_dyn_trait(&mut self) -> fmt::Result {
+        let mut open = self.print_path_maybe_open_generics()?;
+        while self.eat(b'p') {
+            self.print(if open { ", " } else { "<" })?;
+            open = true;
+            let name = self.ident()?;
+            self.print(format_args!("{} = ", name))?;
+            self.print_type()?;
+        }
+        if open {
+            self.print(">")?;
+        }
+        Ok(())
+    }
+
+    /// Prints a path, leaving its generic arguments unclosed so that the bindings can go into them.
+    fn print_path_maybe_open_generics(&mut self) -> Parsed<bool> {
+        if self.eat(b'B') {
+            self.backref(Self::print_path_maybe_open_generics)
+        } else if self.eat(b'I') {
+            self.print_path(false)?;
+            self.print("<")?;
+            self.list(Self::print_generic_arg, ", ")?;
+            Ok(true)
+        } else {
+            self.print_path(false)?;
+            Ok(false)
+        }
+    }
+
+    fn print_const(&mut self) -> fmt::Result {
+        self.nested(Self::constant)
+    }
+
+    fn constant(&mut self) -> fmt::Result {
+        match self.next_byte()? {
+            b'p' => self.print("_"),
+            b'B' => self.backref(Self::print_const),
+            b'b' => match self.hex_nibbles()? {
+                b"0" => self.print("false"),
+                b"1" => self.print("true"),
+                _ => Err(fmt::Error),
+            },
+            b'c' => {
+                let c = hex_value(self.hex_nibbles()?).and_then(|c| char::from_u32(c.try_into().ok()?));
+                self.print(format_args!("{:?}", c.ok_or(fmt::Error)?))
+            }
+            tag @ (b'a' | b's' | b'l' | b'x' | b'n' | b'i' | b'h' | b't' | b'm' | b'y' | b'o' | b'j') => {
+                if matches!(tag, b'a' | b's' | b'l' | b'x' | b'n' | b'i') && self.eat(b'n') {
+                    self.print("-")?;
+                }
+                let hex = self.hex_nibbles()?;
+                match hex_value(hex) {
+                    Some(value) => self.print(value)?,
+                    None if hex.is_empty() => return Err(fmt::Error),
+                    // Wider than 64 bits
+                    None => self.print(format_args!("0x{}", str::from_utf8(hex).map_err(|_| fmt::Error)?))?,
+                }
+                match self.alternate {
+                    true => Ok(()),
+                    false => self.print(basic_type(tag).unwrap_or_default()),
+                }
+            }
+            _ => Err(fmt::Error),
+        }
+    }
+}
+
+#[test]
+fn legacy_names() {
+    let check = |mangled: &str, expected: &str, alternate: &str| {
+        let demangled = demangle(mangled.as_bytes()).unwrap();
+        assert_eq!(format!("{}", demangled), expected);
+        assert_eq!(format!("{:#}", demangled), alternate);
+    };
+    check("_ZN4core3fmt5write17h0123456789abcdefE", "core::fmt::write::h0123456789abcdef", "core::fmt::write");
+    check("_ZN1a17Foo$ue9$$LT$T$GT$4show17h690e0d768509c455E", "a::Fooé<T>::show::h690e0d768509c455", "a::Fooé<T>::show");
+    check(
+        "_ZN4core3ptr135drop_in_place$LT$a..Foo$ue9$$LT$std..collections..hash..map..HashMap$LT$$RF$str$C$alloc..vec..Vec$LT$$LP$i32$C$char$RP$$GT$$GT$$GT$$GT$17hec51e02e0707c75cE",
+        "core::ptr::drop_in_place<a::Fooé<std::collections::hash::map::HashMap<&str,alloc::vec::Vec<(i32,char)>>>>::hec51e02e0707c75c",
+        "core::ptr::drop_in_place<a::Fooé<std::collections::hash::map::HashMap<&str,alloc::vec::Vec<(i32,char)>>>>",
+    );
+    check("_ZN13_$LT$test$GT$3foo17h0123456789abcdefE", "<test>::foo::h0123456789abcdef", "<test>::foo");
+    check("_ZN8$BP$test4foob17h0123456789abcdefE", "*test::foob::h0123456789abcdef", "*test::foob");
+    // Mach-O's extra underscore and LLVM's suffix
+    check("__ZN3std2rt10lang_start17h0123456789abcdefE.llvm.1234", "std::rt::lang_start::h0123456789abcdef", "std::rt::lang_start");
+
+    assert_eq!(demangle(b"_ZN3fooE3bar"), None);
+    assert_eq!(demangle(b"_ZN3foo"), None);
+    assert_eq!(demangle(b"_ZN5$XY$3E"), None);
+    assert_eq!(demangle(b"_ZNE"), None);
+    // C++ names without a hash, and a hash alone
+    assert_eq!(demangle(b"_ZN3foo3barE"), None);
+    assert_eq!(demangle(b"_ZN17h0123456789abcdefE"), None);
+}
+
+#[test]
+fn v0_names() {
+    let check = |mangled: &str, expected: &str, alternate: &str| {
+        let demangled = demangle(mangled.as_bytes()).unwrap();
+        assert_eq!(format!("{}", demangled), expected);
+        assert_eq!(format!("{:#}", demangled), alternate);
+    };
+    check("_RNvC6_123foo3bar", "123foo::bar", "123foo::bar");
+    // Punycode, an inherent impl and generics in type position
+    check(
+        "_RNvMCs6xxl1lAjzDf_1aINtB2_u7Foo_dmaINtNtNtNtCsjrHSEGnQ3l9_3std11collections4hash3map7HashMapReINtNtCslNYArtu3iFV_5alloc3vec3VecTlcEEEE4showB2_",
+        "<a[4c2faf61c2d5dfd7]::Fooé<std[e28293b1aa0f68bd]::collections::hash::map::HashMap<&str, alloc[fdfd2bd8633a6659]::vec::Vec<(i32, char)>>>>::show",
+        "<a::Fooé<std::collections::hash::map::HashMap<&str, alloc::vec::Vec<(i32, char)>>>>::show",
+    );
+    // Closures with empty names and generics in value position
+    check(
+        "_RNCINvNtNtCsjrHSEGnQ3l9_3std6thread7current17with_current_nameNCNCNvNtB8_9panicking12default_hook00uE0B8_",
+        "std[e28293b1aa0f68bd]::thread::current::with_current_name::<std[e28293b1aa0f68bd]::panicking::default_hook::{closure#0}::{closure#0}, ()>::{closure#0}",
+        "std::thread::current::with_current_name::<std::panicking::default_hook::{closure#0}::{closure#0}, ()>::{closure#0}",
+    );
+    // Binders, dyn traits with associated types and lifetimes
+    check(
+        "_RINvNtCsgEmfK2I1SDS_4core3ptr13drop_in_placeINtNtCslNYArtu3iFV_5alloc5boxed3BoxDG0_INtNtNtB4_3ops8function2FnTRL1_INtNtCsjrHSEGnQ3l9_3std5panic13PanicHookInfoL0_EEEp6OutputuNtNtB4_6marker4SyncNtB2N_4SendEL_EEB1T_",
+        "core[c1f1a4ba060b9bfa]::ptr::drop_in_place::<alloc[fdfd2bd8633a6659]::boxed::Box<dyn for<'a, 'b> core[c1f1a4ba060b9bfa]::ops::function::Fn<(&'a std[e28293b1aa0f68bd]::panic::PanicHookInfo<'b>,), Output = ()> + core[c1f1a4ba060b9bfa]::marker::Sync + core[c1f1a4ba060b9bfa]::marker::Send>>",
+        "core::ptr::drop_in_place::<alloc::boxed::Box<dyn for<'a, 'b> core::ops::function::Fn<(&'a std::panic::PanicHookInfo<'b>,), Output = ()> + core::marker::Sync + core::marker::Send>>",
+    );
+    // Function pointers with an ABI
+    check(
+        "_RNvMs3_NtCslNYArtu3iFV_5alloc7raw_vecINtB5_6RawVecTOhFUKCBN_EuENtNtCsjrHSEGnQ3l9_3std5alloc6SystemE8grow_oneB13_",
+        "<alloc[fdfd2bd8633a6659]::raw_vec::RawVec<(*mut u8, unsafe extern \"C\" fn(*mut u8)), std[e28293b1aa0f68bd]::alloc::System>>::grow_one",
+        "<alloc::raw_vec::RawVec<(*mut u8, unsafe extern \"C\" fn(*mut u8)), std::alloc::System>>::grow_one",
+    );
+    // Trait impls and shims
+    check(
+        "_RNSNvYNCINvNtCsjrHSEGnQ3l9_3std2rt10lang_startuE0INtNtNtCsgEmfK2I1SDS_4core3ops8function6FnOnceuE9call_once6vtableCs6xxl1lAjzDf_1a",
+        "<std[e28293b1aa0f68bd]::rt::lang_start<()>::{closure#0} as core[c1f1a4ba060b9bfa]::ops::function::FnOnce<()>>::call_once::{shim:vtable#0}",
+        "<std::rt::lang_start<()>::{closure#0} as core::ops::function::FnOnce<()>>::call_once::{shim:vtable#0}",
+    );
+    // Constants
+    check("_RINvNtCsgEmfK2I1SDS_4core3ptr10swap_chunkKj8_ECs6xxl1lAjzDf_1a", "core[c1f1a4ba060b9bfa]::ptr::swap_chunk::<8usize>", "core::ptr::swap_chunk::<8>");
+    check("_RMC5constINtB0_6SignedKanb_E", "<const::Signed<-11i8>>", "<const::Signed<-11>>");
+    check("_RMC5constINtB0_4CharKc76_E", "<const::Char<'v'>>", "<const::Char<'v'>>");
+    check("_RMC5constINtB0_4BoolKb1_E", "<const::Bool<true>>", "<const::Bool<true>>");
+    check("_RNvNvMC5constINtB4_3FooKpE3foo3FOO", "<const::Foo<_>>::foo::FOO", "<const::Foo<_>>::foo::FOO");
+    // Suffixes
+    check("_RNvC9backtrace3foo.llvm.A5310EB9", "backtrace::foo", "backtrace::foo");
+    check("_RNvNtCsjrHSEGnQ3l9_3std11main_thread4MAIN.0", "std[e28293b1aa0f68bd]::main_thread::MAIN.0", "std::main_thread::MAIN.0");
+
+    assert_eq!(demangle(b"main"), None);
+    assert_eq!(demangle(b"_RNvC3foo"), None);
+    assert_eq!(demangle(b"_RNvC3foo3barX"), None);
+    // A backreference to itself, and one into a loop
+    assert_eq!(demangle(b"_RB_"), None);
+    assert_eq!(demangle(b"_RIB_E"), None);
+    assert_eq!(demangle(b"_R1NvC3foo3bar"), None);
+}
+
+#[test]
+fn punycode_identifiers() {
+    let mut chars = ['\0'; MAX_PUNYCODE];
+    let len = punycode("__", "7hkackfecea1cbdathfdh9hlq6y", &mut chars).unwrap();
+    assert_eq!(chars[..len].iter().collect::<std::string::String>(), "საჭმელად_გემრიელი_სადილი");
+    let len = punycode("Foo", "dma", &mut chars).unwrap();
+    assert_eq!(chars[..len].iter().collect::<std::string::String>(), "Fooé");
+    assert_eq!(punycode("", "9", &mut chars), None);
+    assert_eq!(punycode("", "7hkackfecea1cbdathfdh9hlq6y", &mut chars[..3]), None);
+}
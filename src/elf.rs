@@ -1,10 +1,7 @@
-use core::fmt::Write;
-use crate::{error::Error, os};
+use crate::error::{ElfError, Error};
 
 pub mod parse;
-pub mod load;
 
-pub fn e<T>(s: &str) -> Result<T, Error> {
-    let _ = writeln!(os::STDERR, "{}", s);
-    Err(Error::Elf)
-}
\ No newline at end of file
+pub(crate) fn e<T>(err: ElfError) -> Result<T, Error> {
+    Err(Error::Elf(err))
+}
@@ -1,117 +1,205 @@
 use core::{mem::size_of, fmt::Debug};
 
+mod dynamic;
+mod endian;
 mod enum_impls;
 mod ffi_types;
+mod hash;
+mod note;
+#[cfg(feature = "std")]
+mod read;
+mod reloc;
+#[cfg(feature = "std")]
+mod symbolize;
+mod version;
 #[cfg(test)]
 mod test;
 
-use crate::{e, Error, utils::{ToKnown, TransmuteSafe}};
+use crate::{e, Error, error::ElfError, utils::{ToKnown, TransmuteSafe}};
 
-use ffi_types::{
-    EIData, EIClass, PType, Elf32Offs, Elf64Offs, ElfHead32, ElfHead64, ElfNonArchDep, ElfNonArchDep2, ProgHead32,
-    ProgHead64, SectHead32, SectHead64, Sym32, Sym64, EMachine, ShType,
+use ffi_types::{EIdent, Elf32Offs, Elf64Offs, ElfNonArchDep, ElfNonArchDep2};
+
+pub use ffi_types::{
+    EIClass, EIData, EIOsAbi, EIOsAbiUnchecked, EMachine, EType, PType, ShType, DTag, RelocX86_64, RelocX86_64Unchecked,
+    RelocAarch64, RelocAarch64Unchecked, RelocRiscV, RelocRiscVUnchecked,
+    ElfHead32, ElfHead64, ProgHead32, ProgHead64, SectHead32, SectHead64, Sym32, Sym64, Dyn32, Dyn64, Rel32, Rela32, Rel64, Rela64,
+};
+pub use dynamic::{Dyn, Dynamic, Table};
+pub use endian::{BigEndian, Endian, Field, LittleEndian};
+pub(crate) use endian::{swap_unchecked, Swap};
+pub use hash::{gnu_hash, sysv_hash, GnuHash, SysvHash};
+pub use note::{
+    GnuProperties, GnuProperty, Note, NoteKind, Notes, NT_GNU_ABI_TAG, NT_GNU_BUILD_ID, NT_GNU_PROPERTY_TYPE_0,
+    GNU_PROPERTY_X86_FEATURE_1_AND, GNU_PROPERTY_X86_FEATURE_1_IBT, GNU_PROPERTY_X86_FEATURE_1_SHSTK,
+};
+#[cfg(feature = "std")]
+pub use read::ElfBuf;
+#[cfg(feature = "std")]
+pub use symbolize::SymIndex;
+pub use reloc::{Reloc, RelocEntries, RelocSection, Relocation, Relocations};
+pub use version::{
+    SymVersion, VersionDef, VersionDefs, VersionNeed, VersionNeeds, Versions,
+    VER_NDX_LOCAL, VER_NDX_GLOBAL, VERSYM_HIDDEN, VER_FLG_BASE, VER_FLG_WEAK,
 };
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Strings<'a> {
     buf: &'a [u8],
+    /// The file offset of the table, for errors.
+    offset: usize,
 }
 
-trait ElfHead {
+pub trait ElfHead {
     type Offs: TransmuteSafe + Debug;
-    type SectHead: TransmuteSafe + Debug;
+    type SectHead: TransmuteSafe + Debug + SectHead;
     type ProgHead: TransmuteSafe + Debug;
+    fn e_type(&self) -> Result<EType, Error>;
+    fn machine(&self) -> Result<EMachine, Error>;
+    fn entry(&self) -> usize;
     fn phoff(&self) -> usize;
     fn shoff(&self) -> usize;
     fn phnum(&self) -> usize;
+    fn phentsize(&self) -> usize;
     fn shnum(&self) -> usize;
+    fn shentsize(&self) -> usize;
+    /// The raw `e_shstrndx`, which is `SHN_XINDEX` in files with extended section numbering.
+    fn shstrndx(&self) -> u16;
+    fn ehsize(&self) -> usize;
+    fn flags(&self) -> u32;
+    fn osabi(&self) -> EIOsAbiUnchecked;
+    fn abiversion(&self) -> u8;
     fn prog_headers<'a>(&self, buf: &'a [u8]) -> Result<&'a [Self::ProgHead], Error> {
-        let buf = &buf[self.phoff()..];
-        let (us, _) = Self::ProgHead::slice_from_buf(buf, self.phnum())?;
-        Ok(us)
+        if self.phnum() == 0 {
+            return Ok(&[]);
+        }
+        match buf.get(self.phoff()..).map(|buf| Self::ProgHead::slice_from_buf(buf, self.phnum())) {
+            Some(Ok((us, _))) => Ok(us),
+            _ => e(ElfError::ProgHeadersOutOfBounds { offset: self.phoff(), count: self.phnum() }),
+        }
     }
 
     fn sect_headers<'a>(&self, buf: &'a [u8]) -> Result<&'a [Self::SectHead], Error> {
-        let buf = &buf[self.shoff()..];
-        let (us, _) = Self::SectHead::slice_from_buf(buf, self.shnum())?;
-        Ok(us)
+        if self.shoff() == 0 {
+            return Ok(&[]);
+        }
+        let buf = match buf.get(self.shoff()..) {
+            Some(buf) => buf,
+            None => return e(ElfError::SectHeadersOutOfBounds { offset: self.shoff(), count: self.shnum() }),
+        };
+        // Files with too many sections for e_shnum keep the count in the sh_size of section header 0
+        let shnum = match (self.shnum(), Self::SectHead::from_buf(buf)) {
+            (0, Ok((sh0, _))) => sh0.size(),
+            (shnum, _) => shnum,
+        };
+        match Self::SectHead::slice_from_buf(buf, shnum) {
+            Ok((us, _)) => Ok(us),
+            Err(_) => e(ElfError::SectHeadersOutOfBounds { offset: self.shoff(), count: shnum }),
+        }
     }
 }
 
 pub trait ProgHead {
-    fn p_type(&self) -> Result<PType, Error>;
+    /// The segment type, or the raw `p_type` if it's unknown.
+    /// The header doesn't know its own index, so the caller makes the `ElfError::UnknownSegmentType` out of it.
+    fn p_type(&self) -> Result<PType, u32>;
     fn offset(&self) -> usize;
     fn vaddr(&self) -> usize;
     fn filesz(&self) -> usize;
     fn memsz(&self) -> usize;
     fn align(&self) -> usize;
+    fn flags(&self) -> u32;
+    fn paddr(&self) -> usize;
 }
 
-impl ProgHead for ProgHead32 {
-    fn p_type(&self) -> Result<PType, Error> {
-        match self.p_type.known() {
-            Ok(o) => Ok(o),
-            Err(_) => e("invalid p_type value"),
-        }
+pub mod p_flags {
+    pub const PF_X: u32 = 0x1;
+    pub const PF_W: u32 = 0x2;
+    pub const PF_R: u32 = 0x4;
+}
+
+impl<E: Endian> ProgHead for ProgHead32<E> {
+    fn p_type(&self) -> Result<PType, u32> {
+        self.p_type.get().known()
     }
 
     fn offset(&self) -> usize {
-        self.p_offset as usize
+        self.p_offset.get() as usize
     }
 
     fn vaddr(&self) -> usize {
-        self.p_vaddr as usize
+        self.p_vaddr.get() as usize
     }
 
     fn filesz(&self) -> usize {
-        self.p_filesz as usize
+        self.p_filesz.get() as usize
     }
 
     fn memsz(&self) -> usize {
-        self.p_memsz as usize
+        self.p_memsz.get() as usize
     }
 
     fn align(&self) -> usize {
-        self.p_align as usize
+        self.p_align.get() as usize
+    }
+
+    fn flags(&self) -> u32 {
+        self.p_flag.get()
+    }
+
+    fn paddr(&self) -> usize {
+        self.p_paddr.get() as usize
     }
 }
 
-impl ProgHead for ProgHead64 {
-    fn p_type(&self) -> Result<PType, Error> {
-        match self.p_type.known() {
-            Ok(o) => Ok(o),
-            Err(_) => e("invalid p_type value"),
-        }
+impl<E: Endian> ProgHead for ProgHead64<E> {
+    fn p_type(&self) -> Result<PType, u32> {
+        self.p_type.get().known()
     }
 
     fn offset(&self) -> usize {
-        self.p_offset as usize
+        self.p_offset.get() as usize
     }
 
     fn vaddr(&self) -> usize {
-        self.p_vaddr as usize
+        self.p_vaddr.get() as usize
     }
 
     fn filesz(&self) -> usize {
-        self.p_filesz as usize
+        self.p_filesz.get() as usize
     }
 
     fn memsz(&self) -> usize {
-        self.p_memsz as usize
+        self.p_memsz.get() as usize
     }
 
     fn align(&self) -> usize {
-        self.p_align as usize
+        self.p_align.get() as usize
+    }
+
+    fn flags(&self) -> u32 {
+        self.p_flag.get()
+    }
+
+    fn paddr(&self) -> usize {
+        self.p_paddr.get() as usize
     }
 }
 
 pub trait SectHead: Debug {
-    type SymTab: TransmuteSafe;
-    fn name<'a>(&self, str: &'a Strings) -> Result<&'a [u8], Error>;
-    fn sh_type(&self) -> Result<ShType, Error>;
+    type SymTab: TransmuteSafe + Sym;
+    type Rel: Reloc;
+    type Rela: Reloc;
+    fn name<'a>(&self, str: &Strings<'a>) -> Result<&'a [u8], Error>;
+    /// The section type, or the raw `sh_type` if it's unknown, like `ProgHead::p_type`.
+    fn sh_type(&self) -> Result<ShType, u32>;
+    fn flags(&self) -> usize;
+    fn addr(&self) -> usize;
     fn offset(&self) -> usize;
     fn size(&self) -> usize;
     fn entsize(&self) -> usize;
+    fn link(&self) -> usize;
+    fn info(&self) -> usize;
+    fn addralign(&self) -> usize;
 }
 
 #[repr(u8)]
@@ -134,89 +222,275 @@ pub enum StType {
     Tls = 6,
 }
 
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StVisibility {
+    Default = 0,
+    Internal = 1,
+    Hidden = 2,
+    Protected = 3,
+}
+
+/// Reserved `st_shndx` values.
+pub mod shn {
+    pub const SHN_UNDEF: u16 = 0;
+    pub const SHN_LORESERVE: u16 = 0xff00;
+    pub const SHN_ABS: u16 = 0xfff1;
+    pub const SHN_COMMON: u16 = 0xfff2;
+    /// The real index is in the `SHT_SYMTAB_SHNDX` section. Also used for `e_shstrndx`,
+    /// whose real value is then in the `sh_link` of section header 0.
+    pub const SHN_XINDEX: u16 = 0xffff;
+}
+
+/// The section that a symbol is defined relative to, decoded from `st_shndx`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymSection {
+    Undef,
+    /// The value is absolute and doesn't move with relocation.
+    Abs,
+    /// A tentative definition that the linker allocates; the value is its alignment.
+    Common,
+    Index(usize),
+    /// The index didn't fit in `st_shndx` and is in the `SHT_SYMTAB_SHNDX` section.
+    Xindex,
+    /// The other reserved indices, which are processor or OS specific.
+    Reserved(u16),
+}
+
 pub trait Sym: Debug {
-    fn name<'a>(&self, str: &'a Strings) -> Result<&'a [u8], Error>;
+    fn name<'a>(&self, str: &Strings<'a>) -> Result<&'a [u8], Error>;
     fn binding(&self) -> Result<StBind, Error>;
     fn st_type(&self) -> Result<StType, Error>;
+    fn visibility(&self) -> StVisibility;
+    /// An address in executables and shared objects, but an offset into the symbol's section
+    /// in relocatable objects.
+    fn value(&self) -> usize;
+    fn size(&self) -> usize;
+    fn shndx(&self) -> u16;
+
+    fn section(&self) -> SymSection {
+        match self.shndx() {
+            shn::SHN_UNDEF => SymSection::Undef,
+            shn::SHN_ABS => SymSection::Abs,
+            shn::SHN_COMMON => SymSection::Common,
+            shn::SHN_XINDEX => SymSection::Xindex,
+            i if i >= shn::SHN_LORESERVE => SymSection::Reserved(i),
+            i => SymSection::Index(i as usize),
+        }
+    }
+}
+
+impl<S: Sym + ?Sized> Sym for &S {
+    fn name<'a>(&self, str: &Strings<'a>) -> Result<&'a [u8], Error> {
+        (**self).name(str)
+    }
+
+    fn binding(&self) -> Result<StBind, Error> {
+        (**self).binding()
+    }
+
+    fn st_type(&self) -> Result<StType, Error> {
+        (**self).st_type()
+    }
+
+    fn visibility(&self) -> StVisibility {
+        (**self).visibility()
+    }
+
+    fn value(&self) -> usize {
+        (**self).value()
+    }
+
+    fn size(&self) -> usize {
+        (**self).size()
+    }
+
+    fn shndx(&self) -> u16 {
+        (**self).shndx()
+    }
+
+    fn section(&self) -> SymSection {
+        (**self).section()
+    }
 }
 
-impl ElfHead for ElfHead32<'_> {
-    type Offs = Elf32Offs;
-    type SectHead = SectHead32;
-    type ProgHead = ProgHead32;
+impl<E: Endian> ElfHead for ElfHead32<'_, E> {
+    type Offs = Elf32Offs<E>;
+    type SectHead = SectHead32<E>;
+    type ProgHead = ProgHead32<E>;
+    fn e_type(&self) -> Result<EType, Error> {
+        match self.head.e_type.get().known() {
+            Ok(o) => Ok(o),
+            Err(e_type) => e(ElfError::BadType(e_type)),
+        }
+    }
+    fn machine(&self) -> Result<EMachine, Error> {
+        match self.head.e_machine.get().known() {
+            Ok(o) => Ok(o),
+            Err(machine) => e(ElfError::UnknownMachine(machine)),
+        }
+    }
+    fn entry(&self) -> usize {
+        self.offs.e_entry.get() as usize
+    }
     fn phoff(&self) -> usize {
-        self.offs.e_phoff as usize
+        self.offs.e_phoff.get() as usize
     }
     fn shoff(&self) -> usize {
-        self.offs.e_shoff as usize
+        self.offs.e_shoff.get() as usize
     }
     fn phnum(&self) -> usize {
-        self.tail.e_phnum as usize
+        self.tail.e_phnum.get() as usize
+    }
+    fn phentsize(&self) -> usize {
+        self.tail.e_phentsize.get() as usize
     }
     fn shnum(&self) -> usize {
-        self.tail.e_shnum as usize
+        self.tail.e_shnum.get() as usize
+    }
+    fn shentsize(&self) -> usize {
+        self.tail.e_shentsize.get() as usize
+    }
+    fn shstrndx(&self) -> u16 {
+        self.tail.e_shstrndx.get()
+    }
+    fn ehsize(&self) -> usize {
+        self.tail.e_ehsize.get() as usize
+    }
+    fn flags(&self) -> u32 {
+        self.tail.e_flags.get()
+    }
+    fn osabi(&self) -> EIOsAbiUnchecked {
+        self.head.e_ident.ei_osabi
+    }
+    fn abiversion(&self) -> u8 {
+        self.head.e_ident.ei_abiversion
     }
 }
 
-impl ElfHead for ElfHead64<'_> {
-    type Offs = Elf64Offs;
-    type SectHead = SectHead64;
-    type ProgHead = ProgHead64;
+impl<E: Endian> ElfHead for ElfHead64<'_, E> {
+    type Offs = Elf64Offs<E>;
+    type SectHead = SectHead64<E>;
+    type ProgHead = ProgHead64<E>;
+    fn e_type(&self) -> Result<EType, Error> {
+        match self.head.e_type.get().known() {
+            Ok(o) => Ok(o),
+            Err(e_type) => e(ElfError::BadType(e_type)),
+        }
+    }
+    fn machine(&self) -> Result<EMachine, Error> {
+        match self.head.e_machine.get().known() {
+            Ok(o) => Ok(o),
+            Err(machine) => e(ElfError::UnknownMachine(machine)),
+        }
+    }
+    fn entry(&self) -> usize {
+        self.offs.e_entry.get() as usize
+    }
     fn phoff(&self) -> usize {
-        self.offs.e_phoff as usize
+        self.offs.e_phoff.get() as usize
     }
     fn shoff(&self) -> usize {
-        self.offs.e_shoff as usize
+        self.offs.e_shoff.get() as usize
     }
     fn phnum(&self) -> usize {
-        self.tail.e_phnum as usize
+        self.tail.e_phnum.get() as usize
+    }
+    fn phentsize(&self) -> usize {
+        self.tail.e_phentsize.get() as usize
     }
     fn shnum(&self) -> usize {
-        self.tail.e_shnum as usize
+        self.tail.e_shnum.get() as usize
+    }
+    fn shentsize(&self) -> usize {
+        self.tail.e_shentsize.get() as usize
+    }
+    fn shstrndx(&self) -> u16 {
+        self.tail.e_shstrndx.get()
+    }
+    fn ehsize(&self) -> usize {
+        self.tail.e_ehsize.get() as usize
+    }
+    fn flags(&self) -> u32 {
+        self.tail.e_flags.get()
+    }
+    fn osabi(&self) -> EIOsAbiUnchecked {
+        self.head.e_ident.ei_osabi
+    }
+    fn abiversion(&self) -> u8 {
+        self.head.e_ident.ei_abiversion
     }
 }
 
-impl SectHead for SectHead32 {
-    type SymTab = Sym32;
-    fn name<'a>(&self, str: &'a Strings) -> Result<&'a [u8], Error> {
-        Ok(str.get_string(self.head.sh_name as usize)?)
+impl<E: Endian> SectHead for SectHead32<E> {
+    type SymTab = Sym32<E>;
+    type Rel = Rel32<E>;
+    type Rela = Rela32<E>;
+    fn name<'a>(&self, str: &Strings<'a>) -> Result<&'a [u8], Error> {
+        str.get_string(self.head.sh_name.get() as usize)
     }
-    fn sh_type(&self) -> Result<ShType, Error> {
-        match self.head.sh_type.known() {
-            Ok(o) => Ok(o),
-            Err(_) => e("unknown sh_type"),
-        }
+    fn sh_type(&self) -> Result<ShType, u32> {
+        self.head.sh_type.get().known()
+    }
+    fn flags(&self) -> usize {
+        self.sh_flags.get() as usize
+    }
+    fn addr(&self) -> usize {
+        self.sh_addr.get() as usize
     }
     fn offset(&self) -> usize {
-        self.sh_offset as usize
+        self.sh_offset.get() as usize
     }
     fn size(&self) -> usize {
-        self.sh_size as usize
+        self.sh_size.get() as usize
     }
     fn entsize(&self) -> usize {
-        self.sh_entsize as usize
+        self.sh_entsize.get() as usize
+    }
+    fn link(&self) -> usize {
+        self.sh_link.get() as usize
+    }
+    fn info(&self) -> usize {
+        self.sh_info.get() as usize
+    }
+    fn addralign(&self) -> usize {
+        self.sh_addralign.get() as usize
     }
 }
 
-impl SectHead for SectHead64 {
-    type SymTab = Sym64;
-    fn name<'a>(&self, str: &'a Strings) -> Result<&'a [u8], Error> {
-        Ok(str.get_string(self.head.sh_name as usize)?)
+impl<E: Endian> SectHead for SectHead64<E> {
+    type SymTab = Sym64<E>;
+    type Rel = Rel64<E>;
+    type Rela = Rela64<E>;
+    fn name<'a>(&self, str: &Strings<'a>) -> Result<&'a [u8], Error> {
+        str.get_string(self.head.sh_name.get() as usize)
     }
-    fn sh_type(&self) -> Result<ShType, Error> {
-        match self.head.sh_type.known() {
-            Ok(o) => Ok(o),
-            Err(_) => e("unknown sh_type"),
-        }
+    fn sh_type(&self) -> Result<ShType, u32> {
+        self.head.sh_type.get().known()
+    }
+    fn flags(&self) -> usize {
+        self.sh_flags.get() as usize
+    }
+    fn addr(&self) -> usize {
+        self.sh_addr.get() as usize
     }
     fn offset(&self) -> usize {
-        self.sh_offset as usize
+        self.sh_offset.get() as usize
     }
     fn size(&self) -> usize {
-        self.sh_size as usize
+        self.sh_size.get() as usize
     }
     fn entsize(&self) -> usize {
-        self.sh_entsize as usize
+        self.sh_entsize.get() as usize
+    }
+    fn link(&self) -> usize {
+        self.sh_link.get() as usize
+    }
+    fn info(&self) -> usize {
+        self.sh_info.get() as usize
+    }
+    fn addralign(&self) -> usize {
+        self.sh_addralign.get() as usize
     }
 }
 
@@ -229,22 +503,31 @@ fn st_type(st_info: u8) -> Result<StType, Error>  {
         4 => StType::File,
         5 => StType::Common,
         6 => StType::Tls,
-        _ => return e("unknown symtab.st_type value"),
+        st_type => return e(ElfError::UnknownSymbolType(st_type)),
     })
 }
 
+fn st_visibility(st_other: u8) -> StVisibility {
+    match st_other & 0x03 {
+        0 => StVisibility::Default,
+        1 => StVisibility::Internal,
+        2 => StVisibility::Hidden,
+        _ => StVisibility::Protected,
+    }
+}
+
 fn st_bind(st_info: u8) -> Result<StBind, Error> {
     Ok(match st_info >> 4 {
         0 => StBind::Local,
         1 => StBind::Global,
         2 => StBind::Weak,
-        _ => return e("unknown symtab.st_bind value"),
+        st_bind => return e(ElfError::UnknownSymbolBind(st_bind)),
     })
 }
 
-impl Sym for Sym32 {
-    fn name<'a>(&self, str: &'a Strings) -> Result<&'a [u8], Error> {
-        str.get_string(self.st_name as usize)
+impl<E: Endian> Sym for Sym32<E> {
+    fn name<'a>(&self, str: &Strings<'a>) -> Result<&'a [u8], Error> {
+        str.get_string(self.st_name.get() as usize)
     }
 
     fn binding(&self) -> Result<StBind, Error> {
@@ -254,11 +537,27 @@ impl Sym for Sym32 {
     fn st_type(&self) -> Result<StType, Error> {
         st_type(self.st_info)
     }
+
+    fn visibility(&self) -> StVisibility {
+        st_visibility(self.st_other)
+    }
+
+    fn value(&self) -> usize {
+        self.st_value.get() as usize
+    }
+
+    fn size(&self) -> usize {
+        self.st_size.get() as usize
+    }
+
+    fn shndx(&self) -> u16 {
+        self.st_shndx.get()
+    }
 }
 
-impl Sym for Sym64 {
-    fn name<'a>(&self, str: &'a Strings) -> Result<&'a [u8], Error> {
-        str.get_string(self.st_name as usize)
+impl<E: Endian> Sym for Sym64<E> {
+    fn name<'a>(&self, str: &Strings<'a>) -> Result<&'a [u8], Error> {
+        str.get_string(self.st_name.get() as usize)
     }
 
     fn binding(&self) -> Result<StBind, Error> {
@@ -268,107 +567,132 @@ impl Sym for Sym64 {
     fn st_type(&self) -> Result<StType, Error> {
         st_type(self.st_info)
     }
+
+    fn visibility(&self) -> StVisibility {
+        st_visibility(self.st_other)
+    }
+
+    fn value(&self) -> usize {
+        self.st_value.get() as usize
+    }
+
+    fn size(&self) -> usize {
+        self.st_size.get() as usize
+    }
+
+    fn shndx(&self) -> u16 {
+        self.st_shndx.get()
+    }
 }
 
-impl ElfNonArchDep {
+impl<E: Endian> ElfNonArchDep<E> {
     fn check(&self) -> Result<(), Error> {
         if self.e_ident.ei_mag != [0x7F, b'E', b'L', b'F'] {
-            return e("invalid elf_header.e_ident.ei_mag");
+            return e(ElfError::BadMagic(self.e_ident.ei_mag));
         }
-        match self.e_ident.ei_class.known() {
-            Ok(EIClass::Elf32Bit) => return e("quack doesn't support 32-bit elfs"),
-            Ok(EIClass::Elf64Bit) => (),
-            Err(_) => return e("invalid elf_header.e_ident.ei_class"),
+        if let Err(class) = self.e_ident.ei_class.known() {
+            return e(ElfError::UnsupportedClass(class));
         }
-        match self.e_ident.ei_data.known() {
-            Ok(EIData::LittleEndian) => (),
-            Ok(EIData::BigEndian) => return e("quack doesn't support big-endian elfs"),
-            Err(_) => return e("invalid elf_header.e_ident.ei_class"),
+        let big = match self.e_ident.ei_data.known() {
+            Ok(EIData::LittleEndian) => false,
+            Ok(EIData::BigEndian) => true,
+            Err(data) => return e(ElfError::UnsupportedData(data)),
+        };
+        // Callers pick E from ei_data, so this can only fail when the header is parsed directly
+        if big != E::BIG {
+            return e(ElfError::UnsupportedData(self.e_ident.ei_data.unknown()));
         }
         match self.e_ident.ei_version {
             1 => (),
-            _ => return e("invalid elf_header.e_ident.ei_version"),
+            version => return e(ElfError::BadIdentVersion(version)),
         }
         if self.e_ident.ei_pad != [0; 7] {
-            return e("invalid elf.header.e_ident.ei_pad");
+            return e(ElfError::BadPadding(self.e_ident.ei_pad));
         }
-        if self.e_machine.known() != Ok(EMachine::X86_64) {
-            return e("quack doesn't support other archs than x86-64");
+        if let Err(e_type) = self.e_type.get().known() {
+            return e(ElfError::BadType(e_type));
         }
-        if self.e_version != 0x01 {
-            return e("invalid elf_header.e_version");
+        if self.e_version.get() != 0x01 {
+            return e(ElfError::BadVersion(self.e_version.get()));
         }
         Ok(())
     }
 }
 
-impl ElfNonArchDep2 {
+impl<E: Endian> ElfNonArchDep2<E> {
     fn check<T: ElfHead>(&self) -> Result<(), Error> {
-        let header_size = size_of::<ElfNonArchDep>() + size_of::<T::Offs>() + size_of::<ElfNonArchDep2>();
-        if self.e_ehsize as usize != header_size {
-            return e("elf_header.e_ehsize invalid");
+        let header_size = size_of::<ElfNonArchDep<E>>() + size_of::<T::Offs>() + size_of::<ElfNonArchDep2<E>>();
+        if self.e_ehsize.get() as usize != header_size {
+            return e(ElfError::BadEhsize(self.e_ehsize.get()));
         }
-        if self.e_phnum > 0 && self.e_phentsize as usize != size_of::<T::ProgHead>() {
-            return e("elf_header.e_phentsize invalid");
+        if self.e_phnum.get() > 0 && self.e_phentsize.get() as usize != size_of::<T::ProgHead>() {
+            return e(ElfError::BadPhentsize(self.e_phentsize.get()));
         }
-        if self.e_shnum > 0 && self.e_shentsize as usize != size_of::<T::SectHead>() {
-            return e("elf_header.e_shentsize invalid");
+        if self.e_shnum.get() > 0 && self.e_shentsize.get() as usize != size_of::<T::SectHead>() {
+            return e(ElfError::BadShentsize(self.e_shentsize.get()));
         }
         Ok(())
     }
 }
 
-#[derive(Debug)]
-enum ElfHeadType<'a> {
-    EH32(ElfHead32<'a>),
-    EH64(ElfHead64<'a>),
-}
-
-impl ElfHeadType<'_> {
-    fn from(buf: &[u8]) -> Result<ElfHeadType<'_>, Error> {
-        let (head, buf) = ElfNonArchDep::from_buf(buf)?;
-        head.check()?;
-        let eh = match head.e_ident.ei_class.known() {
-            Ok(EIClass::Elf32Bit) => {
-                let (offs, buf) = Elf32Offs::from_buf(buf)?;
-                let (tail, _) = ElfNonArchDep2::from_buf(buf)?;
-                tail.check::<ElfHead32>()?;
-                ElfHeadType::EH32(ElfHead32 { head, offs, tail })
-            }
-            Ok(EIClass::Elf64Bit) => {
-                let (offs, buf) = Elf64Offs::from_buf(buf)?;
-                let (tail, _) = ElfNonArchDep2::from_buf(buf)?;
-                tail.check::<ElfHead64>()?;
-                ElfHeadType::EH64(ElfHead64 { head, offs, tail })
-            }
-            Err(_) => unreachable!(),
-        };
-        Ok(eh)
-    }
+fn elf_head32<E: Endian>(buf: &[u8]) -> Result<ElfHead32<'_, E>, Error> {
+    let truncated = |_| Error::Elf(ElfError::TruncatedHeader(buf.len()));
+    let (head, rest) = ElfNonArchDep::<E>::from_buf(buf).map_err(truncated)?;
+    head.check()?;
+    let (offs, rest) = Elf32Offs::from_buf(rest).map_err(truncated)?;
+    let (tail, _) = ElfNonArchDep2::from_buf(rest).map_err(truncated)?;
+    tail.check::<ElfHead32<E>>()?;
+    Ok(ElfHead32 { head, offs, tail })
+}
+
+fn elf_head64<E: Endian>(buf: &[u8]) -> Result<ElfHead64<'_, E>, Error> {
+    let truncated = |_| Error::Elf(ElfError::TruncatedHeader(buf.len()));
+    let (head, rest) = ElfNonArchDep::<E>::from_buf(buf).map_err(truncated)?;
+    head.check()?;
+    let (offs, rest) = Elf64Offs::from_buf(rest).map_err(truncated)?;
+    let (tail, _) = ElfNonArchDep2::from_buf(rest).map_err(truncated)?;
+    tail.check::<ElfHead64<E>>()?;
+    Ok(ElfHead64 { head, offs, tail })
 }
 
-impl Strings<'_> {
-    fn from<'a, T: SectHead>(buf: &'a [u8], str_head: &T) -> Result<Strings<'a>, Error> {
-        if str_head.sh_type()? != ShType::Strtab {
-            return e("invalid sh_type for a string section");
+impl<'a> Strings<'a> {
+    /// `index` is the index of the section header, for errors.
+    fn from(buf: &'a [u8], index: usize, str_head: &impl SectHead) -> Result<Strings<'a>, Error> {
+        match str_head.sh_type() {
+            Ok(ShType::Strtab) => (),
+            Ok(sh_type) => return e(ElfError::NotStringTable { index, sh_type: sh_type as u32 }),
+            Err(sh_type) => return e(ElfError::UnknownSectionType { index, sh_type }),
         }
-        if buf.len() < str_head.offset() + str_head.size() {
-            return e("buffer is too small to contain the strtab!")
+        match bytes_at(buf, str_head.offset(), str_head.size()) {
+            Some(bytes) => Strings::from_bytes(bytes, str_head.offset()),
+            None => e(ElfError::SectionOutOfBounds { index, offset: str_head.offset(), size: str_head.size() }),
         }
-        let buf = &buf[str_head.offset()..str_head.offset()+str_head.size()];
-        if str_head.size() > 0 && (buf[0] != b'\0' || buf[buf.len() - 1] != b'\0') {
-            return e("malformed string section");
+    }
+
+    /// `offset` is the file offset of `buf`, for errors.
+    fn from_bytes(buf: &'a [u8], offset: usize) -> Result<Strings<'a>, Error> {
+        if !buf.is_empty() && (buf[0] != b'\0' || buf[buf.len() - 1] != b'\0') {
+            return e(ElfError::UnterminatedStrings { offset });
         }
 
-        Ok(Strings { buf })
+        Ok(Strings { buf, offset })
+    }
+
+    /// A string table that only has to end in a null byte, like the Mach-O ones, which start with a space.
+    /// `offset` is the file offset of `buf`, for errors.
+    pub(crate) fn terminated(buf: &'a [u8], offset: usize) -> Option<Strings<'a>> {
+        match buf.last() {
+            None | Some(b'\0') => Some(Strings { buf, offset }),
+            Some(_) => None,
+        }
     }
 
-    pub fn get_string(&self, offset: usize) -> Result<&[u8], Error> {
-        if offset == 0 {
+    pub fn get_string(&self, offset: usize) -> Result<&'a [u8], Error> {
+        if offset == 0 && !self.buf.is_empty() {
             return Ok(&self.buf[0..1]);
         }
         if offset >= self.buf.len() {
-            return e("invalid offset");
+            return e(ElfError::BadStringOffset { table: self.offset, offset });
         }
         let end = offset
             + self.buf[offset..]
@@ -379,117 +703,437 @@ impl Strings<'_> {
     }
 }
 
+/// `buf[offset..offset + size]`, or `None` if the range isn't contained in `buf`.
+fn bytes_at(buf: &[u8], offset: usize, size: usize) -> Option<&[u8]> {
+    buf.get(offset..offset.checked_add(size)?)
+}
+
+/// Translates a virtual address to a file offset through the `PT_LOAD` segments.
+pub fn vaddr_to_offset(phs: &[impl ProgHead], vaddr: usize) -> Option<usize> {
+    phs.iter()
+        .filter(|ph| ph.p_type() == Ok(PType::Load))
+        .find_map(|ph| match vaddr.checked_sub(ph.vaddr()) {
+            Some(offset) if offset < ph.filesz() => offset.checked_add(ph.offset()),
+            _ => None,
+        })
+}
+
+/// The file offset of the contents of a symbol with the value `value`.
+fn sym_offset(e_type: EType, phs: &[impl ProgHead], shs: &[impl SectHead], section: SymSection, value: usize) -> Option<usize> {
+    match (e_type, section) {
+        // Relocatable objects have no segments and their section addresses are all zero
+        (EType::Rel, SymSection::Index(i)) => {
+            let sh = shs.get(i).filter(|sh| sh.sh_type() != Ok(ShType::Nobits))?;
+            sh.offset().checked_add(value)
+        }
+        (EType::Rel, _) => None,
+        _ => vaddr_to_offset(phs, value),
+    }
+}
+
+/// The file offset of `table`, which was read in place from `buf`.
+fn offset_in<T>(buf: &[u8], table: &[T]) -> usize {
+    table.as_ptr() as usize - buf.as_ptr() as usize
+}
+
+/// The section of the symbol at `index`, with `SHN_XINDEX` resolved through the `SHT_SYMTAB_SHNDX` section.
+fn sym_section<E: Endian>(
+    buf: &[u8],
+    syms: Option<&[impl Sym]>,
+    shndx: Option<&[Field<u32, E>]>,
+    index: usize,
+) -> Result<SymSection, Error> {
+    let syms = match syms {
+        Some(syms) => syms,
+        None => return e(ElfError::NoSymbols),
+    };
+    let sym = match syms.get(index) {
+        Some(sym) => sym,
+        None => return e(ElfError::SymbolOutOfBounds { offset: offset_in(buf, syms), index }),
+    };
+    match (sym.section(), shndx) {
+        (SymSection::Xindex, Some(shndx)) => match shndx.get(index) {
+            Some(i) => Ok(SymSection::Index(i.get() as usize)),
+            None => e(ElfError::MissingXindex(index)),
+        },
+        (SymSection::Xindex, None) => e(ElfError::MissingXindex(index)),
+        (section, _) => Ok(section),
+    }
+}
+
+/// The section names, or `None` for files without section headers, which are optional in executables.
 fn sh_names<'a, T: SectHead>(
     buf: &'a [u8],
-    eh_tail: &ElfNonArchDep2,
+    eh_tail: &ElfNonArchDep2<impl Endian>,
     shs: &[T],
-) -> Result<Strings<'a>, Error> {
-    let sh_strs = &shs[eh_tail.e_shstrndx as usize];
-    Ok(Strings::from(buf, sh_strs)?)
+) -> Result<Option<Strings<'a>>, Error> {
+    if shs.is_empty() {
+        return Ok(None);
+    }
+    let shstrndx = match eh_tail.e_shstrndx.get() {
+        shn::SHN_XINDEX => shs.first().map_or(0, |sh| sh.link()),
+        shstrndx => shstrndx as usize,
+    };
+    match shs.get(shstrndx) {
+        Some(sh_strs) => Ok(Some(Strings::from(buf, shstrndx, sh_strs)?)),
+        None => e(ElfError::BadShstrndx(shstrndx)),
+    }
 }
 
+/// The index and the header of the first section with this type and name.
 fn find_sh_by<'a, T: SectHead>(
     shs: &'a [T],
     sh_names: &Strings,
     sh_type: ShType,
     name: &[u8],
-) -> Result<Option<&'a T>, Error> {
-    for sh in shs {
+) -> Result<Option<(usize, &'a T)>, Error> {
+    for (index, sh) in shs.iter().enumerate() {
         if sh.sh_type() == Ok(sh_type) && sh.name(sh_names)? == name {
-            return Ok(Some(sh));
+            return Ok(Some((index, sh)));
         }
     }
     Ok(None)
 }
 
+/// The contents of section `index`, which consists of fixed-size entries.
+fn sect_entries<'a, E: TransmuteSafe>(buf: &'a [u8], index: usize, sh: &impl SectHead) -> Result<&'a [E], Error> {
+    if sh.entsize() != size_of::<E>() {
+        return e(ElfError::BadEntsize { index, entsize: sh.entsize() });
+    }
+    let buf = match bytes_at(buf, sh.offset(), sh.size()) {
+        Some(buf) => buf,
+        None => return e(ElfError::SectionOutOfBounds { index, offset: sh.offset(), size: sh.size() }),
+    };
+    match E::slice_from_buf(buf, sh.size() / sh.entsize()) {
+        Ok((us, _)) => Ok(us),
+        Err(_) => e(ElfError::MisalignedSection { index, offset: sh.offset() }),
+    }
+}
+
 fn symtab<'a, T: SectHead>(
     buf: &'a [u8],
     shs: &[T],
-    sh_names: &Strings,
+    sh_names: Option<&Strings>,
 ) -> Result<Option<&'a [T::SymTab]>, Error> {
-    if let Some(symtab) = find_sh_by(shs, sh_names, ShType::Symtab, b".symtab")? {
-        if symtab.entsize() != size_of::<T::SymTab>() {
-            return e("invalid symtab entity size");
-        }
-        let buf = &buf[symtab.offset()..];
-        let n = symtab.size() / symtab.entsize();
-        let (us, _) = T::SymTab::slice_from_buf(buf, n)?;
-
-        return Ok(Some(us));
+    let sh_names = match sh_names {
+        Some(sh_names) => sh_names,
+        None => return Ok(None),
+    };
+    if let Some((index, symtab)) = find_sh_by(shs, sh_names, ShType::Symtab, b".symtab")? {
+        return Ok(Some(sect_entries(buf, index, symtab)?));
     }
     Ok(None)
 }
 
+/// The dynamic symbol table and the string table it links to.
+type DynSym<'a, T> = (&'a [<T as SectHead>::SymTab], Strings<'a>);
+
+/// Located by section type rather than by name, so that this works with stripped files too.
+fn dynsym<'a, T: SectHead>(buf: &'a [u8], shs: &[T]) -> Result<Option<DynSym<'a, T>>, Error> {
+    let (index, dynsym) = match shs.iter().enumerate().find(|(_, sh)| sh.sh_type() == Ok(ShType::Dynsym)) {
+        Some(dynsym) => dynsym,
+        None => return Ok(None),
+    };
+    let dyn_names = match shs.get(dynsym.link()) {
+        Some(dynstr) => Strings::from(buf, dynsym.link(), dynstr)?,
+        None => return e(ElfError::BadLink { index, link: dynsym.link() }),
+    };
+    Ok(Some((sect_entries(buf, index, dynsym)?, dyn_names)))
+}
+
+/// The extended section indices of the static symbols, in files with more than `SHN_LORESERVE` sections.
+fn symtab_shndx<'a, T: SectHead, E: Endian>(buf: &'a [u8], shs: &[T]) -> Result<Option<&'a [Field<u32, E>]>, Error> {
+    shs.iter()
+        .enumerate()
+        .find(|(_, sh)| {
+            sh.sh_type() == Ok(ShType::SymtabShndx)
+                && shs.get(sh.link()).is_some_and(|symtab| symtab.sh_type() == Ok(ShType::Symtab))
+        })
+        .map(|(index, sh)| sect_entries(buf, index, sh))
+        .transpose()
+}
+
 fn sym_names<'a, T: SectHead>(
     buf: &'a [u8],
     shs: &[T],
-    sh_names: &Strings,
+    sh_names: Option<&Strings>,
 ) -> Result<Option<Strings<'a>>, Error> {
-    if let Some(strtab) = find_sh_by(shs, sh_names, ShType::Strtab, b".strtab")? {
-        Ok(Some(Strings::from(buf, strtab)?))
+    let sh_names = match sh_names {
+        Some(sh_names) => sh_names,
+        None => return Ok(None),
+    };
+    if let Some((index, strtab)) = find_sh_by(shs, sh_names, ShType::Strtab, b".strtab")? {
+        Ok(Some(Strings::from(buf, index, strtab)?))
     } else {
         Ok(None)
     }
 }
 
 #[derive(Debug)]
-pub struct ElfFile32<'a> {
-    pub eh: ElfHead32<'a>,
-    pub phs: &'a [ProgHead32],
-    pub shs: Option<&'a [SectHead32]>,
+pub struct ElfFile32<'a, E: Endian = LittleEndian> {
+    pub buf: &'a [u8],
+    pub eh: ElfHead32<'a, E>,
+    pub phs: &'a [ProgHead32<E>],
+    pub shs: Option<&'a [SectHead32<E>]>,
     pub sh_names: Option<Strings<'a>>,
-    pub symtab: Option<&'a [Sym32]>,
+    pub symtab: Option<&'a [Sym32<E>]>,
     pub sym_names: Option<Strings<'a>>,
+    pub symtab_shndx: Option<&'a [Field<u32, E>]>,
+    pub dynsym: Option<&'a [Sym32<E>]>,
+    pub dyn_names: Option<Strings<'a>>,
 }
 
 #[derive(Debug, PartialEq)]
-pub struct ElfFile64<'a> {
-    pub eh: ElfHead64<'a>,
-    pub phs: &'a [ProgHead64],
-    pub shs: Option<&'a [SectHead64]>,
+pub struct ElfFile64<'a, E: Endian = LittleEndian> {
+    pub buf: &'a [u8],
+    pub eh: ElfHead64<'a, E>,
+    pub phs: &'a [ProgHead64<E>],
+    pub shs: Option<&'a [SectHead64<E>]>,
     pub sh_names: Option<Strings<'a>>,
-    pub symtab: Option<&'a [Sym64]>,
+    pub symtab: Option<&'a [Sym64<E>]>,
     pub sym_names: Option<Strings<'a>>,
+    pub symtab_shndx: Option<&'a [Field<u32, E>]>,
+    pub dynsym: Option<&'a [Sym64<E>]>,
+    pub dyn_names: Option<Strings<'a>>,
+}
+
+impl<'a, E: Endian> ElfFile32<'a, E> {
+    pub fn dynamic(&self) -> Result<Option<Dynamic<'a, Dyn32<E>>>, Error> {
+        Dynamic::from(self.buf, self.phs)
+    }
+
+    /// The entries of an array that the dynamic table points to.
+    pub fn table<T: TransmuteSafe>(&self, table: &Table) -> Result<&'a [T], Error> {
+        table_entries(self.buf, self.phs, table)
+    }
+
+    pub fn reloc_sections(&self) -> impl Iterator<Item = Result<RelocSection<'a, SectHead32<E>>, Error>> + 'a {
+        RelocSection::all(self.buf, self.shs.unwrap_or(&[]))
+    }
+
+    pub fn relocations(&self) -> Relocations<'a, SectHead32<E>> {
+        Relocations::new(self.buf, self.shs.unwrap_or(&[]))
+    }
+
+    /// Finds a symbol by name, undefined ones included. The dynamic symbols are searched through the hash tables
+    /// when present, or by a scan otherwise; the static symbol table is scanned last.
+    pub fn lookup(&self, name: &[u8]) -> Option<&'a Sym32<E>> {
+        hash::lookup::<_, u32, E>(self.buf, self.shs.unwrap_or(&[]), name)
+            .unwrap_or_else(|| hash::scan(self.dynsym, self.dyn_names.as_ref(), name))
+            .or_else(|| hash::scan(self.symtab, self.sym_names.as_ref(), name))
+    }
+
+    /// The section of the static symbol at `index`.
+    pub fn sym_section(&self, index: usize) -> Result<SymSection, Error> {
+        sym_section(self.buf, self.symtab, self.symtab_shndx, index)
+    }
+
+    /// The function and object symbols of both symbol tables, sorted by address for `SymIndex::symbolize`.
+    #[cfg(feature = "std")]
+    pub fn sym_index(&self) -> SymIndex<'a> {
+        SymIndex::new(&[(self.symtab, self.sym_names.as_ref()), (self.dynsym, self.dyn_names.as_ref())])
+    }
+
+    /// The file offset of the contents of the static symbol at `index`, or `None` if they aren't in the file.
+    pub fn sym_offset(&self, index: usize) -> Option<usize> {
+        let section = self.sym_section(index).ok()?;
+        let sym = self.symtab?.get(index)?;
+        sym_offset(self.eh.e_type().ok()?, self.phs, self.shs.unwrap_or(&[]), section, sym.value())
+    }
+
+    pub fn notes(&self) -> impl Iterator<Item = Result<Note<'a, E>, Error>> + 'a {
+        Notes::<E>::all(self.buf, self.phs, self.shs.unwrap_or(&[]))
+    }
+
+    /// The descriptor of the `NT_GNU_BUILD_ID` note, usually a 20 byte SHA-1.
+    pub fn build_id(&self) -> Result<Option<&'a [u8]>, Error> {
+        for note in self.notes() {
+            if let NoteKind::GnuBuildId(id) = note?.kind()? {
+                return Ok(Some(id));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The symbol versions of the dynamic symbols, if the file is versioned.
+    pub fn versions(&self) -> Result<Option<Versions<'a, E>>, Error> {
+        Versions::from(self.buf, self.shs.unwrap_or(&[]))
+    }
+
+    /// Finds the dynamic symbol called `name` that has the version `version`.
+    pub fn lookup_versioned(&self, name: &[u8], version: &[u8]) -> Result<Option<&'a Sym32<E>>, Error> {
+        let (versions, dynsym, dyn_names) = match (self.versions()?, self.dynsym, &self.dyn_names) {
+            (Some(versions), Some(dynsym), Some(dyn_names)) => (versions, dynsym, dyn_names),
+            _ => return Ok(None),
+        };
+        for (i, sym) in dynsym.iter().enumerate() {
+            if sym.name(dyn_names)? == name && versions.version(i)?.is_some_and(|v| v.name == version) {
+                return Ok(Some(sym));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a, E: Endian> ElfFile64<'a, E> {
+    pub fn dynamic(&self) -> Result<Option<Dynamic<'a, Dyn64<E>>>, Error> {
+        Dynamic::from(self.buf, self.phs)
+    }
+
+    /// The entries of an array that the dynamic table points to.
+    pub fn table<T: TransmuteSafe>(&self, table: &Table) -> Result<&'a [T], Error> {
+        table_entries(self.buf, self.phs, table)
+    }
+
+    pub fn reloc_sections(&self) -> impl Iterator<Item = Result<RelocSection<'a, SectHead64<E>>, Error>> + 'a {
+        RelocSection::all(self.buf, self.shs.unwrap_or(&[]))
+    }
+
+    /// All the relocations of all the `SHT_REL` and `SHT_RELA` sections.
+    pub fn relocations(&self) -> Relocations<'a, SectHead64<E>> {
+        Relocations::new(self.buf, self.shs.unwrap_or(&[]))
+    }
+
+    /// Finds a symbol by name, undefined ones included. The dynamic symbols are searched through the hash tables
+    /// when present, or by a scan otherwise; the static symbol table is scanned last.
+    pub fn lookup(&self, name: &[u8]) -> Option<&'a Sym64<E>> {
+        hash::lookup::<_, u64, E>(self.buf, self.shs.unwrap_or(&[]), name)
+            .unwrap_or_else(|| hash::scan(self.dynsym, self.dyn_names.as_ref(), name))
+            .or_else(|| hash::scan(self.symtab, self.sym_names.as_ref(), name))
+    }
+
+    /// The section of the static symbol at `index`.
+    pub fn sym_section(&self, index: usize) -> Result<SymSection, Error> {
+        sym_section(self.buf, self.symtab, self.symtab_shndx, index)
+    }
+
+    /// The function and object symbols of both symbol tables, sorted by address for `SymIndex::symbolize`.
+    #[cfg(feature = "std")]
+    pub fn sym_index(&self) -> SymIndex<'a> {
+        SymIndex::new(&[(self.symtab, self.sym_names.as_ref()), (self.dynsym, self.dyn_names.as_ref())])
+    }
+
+    /// The file offset of the contents of the static symbol at `index`, or `None` if they aren't in the file.
+    pub fn sym_offset(&self, index: usize) -> Option<usize> {
+        let section = self.sym_section(index).ok()?;
+        let sym = self.symtab?.get(index)?;
+        sym_offset(self.eh.e_type().ok()?, self.phs, self.shs.unwrap_or(&[]), section, sym.value())
+    }
+
+    pub fn notes(&self) -> impl Iterator<Item = Result<Note<'a, E>, Error>> + 'a {
+        Notes::<E>::all(self.buf, self.phs, self.shs.unwrap_or(&[]))
+    }
+
+    /// The descriptor of the `NT_GNU_BUILD_ID` note, usually a 20 byte SHA-1.
+    pub fn build_id(&self) -> Result<Option<&'a [u8]>, Error> {
+        for note in self.notes() {
+            if let NoteKind::GnuBuildId(id) = note?.kind()? {
+                return Ok(Some(id));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The symbol versions of the dynamic symbols, if the file is versioned.
+    pub fn versions(&self) -> Result<Option<Versions<'a, E>>, Error> {
+        Versions::from(self.buf, self.shs.unwrap_or(&[]))
+    }
+
+    /// Finds the dynamic symbol called `name` that has the version `version`.
+    pub fn lookup_versioned(&self, name: &[u8], version: &[u8]) -> Result<Option<&'a Sym64<E>>, Error> {
+        let (versions, dynsym, dyn_names) = match (self.versions()?, self.dynsym, &self.dyn_names) {
+            (Some(versions), Some(dynsym), Some(dyn_names)) => (versions, dynsym, dyn_names),
+            _ => return Ok(None),
+        };
+        for (i, sym) in dynsym.iter().enumerate() {
+            if sym.name(dyn_names)? == name && versions.version(i)?.is_some_and(|v| v.name == version) {
+                return Ok(Some(sym));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn table_entries<'a, T: TransmuteSafe>(buf: &'a [u8], phs: &[impl ProgHead], table: &Table) -> Result<&'a [T], Error> {
+    if table.entsize != size_of::<T>() {
+        return e(ElfError::BadTableEntsize(table.entsize));
+    }
+    let offset = match vaddr_to_offset(phs, table.addr) {
+        Some(offset) if offset <= buf.len() => offset,
+        _ => return e(ElfError::UnmappedAddress(table.addr)),
+    };
+    match T::slice_from_buf(&buf[offset..], table.size / table.entsize) {
+        Ok((us, _)) => Ok(us),
+        Err(_) => e(ElfError::SegmentOutOfBounds { offset, size: table.size }),
+    }
 }
 
 #[derive(Debug)]
 pub enum ElfParse<'a> {
     Elf32(ElfFile32<'a>),
     Elf64(ElfFile64<'a>),
+    Elf32Be(ElfFile32<'a, BigEndian>),
+    Elf64Be(ElfFile64<'a, BigEndian>),
 }
 
-pub fn with(buf: &[u8]) -> Result<ElfParse, Error> {
-    let elf_header = ElfHeadType::from(buf)?;
-    match elf_header {
-        ElfHeadType::EH32(eh) => {
-            let phs = eh.prog_headers(buf)?;
-            let shs = eh.sect_headers(buf)?;
-            let sh_names = sh_names(buf, &eh.tail, &shs)?;
-            let symtab = symtab(buf, &shs, &sh_names)?;
-            let sym_names = sym_names(buf, &shs, &sh_names)?;
-            Ok(ElfParse::Elf32(ElfFile32 {
-                eh,
-                phs,
-                shs: Some(shs),
-                sh_names: Some(sh_names),
-                symtab,
-                sym_names,
-            }))
-        }
-        ElfHeadType::EH64(eh) => {
-            let phs = eh.prog_headers(buf)?;
-            let shs = eh.sect_headers(buf)?;
-            let sh_names = sh_names(buf, &eh.tail, &shs)?;
-            let symtab = symtab(buf, &shs, &sh_names)?;
-            let sym_names = sym_names(buf, &shs, &sh_names)?;
-            Ok(ElfParse::Elf64(ElfFile64 {
-                eh,
-                phs,
-                shs: Some(shs),
-                sh_names: Some(sh_names),
-                symtab,
-                sym_names,
-            }))
-        }
+fn elf_file32<E: Endian>(buf: &[u8]) -> Result<ElfFile32<'_, E>, Error> {
+    let eh = elf_head32(buf)?;
+    let phs = eh.prog_headers(buf)?;
+    let shs = eh.sect_headers(buf)?;
+    let sh_names = sh_names(buf, eh.tail, shs)?;
+    let symtab = symtab(buf, shs, sh_names.as_ref())?;
+    let sym_names = sym_names(buf, shs, sh_names.as_ref())?;
+    let symtab_shndx = symtab_shndx(buf, shs)?;
+    let (dynsym, dyn_names) = dynsym(buf, shs)?.unzip();
+    Ok(ElfFile32 {
+        buf,
+        eh,
+        phs,
+        shs: Some(shs),
+        sh_names,
+        symtab,
+        sym_names,
+        symtab_shndx,
+        dynsym,
+        dyn_names,
+    })
+}
+
+fn elf_file64<E: Endian>(buf: &[u8]) -> Result<ElfFile64<'_, E>, Error> {
+    let eh = elf_head64(buf)?;
+    let phs = eh.prog_headers(buf)?;
+    let shs = eh.sect_headers(buf)?;
+    let sh_names = sh_names(buf, eh.tail, shs)?;
+    let symtab = symtab(buf, shs, sh_names.as_ref())?;
+    let sym_names = sym_names(buf, shs, sh_names.as_ref())?;
+    let symtab_shndx = symtab_shndx(buf, shs)?;
+    let (dynsym, dyn_names) = dynsym(buf, shs)?.unzip();
+    Ok(ElfFile64 {
+        buf,
+        eh,
+        phs,
+        shs: Some(shs),
+        sh_names,
+        symtab,
+        sym_names,
+        symtab_shndx,
+        dynsym,
+        dyn_names,
+    })
+}
+
+pub fn with(buf: &[u8]) -> Result<ElfParse<'_>, Error> {
+    // The identification bytes are the same for every class and byte order
+    let (ident, _) = EIdent::from_buf(buf).map_err(|_| Error::Elf(ElfError::TruncatedHeader(buf.len())))?;
+    if ident.ei_mag != [0x7F, b'E', b'L', b'F'] {
+        return e(ElfError::BadMagic(ident.ei_mag));
+    }
+    match (ident.ei_class.known(), ident.ei_data.known()) {
+        (Ok(EIClass::Elf32Bit), Ok(EIData::LittleEndian)) => Ok(ElfParse::Elf32(elf_file32(buf)?)),
+        (Ok(EIClass::Elf64Bit), Ok(EIData::LittleEndian)) => Ok(ElfParse::Elf64(elf_file64(buf)?)),
+        (Ok(EIClass::Elf32Bit), Ok(EIData::BigEndian)) => Ok(ElfParse::Elf32Be(elf_file32(buf)?)),
+        (Ok(EIClass::Elf64Bit), Ok(EIData::BigEndian)) => Ok(ElfParse::Elf64Be(elf_file64(buf)?)),
+        (Err(class), _) => e(ElfError::UnsupportedClass(class)),
+        (_, Err(data)) => e(ElfError::UnsupportedData(data)),
     }
-}
\ No newline at end of file
+}
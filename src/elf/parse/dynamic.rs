@@ -0,0 +1,158 @@
+use core::{mem::size_of, fmt::Debug};
+
+use crate::{e, Error, error::ElfError, utils::{ToKnown, TransmuteSafe}};
+
+use super::{
+    bytes_at, vaddr_to_offset, Endian, ProgHead, PType, Strings,
+    ffi_types::{DTag, DTagUnchecked, Dyn32, Dyn64},
+};
+
+pub trait Dyn: TransmuteSafe + Debug {
+    fn d_tag(&self) -> DTagUnchecked;
+    fn d_val(&self) -> usize;
+}
+
+impl<E: Endian> Dyn for Dyn32<E> {
+    fn d_tag(&self) -> DTagUnchecked {
+        DTagUnchecked { unknown: self.d_tag.get() as u64 }
+    }
+
+    fn d_val(&self) -> usize {
+        self.d_val.get() as usize
+    }
+}
+
+impl<E: Endian> Dyn for Dyn64<E> {
+    fn d_tag(&self) -> DTagUnchecked {
+        self.d_tag.get()
+    }
+
+    fn d_val(&self) -> usize {
+        self.d_val.get() as usize
+    }
+}
+
+/// An array described by an address/size/entry size triplet of dynamic tags.
+/// The addresses are virtual addresses, not file offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Table {
+    pub addr: usize,
+    pub size: usize,
+    pub entsize: usize,
+}
+
+/// The decoded contents of the `PT_DYNAMIC` segment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dynamic<'a, D: Dyn> {
+    pub entries: &'a [D],
+    pub strings: Option<Strings<'a>>,
+    pub soname: Option<&'a [u8]>,
+    pub rpath: Option<&'a [u8]>,
+    pub runpath: Option<&'a [u8]>,
+    pub init: Option<usize>,
+    pub fini: Option<usize>,
+    pub init_array: Option<Table>,
+    pub fini_array: Option<Table>,
+    pub preinit_array: Option<Table>,
+    pub rela: Option<Table>,
+    pub rel: Option<Table>,
+    pub jmprel: Option<Table>,
+    pub pltrel: Option<DTag>,
+    pub flags: usize,
+    pub flags_1: usize,
+    pub hash: Option<usize>,
+    pub gnu_hash: Option<usize>,
+    pub symtab: Option<usize>,
+    pub strtab: Option<usize>,
+}
+
+fn table(addr: Option<usize>, size: Option<usize>, entsize: usize) -> Option<Table> {
+    Some(Table { addr: addr?, size: size.unwrap_or(0), entsize })
+}
+
+impl<'a, D: Dyn> Dynamic<'a, D> {
+    pub(super) fn from(buf: &'a [u8], phs: &[impl ProgHead]) -> Result<Option<Dynamic<'a, D>>, Error> {
+        let ph = match phs.iter().find(|ph| ph.p_type() == Ok(PType::Dynamic)) {
+            Some(ph) => ph,
+            None => return Ok(None),
+        };
+        let out_of_bounds = ElfError::SegmentOutOfBounds { offset: ph.offset(), size: ph.filesz() };
+        let all = match bytes_at(buf, ph.offset(), ph.filesz()).map(|bytes| D::slice_from_buf(bytes, bytes.len() / size_of::<D>())) {
+            Some(Ok((all, _))) => all,
+            _ => return e(out_of_bounds),
+        };
+        let n = all.iter()
+            .position(|d| d.d_tag().known() == Ok(DTag::Null))
+            .unwrap_or(all.len());
+        let entries = &all[..n];
+
+        let word = size_of::<D>() / 2;
+        let get = |tag: DTag| entries.iter()
+            .find(|d| d.d_tag().known() == Ok(tag))
+            .map(|d| d.d_val());
+
+        let strtab = get(DTag::StrTab);
+        let strings = match (strtab, get(DTag::StrSz)) {
+            (Some(addr), Some(size)) => match vaddr_to_offset(phs, addr) {
+                Some(offset) => match bytes_at(buf, offset, size) {
+                    Some(bytes) => Some(Strings::from_bytes(bytes, offset)?),
+                    None => return e(ElfError::SegmentOutOfBounds { offset, size }),
+                },
+                None => return e(ElfError::UnmappedAddress(addr)),
+            },
+            _ => None,
+        };
+        let string = |tag: DTag| -> Result<Option<&'a [u8]>, Error> {
+            match (get(tag), &strings) {
+                (Some(offset), Some(strings)) => Ok(Some(strings.get_string(offset)?)),
+                (Some(_), None) => e(ElfError::MissingStrtab),
+                (None, _) => Ok(None),
+            }
+        };
+
+        let pltrel = match get(DTag::PltRel) {
+            Some(t) => match (DTagUnchecked { unknown: t as u64 }).known() {
+                Ok(t @ DTag::Rela) | Ok(t @ DTag::Rel) => Some(t),
+                _ => return e(ElfError::BadPltRel(t)),
+            },
+            None => None,
+        };
+        let pltrel_entsize = match pltrel {
+            Some(DTag::Rel) => get(DTag::RelEnt).unwrap_or(2 * word),
+            _ => get(DTag::RelaEnt).unwrap_or(3 * word),
+        };
+
+        Ok(Some(Dynamic {
+            entries,
+            soname: string(DTag::SoName)?,
+            rpath: string(DTag::RPath)?,
+            runpath: string(DTag::RunPath)?,
+            strings,
+            init: get(DTag::Init),
+            fini: get(DTag::Fini),
+            init_array: table(get(DTag::InitArray), get(DTag::InitArraySz), word),
+            fini_array: table(get(DTag::FiniArray), get(DTag::FiniArraySz), word),
+            preinit_array: table(get(DTag::PreinitArray), get(DTag::PreinitArraySz), word),
+            rela: table(get(DTag::Rela), get(DTag::RelaSz), get(DTag::RelaEnt).unwrap_or(3 * word)),
+            rel: table(get(DTag::Rel), get(DTag::RelSz), get(DTag::RelEnt).unwrap_or(2 * word)),
+            jmprel: table(get(DTag::JmpRel), get(DTag::PltRelSz), pltrel_entsize),
+            pltrel,
+            flags: get(DTag::Flags).unwrap_or(0),
+            flags_1: get(DTag::Flags1).unwrap_or(0),
+            hash: get(DTag::Hash),
+            gnu_hash: get(DTag::GnuHash),
+            symtab: get(DTag::SymTab),
+            strtab,
+        }))
+    }
+
+    /// The names of the libraries listed with `DT_NEEDED`, in order.
+    pub fn needed(&self) -> impl Iterator<Item = Result<&'a [u8], Error>> + '_ {
+        self.entries.iter()
+            .filter(|d| d.d_tag().known() == Ok(DTag::Needed))
+            .map(move |d| match &self.strings {
+                Some(strings) => strings.get_string(d.d_val()),
+                None => e(ElfError::MissingStrtab),
+            })
+    }
+}
@@ -0,0 +1,99 @@
+use core::{fmt::{self, Debug}, marker::PhantomData};
+
+use crate::utils::{ToKnown, TransmuteSafe};
+
+use super::ffi_types::{
+    DTagUnchecked, EMachineUnchecked, ETypeUnchecked, PTypeUnchecked, RelocX86_64Unchecked, ShTypeUnchecked,
+};
+
+/// The byte order of an ELF file. The structures read from a file carry it as a type
+/// parameter, so that their fields can be decoded without copying the structures.
+pub trait Endian: Debug + Default + Clone + Copy + PartialEq + 'static {
+    const BIG: bool;
+
+    fn u16(bytes: [u8; 2]) -> u16 {
+        if Self::BIG { u16::from_be_bytes(bytes) } else { u16::from_le_bytes(bytes) }
+    }
+
+    fn u32(bytes: [u8; 4]) -> u32 {
+        if Self::BIG { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) }
+    }
+
+    fn u64(bytes: [u8; 8]) -> u64 {
+        if Self::BIG { u64::from_be_bytes(bytes) } else { u64::from_le_bytes(bytes) }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LittleEndian;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BigEndian;
+
+impl Endian for LittleEndian {
+    const BIG: bool = false;
+}
+
+impl Endian for BigEndian {
+    const BIG: bool = true;
+}
+
+/// Values that can be converted between byte orders by reversing their bytes.
+pub trait Swap: Copy {
+    fn swap(self) -> Self;
+}
+
+macro_rules! swap_int {
+    ($($int: ty),*) => {
+        $(impl Swap for $int {
+            fn swap(self) -> Self {
+                self.swap_bytes()
+            }
+        })*
+    };
+}
+
+swap_int!(u16, u32, u64, i32, i64);
+
+macro_rules! swap_unchecked {
+    ($($unchecked: ident),*) => {
+        $(impl Swap for $unchecked {
+            fn swap(self) -> Self {
+                $unchecked { unknown: self.unknown().swap_bytes() }
+            }
+        })*
+    };
+}
+
+pub(crate) use swap_unchecked;
+
+swap_unchecked!(
+    ETypeUnchecked, EMachineUnchecked, PTypeUnchecked, ShTypeUnchecked, DTagUnchecked, RelocX86_64Unchecked
+);
+
+/// A field stored in the byte order `E`. For files in the host byte order, reading it is a plain copy.
+#[repr(transparent)]
+#[derive(Default, Clone, Copy, PartialEq)]
+pub struct Field<T, E> {
+    raw: T,
+    endian: PhantomData<E>,
+}
+
+impl<T: Swap, E: Endian> Field<T, E> {
+    pub fn get(self) -> T {
+        if E::BIG == cfg!(target_endian = "big") {
+            self.raw
+        } else {
+            self.raw.swap()
+        }
+    }
+}
+
+impl<T: Swap + Debug, E: Endian> Debug for Field<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+// Sound because Field is a transparent wrapper of a TransmuteSafe type
+unsafe impl<T: TransmuteSafe + Swap, E: Endian> TransmuteSafe for Field<T, E> {}
@@ -1,7 +1,7 @@
 use core::{fmt::{self, Debug, Formatter}};
 
 use crate::{
-    elf::parse::ffi_types::{EIClass, EIClassUnchecked, EIData, EIDataUnchecked, EIOsAbi, EIOsAbiUnchecked, EType, ETypeUnchecked, EMachine, EMachineUnchecked, PType, PTypeUnchecked, ShType, ShTypeUnchecked},
+    elf::parse::ffi_types::{EIClass, EIClassUnchecked, EIData, EIDataUnchecked, EIOsAbi, EIOsAbiUnchecked, EType, ETypeUnchecked, EMachine, EMachineUnchecked, PType, PTypeUnchecked, ShType, ShTypeUnchecked, DTag, DTagUnchecked, RelocX86_64, RelocX86_64Unchecked, RelocAarch64, RelocAarch64Unchecked, RelocRiscV, RelocRiscVUnchecked},
     utils::ToKnown,
 };
 
@@ -83,7 +83,7 @@ impl ToKnown for EMachineUnchecked {
 
     fn known(&self) -> Result<Self::Known, Self::Unknown> {
         let u = self.unknown();
-        if u == 0x03 || u == 0x3E || u == 0xB7 {
+        if [0x00, 0x02, 0x03, 0x04, 0x08, 0x14, 0x15, 0x16, 0x28, 0x2B, 0x32, 0x3E, 0xB7, 0xF3, 0xF7, 0x102].contains(&u) {
             Ok(unsafe { self.known })
         } else {
             Err(u)
@@ -101,7 +101,7 @@ impl ToKnown for PTypeUnchecked {
 
     fn known(&self) -> Result<Self::Known, Self::Unknown> {
         let u = self.unknown();
-        if (0x00..=0x07).contains(&u) || [0x6474e550, 0x6474e551, 0x6474e552].contains(&u) {
+        if (0x00..=0x07).contains(&u) || [0x6474e550, 0x6474e551, 0x6474e552, 0x6474e553].contains(&u) {
             Ok(unsafe { self.known })
         } else {
             Err(u)
@@ -131,6 +131,84 @@ impl ToKnown for ShTypeUnchecked {
     }
 }
 
+impl ToKnown for DTagUnchecked {
+    type Known = DTag;
+    type Unknown = u64;
+
+    fn known(&self) -> Result<Self::Known, Self::Unknown> {
+        let u = self.unknown();
+        if (0x00..=0x1E).contains(&u) || (0x20..=0x25).contains(&u) || u == 0x6FFFFEF5
+            || (0x6FFFFFF9..=0x6FFFFFFF).contains(&u) || u == 0x6FFFFFF0 {
+            Ok(unsafe { self.known })
+        } else {
+            Err(u)
+        }
+    }
+
+    fn unknown(&self) -> Self::Unknown {
+        unsafe { self.unknown }
+    }
+}
+
+impl ToKnown for RelocX86_64Unchecked {
+    type Known = RelocX86_64;
+    type Unknown = u32;
+
+    fn known(&self) -> Result<Self::Known, Self::Unknown> {
+        let u = self.unknown();
+        if (0..=38).contains(&u) || u == 41 || u == 42 {
+            Ok(unsafe { self.known })
+        } else {
+            Err(u)
+        }
+    }
+
+    fn unknown(&self) -> Self::Unknown {
+        unsafe { self.unknown }
+    }
+}
+
+impl ToKnown for RelocAarch64Unchecked {
+    type Known = RelocAarch64;
+    type Unknown = u32;
+
+    fn known(&self) -> Result<Self::Known, Self::Unknown> {
+        let u = self.unknown();
+        if u == 0
+            || (257..=280).contains(&u)
+            || (282..=286).contains(&u)
+            || [299, 311, 312].contains(&u)
+            || (1024..=1032).contains(&u)
+        {
+            Ok(unsafe { self.known })
+        } else {
+            Err(u)
+        }
+    }
+
+    fn unknown(&self) -> Self::Unknown {
+        unsafe { self.unknown }
+    }
+}
+
+impl ToKnown for RelocRiscVUnchecked {
+    type Known = RelocRiscV;
+    type Unknown = u32;
+
+    fn known(&self) -> Result<Self::Known, Self::Unknown> {
+        let u = self.unknown();
+        if (0..=12).contains(&u) || (16..=40).contains(&u) || (43..=45).contains(&u) || (51..=59).contains(&u) {
+            Ok(unsafe { self.known })
+        } else {
+            Err(u)
+        }
+    }
+
+    fn unknown(&self) -> Self::Unknown {
+        unsafe { self.unknown }
+    }
+}
+
 impl Default for EIClassUnchecked {
     fn default() -> Self {
         Self { unknown: 0 }
@@ -173,6 +251,30 @@ impl Default for ShTypeUnchecked {
     }
 }
 
+impl Default for DTagUnchecked {
+    fn default() -> Self {
+        Self { unknown: 0 }
+    }
+}
+
+impl Default for RelocX86_64Unchecked {
+    fn default() -> Self {
+        Self { unknown: 0 }
+    }
+}
+
+impl Default for RelocAarch64Unchecked {
+    fn default() -> Self {
+        Self { unknown: 0 }
+    }
+}
+
+impl Default for RelocRiscVUnchecked {
+    fn default() -> Self {
+        Self { unknown: 0 }
+    }
+}
+
 impl Debug for EIClassUnchecked {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         if let Ok(t) = self.known() {
@@ -243,6 +345,46 @@ impl Debug for ShTypeUnchecked {
     }
 }
 
+impl Debug for DTagUnchecked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Ok(t) = self.known() {
+            t.fmt(f)
+        } else {
+            write!(f, "UnknownDTag(0x{:X?})", self.unknown())
+        }
+    }
+}
+
+impl Debug for RelocX86_64Unchecked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Ok(t) = self.known() {
+            t.fmt(f)
+        } else {
+            write!(f, "UnknownRelocX86_64(0x{:X?})", self.unknown())
+        }
+    }
+}
+
+impl Debug for RelocAarch64Unchecked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Ok(t) = self.known() {
+            t.fmt(f)
+        } else {
+            write!(f, "UnknownRelocAarch64(0x{:X?})", self.unknown())
+        }
+    }
+}
+
+impl Debug for RelocRiscVUnchecked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Ok(t) = self.known() {
+            t.fmt(f)
+        } else {
+            write!(f, "UnknownRelocRiscV(0x{:X?})", self.unknown())
+        }
+    }
+}
+
 impl PartialEq for EIClassUnchecked {
     fn eq(&self, other: &Self) -> bool {
         self.unknown().eq(&other.unknown())
@@ -283,6 +425,30 @@ impl PartialEq for ShTypeUnchecked {
     }
 }
 
+impl PartialEq for DTagUnchecked {
+    fn eq(&self, other: &Self) -> bool {
+        self.unknown().eq(&other.unknown())
+    }
+}
+
+impl PartialEq for RelocX86_64Unchecked {
+    fn eq(&self, other: &Self) -> bool {
+        self.unknown().eq(&other.unknown())
+    }
+}
+
+impl PartialEq for RelocAarch64Unchecked {
+    fn eq(&self, other: &Self) -> bool {
+        self.unknown().eq(&other.unknown())
+    }
+}
+
+impl PartialEq for RelocRiscVUnchecked {
+    fn eq(&self, other: &Self) -> bool {
+        self.unknown().eq(&other.unknown())
+    }
+}
+
 #[test]
 fn miri_enum() {
     use crate::elf::parse::TransmuteSafe;
@@ -337,4 +503,44 @@ fn miri_enum() {
             Err(e) => assert_eq!(e, i),
         }
     }
+    let mut d_tag = DTagUnchecked::default();
+    for i in (0..0x01FFu64).chain(0x6FFFFE00..0x70000000) {
+        let bytes = d_tag.as_bytes_mut();
+        bytes.copy_from_slice(&i.to_le_bytes());
+        assert_eq!(i, d_tag.unknown());
+        match d_tag.known() {
+            Ok(o) => assert_eq!(o as u64, i),
+            Err(e) => assert_eq!(e, i),
+        }
+    }
+    let mut r_type = RelocX86_64Unchecked::default();
+    for i in 0..0x01FFu32 {
+        let bytes = r_type.as_bytes_mut();
+        bytes.copy_from_slice(&i.to_le_bytes());
+        assert_eq!(i, r_type.unknown());
+        match r_type.known() {
+            Ok(o) => assert_eq!(o as u32, i),
+            Err(e) => assert_eq!(e, i),
+        }
+    }
+    let mut r_type = RelocAarch64Unchecked::default();
+    for i in (0..0x01FFu32).chain(0x0400..0x0410) {
+        let bytes = r_type.as_bytes_mut();
+        bytes.copy_from_slice(&i.to_le_bytes());
+        assert_eq!(i, r_type.unknown());
+        match r_type.known() {
+            Ok(o) => assert_eq!(o as u32, i),
+            Err(e) => assert_eq!(e, i),
+        }
+    }
+    let mut r_type = RelocRiscVUnchecked::default();
+    for i in 0..0x01FFu32 {
+        let bytes = r_type.as_bytes_mut();
+        bytes.copy_from_slice(&i.to_le_bytes());
+        assert_eq!(i, r_type.unknown());
+        match r_type.known() {
+            Ok(o) => assert_eq!(o as u32, i),
+            Err(e) => assert_eq!(e, i),
+        }
+    }
 }
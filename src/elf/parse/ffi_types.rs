@@ -1,5 +1,7 @@
 use core::fmt::Debug;
 
+use super::endian::{Endian, Field, LittleEndian};
+
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct EIdent {
@@ -56,11 +58,11 @@ pub union EIOsAbiUnchecked {
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct ElfNonArchDep {
+pub struct ElfNonArchDep<E: Endian = LittleEndian> {
     pub(super) e_ident: EIdent,
-    pub(super) e_type: ETypeUnchecked,
-    pub(super) e_machine: EMachineUnchecked,
-    pub(super) e_version: u32,
+    pub(super) e_type: Field<ETypeUnchecked, E>,
+    pub(super) e_machine: Field<EMachineUnchecked, E>,
+    pub(super) e_version: Field<u32, E>,
 }
 
 #[allow(dead_code)] // These are actually constructed via type re-interpretation
@@ -84,9 +86,22 @@ pub union ETypeUnchecked {
 #[repr(u16)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum EMachine {
+    None = 0x00,
+    Sparc = 0x02,
     X86 = 0x03,
+    M68k = 0x04,
+    Mips = 0x08,
+    PowerPC = 0x14,
+    PowerPC64 = 0x15,
+    S390 = 0x16,
+    Arm = 0x28,
+    SparcV9 = 0x2B,
+    Ia64 = 0x32,
     X86_64 = 0x3E,
     Aarch64 = 0xB7,
+    RiscV = 0xF3,
+    Bpf = 0xF7,
+    LoongArch = 0x102,
 }
 
 #[derive(Copy, Clone)]
@@ -97,72 +112,72 @@ pub union EMachineUnchecked {
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct Elf32Offs {
-    pub(super) e_entry: u32,
-    pub(super) e_phoff: u32,
-    pub(super) e_shoff: u32,
+pub struct Elf32Offs<E: Endian = LittleEndian> {
+    pub(super) e_entry: Field<u32, E>,
+    pub(super) e_phoff: Field<u32, E>,
+    pub(super) e_shoff: Field<u32, E>,
 }
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct Elf64Offs {
-    pub(super) e_entry: u64,
-    pub(super) e_phoff: u64,
-    pub(super) e_shoff: u64,
+pub struct Elf64Offs<E: Endian = LittleEndian> {
+    pub(super) e_entry: Field<u64, E>,
+    pub(super) e_phoff: Field<u64, E>,
+    pub(super) e_shoff: Field<u64, E>,
 }
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct ElfNonArchDep2 {
-    pub(super) e_flags: u32,
-    pub(super) e_ehsize: u16,
-    pub(super) e_phentsize: u16,
-    pub(super) e_phnum: u16,
-    pub(super) e_shentsize: u16,
-    pub(super) e_shnum: u16,
-    pub(super) e_shstrndx: u16,
+pub struct ElfNonArchDep2<E: Endian = LittleEndian> {
+    pub(super) e_flags: Field<u32, E>,
+    pub(super) e_ehsize: Field<u16, E>,
+    pub(super) e_phentsize: Field<u16, E>,
+    pub(super) e_phnum: Field<u16, E>,
+    pub(super) e_shentsize: Field<u16, E>,
+    pub(super) e_shnum: Field<u16, E>,
+    pub(super) e_shstrndx: Field<u16, E>,
 }
 
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq)]
-pub struct ElfHead32<'a> {
-    pub(super) head: &'a ElfNonArchDep,
-    pub(super) offs: &'a Elf32Offs,
-    pub(super) tail: &'a ElfNonArchDep2,
+pub struct ElfHead32<'a, E: Endian = LittleEndian> {
+    pub(super) head: &'a ElfNonArchDep<E>,
+    pub(super) offs: &'a Elf32Offs<E>,
+    pub(super) tail: &'a ElfNonArchDep2<E>,
 }
 
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq)]
-pub struct ElfHead64<'a> {
-    pub(super) head: &'a ElfNonArchDep,
-    pub(super) offs: &'a Elf64Offs,
-    pub(super) tail: &'a ElfNonArchDep2,
+pub struct ElfHead64<'a, E: Endian = LittleEndian> {
+    pub(super) head: &'a ElfNonArchDep<E>,
+    pub(super) offs: &'a Elf64Offs<E>,
+    pub(super) tail: &'a ElfNonArchDep2<E>,
 }
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct ProgHead64 {
-    pub(super) p_type: PTypeUnchecked,
-    pub(super) p_flag: u32,
-    pub(super) p_offset: u64,
-    pub(super) p_vaddr: u64,
-    pub(super) p_paddr: u64,
-    pub(super) p_filesz: u64,
-    pub(super) p_memsz: u64,
-    pub(super) p_align: u64,
+pub struct ProgHead64<E: Endian = LittleEndian> {
+    pub(super) p_type: Field<PTypeUnchecked, E>,
+    pub(super) p_flag: Field<u32, E>,
+    pub(super) p_offset: Field<u64, E>,
+    pub(super) p_vaddr: Field<u64, E>,
+    pub(super) p_paddr: Field<u64, E>,
+    pub(super) p_filesz: Field<u64, E>,
+    pub(super) p_memsz: Field<u64, E>,
+    pub(super) p_align: Field<u64, E>,
 }
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct ProgHead32 {
-    pub(super) p_type: PTypeUnchecked,
-    pub(super) p_offset: u32,
-    pub(super) p_vaddr: u32,
-    pub(super) p_paddr: u32,
-    pub(super) p_filesz: u32,
-    pub(super) p_memsz: u32,
-    pub(super) p_flag: u32,
-    pub(super) p_align: u32,
+pub struct ProgHead32<E: Endian = LittleEndian> {
+    pub(super) p_type: Field<PTypeUnchecked, E>,
+    pub(super) p_offset: Field<u32, E>,
+    pub(super) p_vaddr: Field<u32, E>,
+    pub(super) p_paddr: Field<u32, E>,
+    pub(super) p_filesz: Field<u32, E>,
+    pub(super) p_memsz: Field<u32, E>,
+    pub(super) p_flag: Field<u32, E>,
+    pub(super) p_align: Field<u32, E>,
 }
 
 #[allow(dead_code)] // These are actually constructed via type re-interpretation
@@ -180,6 +195,7 @@ pub enum PType {
     GnuEhFrame = 0x6474e550,
     GnuStack = 0x6474e551,
     GnuRelro = 0x6474e552,
+    GnuProperty = 0x6474e553,
 }
 
 #[derive(Copy, Clone)]
@@ -190,9 +206,9 @@ pub union PTypeUnchecked {
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct SectNonArchDep {
-    pub(super) sh_name: u32,
-    pub(super) sh_type: ShTypeUnchecked,
+pub struct SectNonArchDep<E: Endian = LittleEndian> {
+    pub(super) sh_name: Field<u32, E>,
+    pub(super) sh_type: Field<ShTypeUnchecked, E>,
 }
 
 #[allow(dead_code)] // These are actually constructed via type re-interpretation
@@ -231,52 +247,364 @@ pub union ShTypeUnchecked {
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct SectHead32 {
-    pub(super) head: SectNonArchDep,
-    pub(super) sh_flags: u32,
-    pub(super) sh_addr: u32,
-    pub(super) sh_offset: u32,
-    pub(super) sh_size: u32,
-    pub(super) sh_link: u32,
-    pub(super) sh_info: u32,
-    pub(super) sh_addralign: u32,
-    pub(super) sh_entsize: u32,
+pub struct SectHead32<E: Endian = LittleEndian> {
+    pub(super) head: SectNonArchDep<E>,
+    pub(super) sh_flags: Field<u32, E>,
+    pub(super) sh_addr: Field<u32, E>,
+    pub(super) sh_offset: Field<u32, E>,
+    pub(super) sh_size: Field<u32, E>,
+    pub(super) sh_link: Field<u32, E>,
+    pub(super) sh_info: Field<u32, E>,
+    pub(super) sh_addralign: Field<u32, E>,
+    pub(super) sh_entsize: Field<u32, E>,
 }
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct SectHead64 {
-    pub(super) head: SectNonArchDep,
-    pub(super) sh_flags: u64,
-    pub(super) sh_addr: u64,
-    pub(super) sh_offset: u64,
-    pub(super) sh_size: u64,
-    pub(super) sh_link: u32,
-    pub(super) sh_info: u32,
-    pub(super) sh_addralign: u64,
-    pub(super) sh_entsize: u64,
+pub struct SectHead64<E: Endian = LittleEndian> {
+    pub(super) head: SectNonArchDep<E>,
+    pub(super) sh_flags: Field<u64, E>,
+    pub(super) sh_addr: Field<u64, E>,
+    pub(super) sh_offset: Field<u64, E>,
+    pub(super) sh_size: Field<u64, E>,
+    pub(super) sh_link: Field<u32, E>,
+    pub(super) sh_info: Field<u32, E>,
+    pub(super) sh_addralign: Field<u64, E>,
+    pub(super) sh_entsize: Field<u64, E>,
 }
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct Sym32 {
-    pub(super) st_name: u32,
-    pub(super) st_value: u32,
-    pub(super) st_size: u32,
+pub struct Sym32<E: Endian = LittleEndian> {
+    pub(super) st_name: Field<u32, E>,
+    pub(super) st_value: Field<u32, E>,
+    pub(super) st_size: Field<u32, E>,
     pub(super) st_info: u8,
     pub(super) st_other: u8,
-    pub(super) st_shndx: u16,
+    pub(super) st_shndx: Field<u16, E>,
 }
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct Sym64 {
-    pub(super) st_name: u32,
+pub struct Sym64<E: Endian = LittleEndian> {
+    pub(super) st_name: Field<u32, E>,
     pub(super) st_info: u8,
     pub(super) st_other: u8,
-    pub(super) st_shndx: u16,
-    pub(super) st_value: u64,
-    pub(super) st_size: u64,
+    pub(super) st_shndx: Field<u16, E>,
+    pub(super) st_value: Field<u64, E>,
+    pub(super) st_size: Field<u64, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Dyn32<E: Endian = LittleEndian> {
+    pub(super) d_tag: Field<u32, E>,
+    pub(super) d_val: Field<u32, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Dyn64<E: Endian = LittleEndian> {
+    pub(super) d_tag: Field<DTagUnchecked, E>,
+    pub(super) d_val: Field<u64, E>,
+}
+
+#[allow(dead_code)] // These are actually constructed via type re-interpretation
+#[repr(u64)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DTag {
+    Null = 0,
+    Needed = 1,
+    PltRelSz = 2,
+    PltGot = 3,
+    Hash = 4,
+    StrTab = 5,
+    SymTab = 6,
+    Rela = 7,
+    RelaSz = 8,
+    RelaEnt = 9,
+    StrSz = 10,
+    SymEnt = 11,
+    Init = 12,
+    Fini = 13,
+    SoName = 14,
+    RPath = 15,
+    Symbolic = 16,
+    Rel = 17,
+    RelSz = 18,
+    RelEnt = 19,
+    PltRel = 20,
+    Debug = 21,
+    TextRel = 22,
+    JmpRel = 23,
+    BindNow = 24,
+    InitArray = 25,
+    FiniArray = 26,
+    InitArraySz = 27,
+    FiniArraySz = 28,
+    RunPath = 29,
+    Flags = 30,
+    PreinitArray = 32,
+    PreinitArraySz = 33,
+    SymtabShndx = 34,
+    RelrSz = 35,
+    Relr = 36,
+    RelrEnt = 37,
+    GnuHash = 0x6FFFFEF5,
+    VerSym = 0x6FFFFFF0,
+    RelaCount = 0x6FFFFFF9,
+    RelCount = 0x6FFFFFFA,
+    Flags1 = 0x6FFFFFFB,
+    VerDef = 0x6FFFFFFC,
+    VerDefNum = 0x6FFFFFFD,
+    VerNeed = 0x6FFFFFFE,
+    VerNeedNum = 0x6FFFFFFF,
+}
+
+#[derive(Copy, Clone)]
+pub union DTagUnchecked {
+    pub(super) unknown: u64,
+    pub(super) known: DTag,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Rel32<E: Endian = LittleEndian> {
+    pub(super) r_offset: Field<u32, E>,
+    pub(super) r_info: Field<u32, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Rela32<E: Endian = LittleEndian> {
+    pub(super) r_offset: Field<u32, E>,
+    pub(super) r_info: Field<u32, E>,
+    pub(super) r_addend: Field<i32, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Rel64<E: Endian = LittleEndian> {
+    pub(super) r_offset: Field<u64, E>,
+    pub(super) r_info: Field<u64, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Rela64<E: Endian = LittleEndian> {
+    pub(super) r_offset: Field<u64, E>,
+    pub(super) r_info: Field<u64, E>,
+    pub(super) r_addend: Field<i64, E>,
+}
+
+// The symbol versioning structures have the same layout in both ELF classes
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Verdef<E: Endian = LittleEndian> {
+    pub(super) vd_version: Field<u16, E>,
+    pub(super) vd_flags: Field<u16, E>,
+    pub(super) vd_ndx: Field<u16, E>,
+    pub(super) vd_cnt: Field<u16, E>,
+    pub(super) vd_hash: Field<u32, E>,
+    pub(super) vd_aux: Field<u32, E>,
+    pub(super) vd_next: Field<u32, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Verdaux<E: Endian = LittleEndian> {
+    pub(super) vda_name: Field<u32, E>,
+    pub(super) vda_next: Field<u32, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Verneed<E: Endian = LittleEndian> {
+    pub(super) vn_version: Field<u16, E>,
+    pub(super) vn_cnt: Field<u16, E>,
+    pub(super) vn_file: Field<u32, E>,
+    pub(super) vn_aux: Field<u32, E>,
+    pub(super) vn_next: Field<u32, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Vernaux<E: Endian = LittleEndian> {
+    pub(super) vna_hash: Field<u32, E>,
+    pub(super) vna_flags: Field<u16, E>,
+    pub(super) vna_other: Field<u16, E>,
+    pub(super) vna_name: Field<u32, E>,
+    pub(super) vna_next: Field<u32, E>,
+}
+
+#[allow(dead_code)] // These are actually constructed via type re-interpretation
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RelocX86_64 {
+    None = 0,
+    R64 = 1,
+    Pc32 = 2,
+    Got32 = 3,
+    Plt32 = 4,
+    Copy = 5,
+    GlobDat = 6,
+    JumpSlot = 7,
+    Relative = 8,
+    GotPcRel = 9,
+    R32 = 10,
+    R32S = 11,
+    R16 = 12,
+    Pc16 = 13,
+    R8 = 14,
+    Pc8 = 15,
+    DtpMod64 = 16,
+    DtpOff64 = 17,
+    TpOff64 = 18,
+    TlsGd = 19,
+    TlsLd = 20,
+    DtpOff32 = 21,
+    GotTpOff = 22,
+    TpOff32 = 23,
+    Pc64 = 24,
+    GotOff64 = 25,
+    GotPc32 = 26,
+    Got64 = 27,
+    GotPcRel64 = 28,
+    GotPc64 = 29,
+    GotPlt64 = 30,
+    PltOff64 = 31,
+    Size32 = 32,
+    Size64 = 33,
+    GotPc32TlsDesc = 34,
+    TlsDescCall = 35,
+    TlsDesc = 36,
+    IRelative = 37,
+    Relative64 = 38,
+    GotPcRelX = 41,
+    RexGotPcRelX = 42,
+}
+
+#[derive(Copy, Clone)]
+pub union RelocX86_64Unchecked {
+    pub(super) unknown: u32,
+    pub(super) known: RelocX86_64,
+}
+
+#[allow(dead_code)] // These are actually constructed via type re-interpretation
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RelocAarch64 {
+    None = 0,
+    Abs64 = 257,
+    Abs32 = 258,
+    Abs16 = 259,
+    Prel64 = 260,
+    Prel32 = 261,
+    Prel16 = 262,
+    MovwUabsG0 = 263,
+    MovwUabsG0Nc = 264,
+    MovwUabsG1 = 265,
+    MovwUabsG1Nc = 266,
+    MovwUabsG2 = 267,
+    MovwUabsG2Nc = 268,
+    MovwUabsG3 = 269,
+    MovwSabsG0 = 270,
+    MovwSabsG1 = 271,
+    MovwSabsG2 = 272,
+    LdPrelLo19 = 273,
+    AdrPrelLo21 = 274,
+    AdrPrelPgHi21 = 275,
+    AdrPrelPgHi21Nc = 276,
+    AddAbsLo12Nc = 277,
+    Ldst8AbsLo12Nc = 278,
+    TstBr14 = 279,
+    CondBr19 = 280,
+    Jump26 = 282,
+    Call26 = 283,
+    Ldst16AbsLo12Nc = 284,
+    Ldst32AbsLo12Nc = 285,
+    Ldst64AbsLo12Nc = 286,
+    Ldst128AbsLo12Nc = 299,
+    AdrGotPage = 311,
+    Ld64GotLo12Nc = 312,
+    Copy = 1024,
+    GlobDat = 1025,
+    JumpSlot = 1026,
+    Relative = 1027,
+    TlsDtpMod64 = 1028,
+    TlsDtpRel64 = 1029,
+    TlsTpRel64 = 1030,
+    TlsDesc = 1031,
+    IRelative = 1032,
+}
+
+#[derive(Copy, Clone)]
+pub union RelocAarch64Unchecked {
+    pub(super) unknown: u32,
+    pub(super) known: RelocAarch64,
+}
+
+#[allow(dead_code)] // These are actually constructed via type re-interpretation
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RelocRiscV {
+    None = 0,
+    R32 = 1,
+    R64 = 2,
+    Relative = 3,
+    Copy = 4,
+    JumpSlot = 5,
+    TlsDtpMod32 = 6,
+    TlsDtpMod64 = 7,
+    TlsDtpRel32 = 8,
+    TlsDtpRel64 = 9,
+    TlsTpRel32 = 10,
+    TlsTpRel64 = 11,
+    TlsDesc = 12,
+    Branch = 16,
+    Jal = 17,
+    Call = 18,
+    CallPlt = 19,
+    GotHi20 = 20,
+    TlsGotHi20 = 21,
+    TlsGdHi20 = 22,
+    PcRelHi20 = 23,
+    PcRelLo12I = 24,
+    PcRelLo12S = 25,
+    Hi20 = 26,
+    Lo12I = 27,
+    Lo12S = 28,
+    TpRelHi20 = 29,
+    TpRelLo12I = 30,
+    TpRelLo12S = 31,
+    TpRelAdd = 32,
+    Add8 = 33,
+    Add16 = 34,
+    Add32 = 35,
+    Add64 = 36,
+    Sub8 = 37,
+    Sub16 = 38,
+    Sub32 = 39,
+    Sub64 = 40,
+    Align = 43,
+    RvcBranch = 44,
+    RvcJump = 45,
+    Relax = 51,
+    Sub6 = 52,
+    Set6 = 53,
+    Set8 = 54,
+    Set16 = 55,
+    Set32 = 56,
+    R32PcRel = 57,
+    IRelative = 58,
+    Plt32 = 59,
+}
+
+#[derive(Copy, Clone)]
+pub union RelocRiscVUnchecked {
+    pub(super) unknown: u32,
+    pub(super) known: RelocRiscV,
 }
 
 // These unsafe implementations are sound, because each of the implemeting types
@@ -286,17 +614,28 @@ pub struct Sym64 {
 use crate::utils::TransmuteSafe;
 
 
-unsafe impl TransmuteSafe for ElfNonArchDep {}
-unsafe impl TransmuteSafe for Elf32Offs {}
-unsafe impl TransmuteSafe for Elf64Offs {}
-unsafe impl TransmuteSafe for ElfNonArchDep2 {}
-unsafe impl TransmuteSafe for ProgHead32 {}
-unsafe impl TransmuteSafe for ProgHead64 {}
-unsafe impl TransmuteSafe for SectHead32 {}
-unsafe impl TransmuteSafe for SectHead64 {}
-unsafe impl TransmuteSafe for Sym32 {}
-unsafe impl TransmuteSafe for Sym64 {}
-
+unsafe impl<E: Endian> TransmuteSafe for ElfNonArchDep<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Elf32Offs<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Elf64Offs<E> {}
+unsafe impl<E: Endian> TransmuteSafe for ElfNonArchDep2<E> {}
+unsafe impl<E: Endian> TransmuteSafe for ProgHead32<E> {}
+unsafe impl<E: Endian> TransmuteSafe for ProgHead64<E> {}
+unsafe impl<E: Endian> TransmuteSafe for SectHead32<E> {}
+unsafe impl<E: Endian> TransmuteSafe for SectHead64<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Sym32<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Sym64<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Dyn32<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Dyn64<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Rel32<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Rela32<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Rel64<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Rela64<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Verdef<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Verdaux<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Verneed<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Vernaux<E> {}
+
+unsafe impl TransmuteSafe for EIdent {}
 unsafe impl TransmuteSafe for EIClassUnchecked {}
 unsafe impl TransmuteSafe for EIDataUnchecked {}
 unsafe impl TransmuteSafe for EIOsAbiUnchecked {}
@@ -304,6 +643,10 @@ unsafe impl TransmuteSafe for ETypeUnchecked {}
 unsafe impl TransmuteSafe for EMachineUnchecked {}
 unsafe impl TransmuteSafe for PTypeUnchecked {}
 unsafe impl TransmuteSafe for ShTypeUnchecked {}
+unsafe impl TransmuteSafe for DTagUnchecked {}
+unsafe impl TransmuteSafe for RelocX86_64Unchecked {}
+unsafe impl TransmuteSafe for RelocAarch64Unchecked {}
+unsafe impl TransmuteSafe for RelocRiscVUnchecked {}
 
 // To ensure that there isn't any accidental padding etc.
 #[test]
@@ -314,8 +657,6 @@ fn sizes_and_alignments() {
     assert_eq!(align_of::<Elf32Offs>(), 4);
     assert_eq!(align_of::<Elf64Offs>(), 8);
     assert_eq!(align_of::<ElfNonArchDep2>(), 4);
-    assert_eq!(align_of::<ElfHead32>(), 4);
-    assert_eq!(align_of::<ElfHead64>(), 8);
     assert_eq!(align_of::<ProgHead32>(), 4);
     assert_eq!(align_of::<ProgHead64>(), 8);
     assert_eq!(align_of::<SectNonArchDep>(), 4);
@@ -323,6 +664,16 @@ fn sizes_and_alignments() {
     assert_eq!(align_of::<SectHead64>(), 8);
     assert_eq!(align_of::<Sym32>(), 4);
     assert_eq!(align_of::<Sym64>(), 8);
+    assert_eq!(align_of::<Dyn32>(), 4);
+    assert_eq!(align_of::<Dyn64>(), 8);
+    assert_eq!(align_of::<Rel32>(), 4);
+    assert_eq!(align_of::<Rela32>(), 4);
+    assert_eq!(align_of::<Rel64>(), 8);
+    assert_eq!(align_of::<Rela64>(), 8);
+    assert_eq!(align_of::<Verdef>(), 4);
+    assert_eq!(align_of::<Verdaux>(), 4);
+    assert_eq!(align_of::<Verneed>(), 4);
+    assert_eq!(align_of::<Vernaux>(), 4);
 
     assert_eq!(align_of::<EIClass>(), 1);
     assert_eq!(align_of::<EIClassUnchecked>(), 1);
@@ -336,14 +687,23 @@ fn sizes_and_alignments() {
     assert_eq!(align_of::<PTypeUnchecked>(), 4);
     assert_eq!(align_of::<ShType>(), 4);
     assert_eq!(align_of::<ShTypeUnchecked>(), 4);
+    assert_eq!(align_of::<DTag>(), 8);
+    assert_eq!(align_of::<DTagUnchecked>(), 8);
+    assert_eq!(align_of::<RelocX86_64>(), 4);
+    assert_eq!(align_of::<RelocX86_64Unchecked>(), 4);
+    assert_eq!(align_of::<RelocAarch64>(), 4);
+    assert_eq!(align_of::<RelocAarch64Unchecked>(), 4);
+    assert_eq!(align_of::<RelocRiscV>(), 4);
+    assert_eq!(align_of::<RelocRiscVUnchecked>(), 4);
 
     assert_eq!(size_of::<EIdent>(), 16);
     assert_eq!(size_of::<ElfNonArchDep>(), 24);
     assert_eq!(size_of::<Elf32Offs>(), 12);
     assert_eq!(size_of::<Elf64Offs>(), 24);
     assert_eq!(size_of::<ElfNonArchDep2>(), 16);
-    assert_eq!(size_of::<ElfHead32>(), 52);
-    assert_eq!(size_of::<ElfHead64>(), 64);
+    // ElfHead32 and ElfHead64 only point to the parts of the header
+    assert_eq!(size_of::<ElfNonArchDep>() + size_of::<Elf32Offs>() + size_of::<ElfNonArchDep2>(), 52);
+    assert_eq!(size_of::<ElfNonArchDep>() + size_of::<Elf64Offs>() + size_of::<ElfNonArchDep2>(), 64);
     assert_eq!(size_of::<ProgHead32>(), 32);
     assert_eq!(size_of::<ProgHead64>(), 56);
     assert_eq!(size_of::<SectNonArchDep>(), 8);
@@ -351,6 +711,16 @@ fn sizes_and_alignments() {
     assert_eq!(size_of::<SectHead64>(), 64);
     assert_eq!(size_of::<Sym32>(), 16);
     assert_eq!(size_of::<Sym64>(), 24);
+    assert_eq!(size_of::<Dyn32>(), 8);
+    assert_eq!(size_of::<Dyn64>(), 16);
+    assert_eq!(size_of::<Rel32>(), 8);
+    assert_eq!(size_of::<Rela32>(), 12);
+    assert_eq!(size_of::<Rel64>(), 16);
+    assert_eq!(size_of::<Rela64>(), 24);
+    assert_eq!(size_of::<Verdef>(), 20);
+    assert_eq!(size_of::<Verdaux>(), 8);
+    assert_eq!(size_of::<Verneed>(), 16);
+    assert_eq!(size_of::<Vernaux>(), 16);
 
     assert_eq!(size_of::<EIClass>(), 1);
     assert_eq!(size_of::<EIClassUnchecked>(), 1);
@@ -364,6 +734,14 @@ fn sizes_and_alignments() {
     assert_eq!(size_of::<PTypeUnchecked>(), 4);
     assert_eq!(size_of::<ShType>(), 4);
     assert_eq!(size_of::<ShTypeUnchecked>(), 4);
+    assert_eq!(size_of::<DTag>(), 8);
+    assert_eq!(size_of::<DTagUnchecked>(), 8);
+    assert_eq!(size_of::<RelocX86_64>(), 4);
+    assert_eq!(size_of::<RelocX86_64Unchecked>(), 4);
+    assert_eq!(size_of::<RelocAarch64>(), 4);
+    assert_eq!(size_of::<RelocAarch64Unchecked>(), 4);
+    assert_eq!(size_of::<RelocRiscV>(), 4);
+    assert_eq!(size_of::<RelocRiscVUnchecked>(), 4);
 }
 
 #[test]
@@ -388,10 +766,17 @@ fn miri_as_bytes_mut() {
     test::<SectHead64>();
     test::<Sym32>();
     test::<Sym64>();
+    test::<Dyn32>();
+    test::<Dyn64>();
+    test::<Rel32>();
+    test::<Rela32>();
+    test::<Rel64>();
+    test::<Rela64>();
 }
 
 #[test]
 fn miri_vec_as_bytes_mut() {
+    use crate::utils::vec_as_bytes_mut;
     use rand::Fill;
     fn test<T: TransmuteSafe>() -> Vec<T> {
         let mut vec = Vec::new();
@@ -413,4 +798,10 @@ fn miri_vec_as_bytes_mut() {
     test::<SectHead64>();
     test::<Sym32>();
     test::<Sym64>();
+    test::<Dyn32>();
+    test::<Dyn64>();
+    test::<Rel32>();
+    test::<Rela32>();
+    test::<Rel64>();
+    test::<Rela64>();
 }
@@ -0,0 +1,184 @@
+use core::mem::size_of;
+
+use crate::{e, Error, error::ElfError, utils::TransmuteSafe};
+
+use super::{bytes_at, sect_entries, endian::{Endian, Field, LittleEndian, Swap}, SectHead, ShType, Strings, Sym};
+
+/// The hash function of `SHT_GNU_HASH` tables.
+pub fn gnu_hash(name: &[u8]) -> u32 {
+    name.iter().fold(5381u32, |h, &c| h.wrapping_mul(33).wrapping_add(c as u32))
+}
+
+/// The hash function of SysV `SHT_HASH` tables.
+pub fn sysv_hash(name: &[u8]) -> u32 {
+    name.iter().fold(0u32, |h, &c| {
+        let h = (h << 4).wrapping_add(c as u32);
+        let g = h & 0xf000_0000;
+        (h ^ (g >> 24)) & !g
+    })
+}
+
+fn truncated<T>(sh: &impl SectHead) -> Result<T, Error> {
+    e(ElfError::TruncatedHashTable { offset: sh.offset(), size: sh.size() })
+}
+
+fn section<'a>(buf: &'a [u8], sh: &impl SectHead) -> Result<&'a [u8], Error> {
+    match bytes_at(buf, sh.offset(), sh.size()) {
+        Some(bytes) => Ok(bytes),
+        None => truncated(sh),
+    }
+}
+
+/// `n` words from the start of `buf`, a part of the hash section `sh`.
+fn words<'a, T: TransmuteSafe>(buf: &'a [u8], n: usize, sh: &impl SectHead) -> Result<(&'a [T], &'a [u8]), Error> {
+    match T::slice_from_buf(buf, n) {
+        Ok(o) => Ok(o),
+        Err(_) => truncated(sh),
+    }
+}
+
+fn is_named<S: Sym>(sym: &S, name: &[u8], names: &Strings) -> bool {
+    sym.name(names).is_ok_and(|n| n == name)
+}
+
+/// A `SHT_GNU_HASH` table. `W` is the word size of the bloom filter, which follows the ELF class.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GnuHash<'a, W: Swap, E: Endian = LittleEndian> {
+    /// Index of the first symbol that is covered by the table.
+    pub symoffset: u32,
+    pub bloom_shift: u32,
+    pub bloom: &'a [Field<W, E>],
+    pub buckets: &'a [Field<u32, E>],
+    pub chains: &'a [Field<u32, E>],
+}
+
+impl<'a, W: TransmuteSafe + Swap + Into<u64>, E: Endian> GnuHash<'a, W, E> {
+    pub fn from(buf: &'a [u8], sh: &impl SectHead) -> Result<GnuHash<'a, W, E>, Error> {
+        let (header, tail) = words::<Field<u32, E>>(section(buf, sh)?, 4, sh)?;
+        let [nbuckets, symoffset, bloom_size, bloom_shift] = [0, 1, 2, 3].map(|i| header[i].get());
+        if bloom_size == 0 {
+            return e(ElfError::EmptyBloomFilter);
+        }
+        let (bloom, tail) = words(tail, bloom_size as usize, sh)?;
+        let (buckets, tail) = words(tail, nbuckets as usize, sh)?;
+        let (chains, _) = words(tail, tail.len() / size_of::<u32>(), sh)?;
+        Ok(GnuHash { symoffset, bloom_shift, bloom, buckets, chains })
+    }
+
+    /// Index of the symbol called `name` in `syms`, the symbol table the hash table belongs to.
+    /// The symbols before `symoffset` aren't in the table, so they're scanned; they're usually the undefined ones.
+    pub fn find<S: Sym>(&self, name: &[u8], syms: &[S], names: &Strings) -> Option<usize> {
+        self.find_hashed(name, syms, names).or_else(|| {
+            let unhashed = syms.get(..self.symoffset as usize).unwrap_or(syms);
+            unhashed.iter().position(|sym| is_named(sym, name, names))
+        })
+    }
+
+    fn find_hashed<S: Sym>(&self, name: &[u8], syms: &[S], names: &Strings) -> Option<usize> {
+        let h = gnu_hash(name);
+        let bits = 8 * size_of::<W>() as u32;
+        let word: u64 = self.bloom[(h / bits) as usize % self.bloom.len()].get().into();
+        let mask = 1u64 << (h % bits) | 1u64 << (h.checked_shr(self.bloom_shift).unwrap_or(0) % bits);
+        if word & mask != mask || self.buckets.is_empty() {
+            return None;
+        }
+        let symoffset = self.symoffset as usize;
+        let start = self.buckets[h as usize % self.buckets.len()].get() as usize;
+        if start < symoffset {
+            return None;
+        }
+        for i in start..symoffset.saturating_add(self.chains.len()) {
+            let chain_h = self.chains[i - symoffset].get();
+            if h | 1 == chain_h | 1 && is_named(syms.get(i)?, name, names) {
+                return Some(i);
+            }
+            // The lowest bit marks the end of a bucket's chain
+            if chain_h & 1 == 1 {
+                break;
+            }
+        }
+        None
+    }
+}
+
+/// A SysV `SHT_HASH` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SysvHash<'a, E: Endian = LittleEndian> {
+    pub buckets: &'a [Field<u32, E>],
+    pub chains: &'a [Field<u32, E>],
+}
+
+impl<'a, E: Endian> SysvHash<'a, E> {
+    pub fn from(buf: &'a [u8], sh: &impl SectHead) -> Result<SysvHash<'a, E>, Error> {
+        let (header, tail) = words::<Field<u32, E>>(section(buf, sh)?, 2, sh)?;
+        let (buckets, tail) = words(tail, header[0].get() as usize, sh)?;
+        let (chains, _) = words(tail, header[1].get() as usize, sh)?;
+        Ok(SysvHash { buckets, chains })
+    }
+
+    /// Index of the symbol called `name` in `syms`, the symbol table the hash table belongs to.
+    pub fn find<S: Sym>(&self, name: &[u8], syms: &[S], names: &Strings) -> Option<usize> {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let mut i = self.buckets[sysv_hash(name) as usize % self.buckets.len()].get() as usize;
+        // Bounded by the chain length so that a cyclic chain can't hang the lookup
+        for _ in 0..self.chains.len() {
+            if i == 0 {
+                break;
+            }
+            if is_named(syms.get(i)?, name, names) {
+                return Some(i);
+            }
+            i = self.chains.get(i)?.get() as usize;
+        }
+        None
+    }
+}
+
+/// The symbol table and string table that a hash section links to.
+fn linked_syms<'a, T: SectHead>(
+    buf: &'a [u8],
+    shs: &[T],
+    index: usize,
+    sh: &T,
+) -> Result<(&'a [T::SymTab], Strings<'a>), Error> {
+    let symtab = match shs.get(sh.link()) {
+        Some(symtab) => symtab,
+        None => return e(ElfError::BadLink { index, link: sh.link() }),
+    };
+    let names = match shs.get(symtab.link()) {
+        Some(strtab) => Strings::from(buf, symtab.link(), strtab)?,
+        None => return e(ElfError::BadLink { index: sh.link(), link: symtab.link() }),
+    };
+    Ok((sect_entries(buf, sh.link(), symtab)?, names))
+}
+
+/// Looks `name` up through the `SHT_GNU_HASH` or, failing that, the `SHT_HASH` section.
+/// Returns `None` if neither is present or usable, and `Some(None)` if the table has no such symbol.
+pub(super) fn lookup<'a, T: SectHead, W: TransmuteSafe + Swap + Into<u64>, E: Endian>(
+    buf: &'a [u8],
+    shs: &[T],
+    name: &[u8],
+) -> Option<Option<&'a T::SymTab>> {
+    let find = |ty: ShType| shs.iter().enumerate().find(|(_, sh)| sh.sh_type() == Ok(ty));
+    if let Some((index, sh)) = find(ShType::GnuHash) {
+        if let (Ok(table), Ok((syms, names))) = (GnuHash::<W, E>::from(buf, sh), linked_syms(buf, shs, index, sh)) {
+            return Some(table.find(name, syms, &names).map(|i| &syms[i]));
+        }
+    }
+    if let Some((index, sh)) = find(ShType::Hash) {
+        if let (Ok(table), Ok((syms, names))) = (SysvHash::<E>::from(buf, sh), linked_syms(buf, shs, index, sh)) {
+            return Some(table.find(name, syms, &names).map(|i| &syms[i]));
+        }
+    }
+    None
+}
+
+/// Finds `name` by a linear scan over a symbol table.
+pub(super) fn scan<'a, S: Sym>(syms: Option<&'a [S]>, names: Option<&Strings>, name: &[u8]) -> Option<&'a S> {
+    match (syms, names) {
+        (Some(syms), Some(names)) => syms.iter().find(|sym| is_named(*sym, name, names)),
+        _ => None,
+    }
+}
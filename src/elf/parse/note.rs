@@ -0,0 +1,269 @@
+use core::marker::PhantomData;
+
+use crate::{e, Error, error::ElfError};
+
+use super::{Endian, LittleEndian, ProgHead, PType, SectHead, ShType};
+
+pub const NT_GNU_ABI_TAG: u32 = 1;
+pub const NT_GNU_BUILD_ID: u32 = 3;
+pub const NT_GNU_PROPERTY_TYPE_0: u32 = 5;
+
+pub const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xc0000002;
+pub const GNU_PROPERTY_X86_FEATURE_1_IBT: u32 = 0x1;
+pub const GNU_PROPERTY_X86_FEATURE_1_SHSTK: u32 = 0x2;
+
+fn u32_at<E: Endian>(buf: &[u8], offset: usize) -> Option<u32> {
+    let bytes = buf.get(offset..offset.checked_add(4)?)?;
+    Some(E::u32([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn align_up(n: usize, align: usize) -> Option<usize> {
+    Some(n.checked_add(align - 1)? & !(align - 1))
+}
+
+/// A single entry of a `PT_NOTE` segment or a `SHT_NOTE` section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note<'a, E: Endian = LittleEndian> {
+    /// The owner of the note, without the terminating null byte.
+    pub name: &'a [u8],
+    pub n_type: u32,
+    pub desc: &'a [u8],
+    align: usize,
+    endian: PhantomData<E>,
+}
+
+/// The notes that quack knows how to decode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NoteKind<'a, E: Endian = LittleEndian> {
+    GnuBuildId(&'a [u8]),
+    /// The OS (0 for Linux) and the oldest kernel version the binary supports.
+    GnuAbiTag { os: u32, version: [u32; 3] },
+    GnuProperties(GnuProperties<'a, E>),
+    Other,
+}
+
+impl<'a, E: Endian> Note<'a, E> {
+    pub fn kind(&self) -> Result<NoteKind<'a, E>, Error> {
+        if self.name != b"GNU" {
+            return Ok(NoteKind::Other);
+        }
+        match self.n_type {
+            NT_GNU_BUILD_ID => Ok(NoteKind::GnuBuildId(self.desc)),
+            NT_GNU_ABI_TAG => match [0, 4, 8, 12].map(|offset| u32_at::<E>(self.desc, offset)) {
+                [Some(os), Some(major), Some(minor), Some(subminor)] => {
+                    Ok(NoteKind::GnuAbiTag { os, version: [major, minor, subminor] })
+                }
+                _ => e(ElfError::TruncatedAbiTag),
+            },
+            NT_GNU_PROPERTY_TYPE_0 => Ok(NoteKind::GnuProperties(GnuProperties {
+                desc: self.desc,
+                align: self.align,
+                offset: 0,
+                endian: PhantomData,
+            })),
+            _ => Ok(NoteKind::Other),
+        }
+    }
+}
+
+/// A property of a `NT_GNU_PROPERTY_TYPE_0` note.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GnuProperty<'a, E: Endian = LittleEndian> {
+    pub pr_type: u32,
+    pub data: &'a [u8],
+    endian: PhantomData<E>,
+}
+
+impl<E: Endian> GnuProperty<'_, E> {
+    /// The `GNU_PROPERTY_X86_FEATURE_1_*` bits, if this is the x86 feature property.
+    pub fn x86_features(&self) -> Option<u32> {
+        match self.pr_type {
+            GNU_PROPERTY_X86_FEATURE_1_AND => u32_at::<E>(self.data, 0),
+            _ => None,
+        }
+    }
+}
+
+/// The properties of a `NT_GNU_PROPERTY_TYPE_0` note.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GnuProperties<'a, E: Endian = LittleEndian> {
+    desc: &'a [u8],
+    align: usize,
+    offset: usize,
+    endian: PhantomData<E>,
+}
+
+impl<'a, E: Endian> GnuProperties<'a, E> {
+    /// Whether the object is marked as compatible with Indirect Branch Tracking.
+    pub fn ibt(&self) -> bool {
+        self.x86_features() & GNU_PROPERTY_X86_FEATURE_1_IBT != 0
+    }
+
+    /// Whether the object is marked as compatible with Shadow Stacks.
+    pub fn shstk(&self) -> bool {
+        self.x86_features() & GNU_PROPERTY_X86_FEATURE_1_SHSTK != 0
+    }
+
+    fn x86_features(&self) -> u32 {
+        self.clone()
+            .filter_map(Result::ok)
+            .find_map(|p| p.x86_features())
+            .unwrap_or(0)
+    }
+}
+
+impl<'a, E: Endian> Iterator for GnuProperties<'a, E> {
+    type Item = Result<GnuProperty<'a, E>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.desc.len() {
+            return None;
+        }
+        let property = (|| {
+            let pr_type = u32_at::<E>(self.desc, self.offset)?;
+            let datasz = u32_at::<E>(self.desc, self.offset + 4)? as usize;
+            let start = self.offset + 8;
+            let data = self.desc.get(start..start.checked_add(datasz)?)?;
+            // Properties are padded to the word size, which is also the alignment of the note
+            let next = align_up(start + datasz, self.align)?;
+            Some((GnuProperty { pr_type, data, endian: PhantomData }, next))
+        })();
+        match property {
+            Some((property, next)) => {
+                self.offset = next;
+                Some(Ok(property))
+            }
+            None => {
+                let offset = self.offset;
+                self.offset = self.desc.len();
+                Some(e(ElfError::TruncatedProperty(offset)))
+            }
+        }
+    }
+}
+
+/// The notes of one note segment or section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notes<'a, E: Endian = LittleEndian> {
+    buf: Result<&'a [u8], ElfError>,
+    align: usize,
+    offset: usize,
+    endian: PhantomData<E>,
+}
+
+impl<'a, E: Endian> Notes<'a, E> {
+    fn new(buf: &'a [u8], offset: usize, size: usize, align: usize) -> Notes<'a, E> {
+        Notes {
+            buf: match offset.checked_add(size).and_then(|end| buf.get(offset..end)) {
+                Some(buf) => Ok(buf),
+                None => Err(ElfError::SegmentOutOfBounds { offset, size }),
+            },
+            // Only 8 byte aligned notes are padded to 8, everything else to 4
+            align: if align == 8 { 8 } else { 4 },
+            offset: 0,
+            endian: PhantomData,
+        }
+    }
+
+    pub(super) fn from_segment(buf: &'a [u8], ph: &impl ProgHead) -> Notes<'a, E> {
+        Notes::new(buf, ph.offset(), ph.filesz(), ph.align())
+    }
+
+    pub(super) fn from_section<T: SectHead>(buf: &'a [u8], sh: &T) -> Notes<'a, E> {
+        Notes::new(buf, sh.offset(), sh.size(), sh.addralign())
+    }
+
+    /// The notes of all the `PT_NOTE` segments, or of all the `SHT_NOTE` sections
+    /// if there are no program headers, as in relocatable objects.
+    pub(super) fn all<T: SectHead>(
+        buf: &'a [u8],
+        phs: &'a [impl ProgHead],
+        shs: &'a [T],
+    ) -> impl Iterator<Item = Result<Note<'a, E>, Error>> + 'a {
+        let segments = phs.iter()
+            .filter(|ph| ph.p_type() == Ok(PType::Note))
+            .map(move |ph| Notes::from_segment(buf, ph));
+        let sections = shs.iter()
+            .filter(move |sh| phs.is_empty() && sh.sh_type() == Ok(ShType::Note))
+            .map(move |sh| Notes::from_section(buf, sh));
+        segments.chain(sections).flatten()
+    }
+
+    fn read(&self, buf: &'a [u8]) -> Option<(Note<'a, E>, usize)> {
+        let namesz = u32_at::<E>(buf, self.offset)? as usize;
+        let descsz = u32_at::<E>(buf, self.offset + 4)? as usize;
+        let n_type = u32_at::<E>(buf, self.offset + 8)?;
+        let name_start = self.offset + 12;
+        let name = buf.get(name_start..name_start.checked_add(namesz)?)?;
+        let desc_start = align_up(name_start + namesz, self.align)?;
+        let desc = buf.get(desc_start..desc_start.checked_add(descsz)?)?;
+        let next = align_up(desc_start + descsz, self.align)?;
+        let name = match name.split_last() {
+            Some((0, name)) => name,
+            _ => name,
+        };
+        Some((Note { name, n_type, desc, align: self.align, endian: PhantomData }, next))
+    }
+}
+
+impl<'a, E: Endian> Iterator for Notes<'a, E> {
+    type Item = Result<Note<'a, E>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let buf = match self.buf {
+            Ok(buf) => buf,
+            Err(err) => {
+                self.buf = Ok(&[]);
+                return Some(e(err));
+            }
+        };
+        if self.offset >= buf.len() {
+            return None;
+        }
+        match self.read(buf) {
+            Some((note, next)) => {
+                self.offset = next;
+                Some(Ok(note))
+            }
+            None => {
+                let offset = self.offset;
+                self.offset = buf.len();
+                Some(e(ElfError::TruncatedNote(offset)))
+            }
+        }
+    }
+}
+
+#[test]
+fn gnu_notes() {
+    use super::BigEndian;
+
+    fn words(ws: &[u32]) -> Vec<u8> {
+        ws.iter().flat_map(|w| w.to_be_bytes()).collect()
+    }
+    let gnu = u32::from_be_bytes(*b"GNU\0");
+
+    let abi_tag = words(&[4, 16, NT_GNU_ABI_TAG, gnu, 0, 3, 2, 0]);
+    let notes: Vec<_> = Notes::<BigEndian>::new(&abi_tag, 0, abi_tag.len(), 4).map(Result::unwrap).collect();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].name, b"GNU");
+    assert_eq!(notes[0].kind().unwrap(), NoteKind::GnuAbiTag { os: 0, version: [3, 2, 0] });
+
+    let ibt_shstk = GNU_PROPERTY_X86_FEATURE_1_IBT | GNU_PROPERTY_X86_FEATURE_1_SHSTK;
+    let properties = words(&[
+        4, 32, NT_GNU_PROPERTY_TYPE_0, gnu,
+        0xc0008002, 4, 1, 0,
+        GNU_PROPERTY_X86_FEATURE_1_AND, 4, ibt_shstk, 0,
+    ]);
+    let mut notes = Notes::<BigEndian>::new(&properties, 0, properties.len(), 8);
+    let properties = match notes.next().unwrap().unwrap().kind().unwrap() {
+        NoteKind::GnuProperties(properties) => properties,
+        kind => panic!("unexpected note {:?}", kind),
+    };
+    assert!(notes.next().is_none());
+    assert_eq!(properties.clone().count(), 2);
+    assert!(properties.ibt() && properties.shstk());
+
+    let truncated = words(&[4, 32, NT_GNU_BUILD_ID, gnu, 0]);
+    assert!(Notes::<BigEndian>::new(&truncated, 0, truncated.len(), 4).next().unwrap().is_err());
+}
@@ -0,0 +1,126 @@
+use std::{
+    cmp::max,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    slice,
+    vec::Vec,
+};
+
+use crate::error::ReadError;
+
+use super::{with, ElfParse};
+
+/// An owned copy of a file for sources that can't be mapped, like pipes or members of archives in memory.
+/// The bytes are kept 8 byte aligned, as the parser reads the structures in place just like from a mapping.
+#[derive(Debug, Default, Clone)]
+pub struct ElfBuf {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl ElfBuf {
+    pub fn new() -> ElfBuf {
+        ElfBuf::default()
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        // This unsafe is sound because:
+        // - u8 allows any byte pattern and has alignment of 1
+        // - len never exceeds the size of words in bytes, and all of the words are initialized
+        // - The shared access to the bytes is constrained by the lifetime of &self
+        unsafe { slice::from_raw_parts(self.words.as_ptr() as *const u8, self.len) }
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        // This unsafe is sound for the same reasons as in as_bytes, over the whole of words
+        unsafe { slice::from_raw_parts_mut(self.words.as_mut_ptr() as *mut u8, self.words.len() * 8) }
+    }
+
+    /// Reads `reader` until its end.
+    pub fn read_to_end(&mut self, mut reader: impl Read) -> io::Result<&[u8]> {
+        self.len = 0;
+        loop {
+            if self.len == self.words.len() * 8 {
+                let words = max(self.words.len() * 2, 0x200);
+                self.words.resize(words, 0);
+            }
+            let len = self.len;
+            match reader.read(&mut self.bytes_mut()[len..]) {
+                Ok(0) => return Ok(self.as_bytes()),
+                Ok(n) => self.len += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Reads the `len` bytes at `offset` of `reader`.
+    pub fn read_at(&mut self, mut reader: impl Read + Seek, offset: u64, len: usize) -> io::Result<&[u8]> {
+        self.len = 0;
+        self.words.clear();
+        self.words.resize(len.div_ceil(8), 0);
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut self.bytes_mut()[..len])?;
+        self.len = len;
+        Ok(self.as_bytes())
+    }
+}
+
+impl<'a> ElfParse<'a> {
+    /// Reads the file at `path` into `buf` and parses it.
+    pub fn from_path(path: impl AsRef<Path>, buf: &'a mut ElfBuf) -> Result<ElfParse<'a>, ReadError> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        Ok(with(buf.read_at(file, 0, len)?)?)
+    }
+
+    /// Reads `reader` until its end into `buf` and parses it.
+    pub fn from_reader(reader: impl Read, buf: &'a mut ElfBuf) -> Result<ElfParse<'a>, ReadError> {
+        Ok(with(buf.read_to_end(reader)?)?)
+    }
+
+    /// Reads the `len` bytes at `offset` of `reader` into `buf` and parses them,
+    /// for files embedded in something bigger.
+    pub fn from_reader_at(
+        reader: impl Read + Seek,
+        offset: u64,
+        len: usize,
+        buf: &'a mut ElfBuf,
+    ) -> Result<ElfParse<'a>, ReadError> {
+        Ok(with(buf.read_at(reader, offset, len)?)?)
+    }
+}
+
+#[test]
+fn reading() {
+    use std::io::Cursor;
+    use crate::{error::{ElfError, Error}, utils::fixture};
+
+    let file = fixture!("test.gnu.elf");
+    let symtab_len = |elf: ElfParse| match elf {
+        ElfParse::Elf64(elf64) => elf64.symtab.unwrap().len(),
+        _ => unreachable!(),
+    };
+    let mut buf = ElfBuf::new();
+    let expected = symtab_len(ElfParse::from_path("test/test.gnu.elf", &mut buf).unwrap());
+    assert_eq!(buf.as_bytes(), file);
+
+    // A reader that returns a few bytes at a time, like a pipe
+    let mut buf = ElfBuf::new();
+    let reader = file.chunks(1000).fold(Box::new(io::empty()) as Box<dyn Read>, |r, chunk| Box::new(r.chain(chunk)));
+    assert_eq!(symtab_len(ElfParse::from_reader(reader, &mut buf).unwrap()), expected);
+
+    // An unaligned member of a bigger buffer in memory
+    let archive = [&b"!<arch>\n"[..], b"xyz", file, b"\n"].concat();
+    let mut buf = ElfBuf::new();
+    let elf = ElfParse::from_reader_at(Cursor::new(&archive), 11, file.len(), &mut buf).unwrap();
+    assert_eq!(symtab_len(elf), expected);
+
+    let err = ElfParse::from_path("test/no-such-file.elf", &mut ElfBuf::new()).unwrap_err();
+    assert!(matches!(err, ReadError::Io(e) if e.kind() == io::ErrorKind::NotFound));
+    let err = ElfParse::from_reader_at(Cursor::new(&archive), 11, file.len() + 10, &mut ElfBuf::new()).unwrap_err();
+    assert!(matches!(err, ReadError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+    let err = ElfParse::from_reader(&b"not an elf"[..], &mut ElfBuf::new()).unwrap_err();
+    assert!(matches!(err, ReadError::Parse(Error::Elf(ElfError::TruncatedHeader(10)))));
+}
@@ -0,0 +1,244 @@
+use core::fmt::Debug;
+
+use crate::{Error, utils::TransmuteSafe};
+
+use super::{
+    sect_entries, Endian, SectHead, ShType,
+    ffi_types::{Rel32, Rel64, Rela32, Rela64, RelocAarch64Unchecked, RelocRiscVUnchecked, RelocX86_64Unchecked},
+};
+
+pub trait Reloc: TransmuteSafe + Debug {
+    fn offset(&self) -> usize;
+    /// Index of the referenced symbol in the symbol table the section links to.
+    fn sym(&self) -> usize;
+    /// The raw, architecture-dependent relocation type.
+    fn r_type(&self) -> u32;
+    /// `None` for `SHT_REL` entries, which keep the addend at the relocated location.
+    fn addend(&self) -> Option<i64>;
+
+    fn x86_64_type(&self) -> RelocX86_64Unchecked {
+        RelocX86_64Unchecked { unknown: self.r_type() }
+    }
+
+    fn aarch64_type(&self) -> RelocAarch64Unchecked {
+        RelocAarch64Unchecked { unknown: self.r_type() }
+    }
+
+    fn riscv_type(&self) -> RelocRiscVUnchecked {
+        RelocRiscVUnchecked { unknown: self.r_type() }
+    }
+}
+
+impl<E: Endian> Reloc for Rel32<E> {
+    fn offset(&self) -> usize {
+        self.r_offset.get() as usize
+    }
+
+    fn sym(&self) -> usize {
+        (self.r_info.get() >> 8) as usize
+    }
+
+    fn r_type(&self) -> u32 {
+        self.r_info.get() & 0xff
+    }
+
+    fn addend(&self) -> Option<i64> {
+        None
+    }
+}
+
+impl<E: Endian> Reloc for Rela32<E> {
+    fn offset(&self) -> usize {
+        self.r_offset.get() as usize
+    }
+
+    fn sym(&self) -> usize {
+        (self.r_info.get() >> 8) as usize
+    }
+
+    fn r_type(&self) -> u32 {
+        self.r_info.get() & 0xff
+    }
+
+    fn addend(&self) -> Option<i64> {
+        Some(self.r_addend.get() as i64)
+    }
+}
+
+impl<E: Endian> Reloc for Rel64<E> {
+    fn offset(&self) -> usize {
+        self.r_offset.get() as usize
+    }
+
+    fn sym(&self) -> usize {
+        (self.r_info.get() >> 32) as usize
+    }
+
+    fn r_type(&self) -> u32 {
+        self.r_info.get() as u32
+    }
+
+    fn addend(&self) -> Option<i64> {
+        None
+    }
+}
+
+impl<E: Endian> Reloc for Rela64<E> {
+    fn offset(&self) -> usize {
+        self.r_offset.get() as usize
+    }
+
+    fn sym(&self) -> usize {
+        (self.r_info.get() >> 32) as usize
+    }
+
+    fn r_type(&self) -> u32 {
+        self.r_info.get() as u32
+    }
+
+    fn addend(&self) -> Option<i64> {
+        Some(self.r_addend.get())
+    }
+}
+
+#[derive(Debug)]
+pub enum RelocEntries<'a, S: SectHead> {
+    Rel(&'a [S::Rel]),
+    Rela(&'a [S::Rela]),
+}
+
+// Derived impls would needlessly require S::Rel and S::Rela to be Copy
+impl<S: SectHead> Clone for RelocEntries<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: SectHead> Copy for RelocEntries<'_, S> {}
+
+impl<'a, S: SectHead> RelocEntries<'a, S> {
+    pub fn len(&self) -> usize {
+        match self {
+            RelocEntries::Rel(rels) => rels.len(),
+            RelocEntries::Rela(relas) => relas.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A `SHT_REL` or `SHT_RELA` section together with the sections it refers to.
+#[derive(Debug, Clone)]
+pub struct RelocSection<'a, S: SectHead> {
+    pub section: &'a S,
+    /// The section the relocations apply to (`sh_info`), if any.
+    pub target: Option<&'a S>,
+    /// The symbol table the relocations refer to (`sh_link`), if any.
+    pub symtab: Option<&'a S>,
+    pub entries: RelocEntries<'a, S>,
+}
+
+impl<'a, S: SectHead> RelocSection<'a, S> {
+    fn from(buf: &'a [u8], shs: &'a [S], index: usize, sh: &'a S) -> Result<Option<RelocSection<'a, S>>, Error> {
+        let entries = match sh.sh_type() {
+            Ok(ShType::Rel) => RelocEntries::Rel(sect_entries(buf, index, sh)?),
+            Ok(ShType::Rela) => RelocEntries::Rela(sect_entries(buf, index, sh)?),
+            _ => return Ok(None),
+        };
+        let nonzero = |i: usize| if i == 0 { None } else { shs.get(i) };
+        Ok(Some(RelocSection {
+            section: sh,
+            target: nonzero(sh.info()),
+            symtab: nonzero(sh.link()),
+            entries,
+        }))
+    }
+
+    pub(super) fn all(buf: &'a [u8], shs: &'a [S]) -> impl Iterator<Item = Result<RelocSection<'a, S>, Error>> + 'a {
+        shs.iter().enumerate().filter_map(move |(index, sh)| RelocSection::from(buf, shs, index, sh).transpose())
+    }
+
+    pub fn get(&self, i: usize) -> Option<Relocation<'a, S>> {
+        match self.entries {
+            RelocEntries::Rel(rels) => rels.get(i).map(|r| self.relocation(r)),
+            RelocEntries::Rela(relas) => relas.get(i).map(|r| self.relocation(r)),
+        }
+    }
+
+    fn relocation(&self, r: &impl Reloc) -> Relocation<'a, S> {
+        Relocation {
+            offset: r.offset(),
+            sym: r.sym(),
+            r_type: r.r_type(),
+            addend: r.addend(),
+            section: self.section,
+            target: self.target,
+            symtab: self.symtab,
+        }
+    }
+}
+
+/// A single relocation entry, normalized over `SHT_REL` and `SHT_RELA`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relocation<'a, S: SectHead> {
+    pub offset: usize,
+    pub sym: usize,
+    pub r_type: u32,
+    pub addend: Option<i64>,
+    pub section: &'a S,
+    pub target: Option<&'a S>,
+    pub symtab: Option<&'a S>,
+}
+
+impl<S: SectHead> Relocation<'_, S> {
+    pub fn x86_64_type(&self) -> RelocX86_64Unchecked {
+        RelocX86_64Unchecked { unknown: self.r_type }
+    }
+
+    pub fn aarch64_type(&self) -> RelocAarch64Unchecked {
+        RelocAarch64Unchecked { unknown: self.r_type }
+    }
+
+    pub fn riscv_type(&self) -> RelocRiscVUnchecked {
+        RelocRiscVUnchecked { unknown: self.r_type }
+    }
+}
+
+pub struct Relocations<'a, S: SectHead> {
+    buf: &'a [u8],
+    shs: &'a [S],
+    next_sect: usize,
+    current: Option<(RelocSection<'a, S>, usize)>,
+}
+
+impl<'a, S: SectHead> Relocations<'a, S> {
+    pub(super) fn new(buf: &'a [u8], shs: &'a [S]) -> Relocations<'a, S> {
+        Relocations { buf, shs, next_sect: 0, current: None }
+    }
+}
+
+impl<'a, S: SectHead> Iterator for Relocations<'a, S> {
+    type Item = Result<Relocation<'a, S>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((sect, i)) = &mut self.current {
+                if let Some(r) = sect.get(*i) {
+                    *i += 1;
+                    return Some(Ok(r));
+                }
+                self.current = None;
+            }
+            let index = self.next_sect;
+            let sh = self.shs.get(index)?;
+            self.next_sect += 1;
+            match RelocSection::from(self.buf, self.shs, index, sh) {
+                Ok(Some(sect)) => self.current = Some((sect, 0)),
+                Ok(None) => (),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
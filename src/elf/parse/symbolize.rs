@@ -0,0 +1,141 @@
+use std::vec::Vec;
+
+use super::{Strings, StBind, StType, Sym, SymSection};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SymAddr<'a> {
+    addr: usize,
+    size: usize,
+    name: &'a [u8],
+    /// Orders the symbols at the same address so that global names win over local aliases.
+    rank: u8,
+    /// The furthest end of this and the symbols before it, as symbols can contain later ones.
+    reach: usize,
+}
+
+impl SymAddr<'_> {
+    fn contains(&self, addr: usize) -> bool {
+        addr.wrapping_sub(self.addr) < self.size.max(1)
+    }
+}
+
+/// The defined function and object symbols of a file sorted by address, for resolving addresses to symbols.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymIndex<'a> {
+    syms: Vec<SymAddr<'a>>,
+}
+
+impl<'a> SymIndex<'a> {
+    pub(super) fn new<S: Sym>(tables: &[(Option<&'a [S]>, Option<&Strings<'a>>)]) -> SymIndex<'a> {
+        let mut syms = Vec::new();
+        for (symtab, names) in tables {
+            let (symtab, names) = match (symtab, names) {
+                (Some(symtab), Some(names)) => (symtab, names),
+                _ => continue,
+            };
+            for sym in symtab.iter() {
+                match (sym.st_type(), sym.section()) {
+                    (Ok(StType::Func | StType::Object), SymSection::Index(_) | SymSection::Xindex | SymSection::Abs) => (),
+                    _ => continue,
+                }
+                let rank = match sym.binding() {
+                    Ok(StBind::Global) => 0,
+                    Ok(StBind::Weak) => 1,
+                    _ => 2,
+                };
+                if let Ok(name) = sym.name(names) {
+                    syms.push(SymAddr { addr: sym.value(), size: sym.size(), name, rank, reach: 0 });
+                }
+            }
+        }
+        SymIndex::sorted(syms)
+    }
+
+    fn sorted(mut syms: Vec<SymAddr<'a>>) -> SymIndex<'a> {
+        syms.sort_unstable_by(|a, b| (a.addr, a.rank, a.name).cmp(&(b.addr, b.rank, b.name)));
+        // The dynamic symbols are usually in the static symbol table too
+        syms.dedup_by(|a, b| a.addr == b.addr && a.name == b.name);
+        let mut reach = 0;
+        for sym in &mut syms {
+            reach = reach.max(sym.addr.saturating_add(sym.size.max(1)));
+            sym.reach = reach;
+        }
+        SymIndex { syms }
+    }
+
+    pub fn len(&self) -> usize {
+        self.syms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.syms.is_empty()
+    }
+
+    /// The name of the symbol that contains the virtual address `addr`, and the offset of `addr` into it.
+    /// Runtime addresses of position independent files have to have the load bias subtracted first.
+    /// Symbols without a size only match their exact address. Of nested symbols, the innermost one matches.
+    pub fn symbolize(&self, addr: usize) -> Option<(&'a [u8], usize)> {
+        let end = self.syms.partition_point(|sym| sym.addr <= addr);
+        let mut found: Option<&SymAddr> = None;
+        // Going back, the symbols at the same address come in the reverse order of their ranks
+        for sym in self.syms[..end].iter().rev().take_while(|sym| sym.reach > addr) {
+            match found {
+                Some(found) if found.addr != sym.addr => break,
+                _ if sym.contains(addr) => found = Some(sym),
+                _ => (),
+            }
+        }
+        found.map(|sym| (sym.name, addr - sym.addr))
+    }
+}
+
+#[test]
+fn symbolize() {
+    use super::{with, ElfParse};
+    use crate::utils::fixture;
+
+    let elf64 = match with(fixture!("test.gnu.elf")).unwrap() {
+        ElfParse::Elf64(elf64) => elf64,
+        _ => unreachable!(),
+    };
+    let index = elf64.sym_index();
+    let main = elf64.lookup(b"main").unwrap();
+    assert_eq!(index.symbolize(main.value()), Some((&b"main"[..], 0)));
+    assert_eq!(index.symbolize(main.value() + main.size() - 1), Some((&b"main"[..], main.size() - 1)));
+    assert!(index.symbolize(main.value() + main.size()).is_none_or(|(name, _)| name != b"main"));
+    assert_eq!(index.symbolize(0), None);
+
+    let yukichan = elf64.lookup(b"yukichan").unwrap();
+    assert_eq!(index.symbolize(yukichan.value() + 4), Some((&b"yukichan"[..], 4)));
+    let names = |index: &SymIndex| index.syms.iter().filter(|sym| sym.name == b"yukichan").count();
+    assert_eq!(names(&index), 1);
+
+    // Only the dynamic symbols are left in stripped shared objects
+    let shared = match with(fixture!("test.shared.elf")).unwrap() {
+        ElfParse::Elf64(elf64) => elf64,
+        _ => unreachable!(),
+    };
+    let dynamic_only = SymIndex::new(&[(shared.dynsym, shared.dyn_names.as_ref())]);
+    let sumikko = shared.lookup(b"sumikko").unwrap();
+    assert_eq!(dynamic_only.symbolize(sumikko.value() + 1), Some((&b"sumikko"[..], 1)));
+}
+
+#[test]
+fn nested_symbols() {
+    let sym = |addr: usize, size: usize, name: &'static [u8]| SymAddr { addr, size, name, rank: 0, reach: 0 };
+    let index = SymIndex::sorted(vec![
+        sym(0x1000, 0x100, b"outer"),
+        sym(0x1010, 0x10, b"inner"),
+        sym(0x1040, 0, b"label"),
+        sym(0x1100, 0x10, b"next"),
+    ]);
+    assert_eq!(index.symbolize(0x1000), Some((&b"outer"[..], 0)));
+    assert_eq!(index.symbolize(0x1018), Some((&b"inner"[..], 8)));
+    // Past the end of a symbol inside the function, and past a label without a size
+    assert_eq!(index.symbolize(0x1020), Some((&b"outer"[..], 0x20)));
+    assert_eq!(index.symbolize(0x1040), Some((&b"label"[..], 0)));
+    assert_eq!(index.symbolize(0x1041), Some((&b"outer"[..], 0x41)));
+    assert_eq!(index.symbolize(0x1104), Some((&b"next"[..], 4)));
+    assert_eq!(index.symbolize(0x1110), None);
+    assert_eq!(index.symbolize(0xfff), None);
+}
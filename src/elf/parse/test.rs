@@ -1,14 +1,357 @@
-use std::io::Cursor;
-
-use crate::elf::{self, parse::ElfParse};
+use crate::{
+    elf::{self, parse::{
+        gnu_hash, sysv_hash, EIOsAbi, EMachine, ElfFile64, ElfHead, ElfParse, EType, GnuHash, ProgHead, PType, RelocAarch64, RelocRiscV, RelocX86_64, SectHead, ShType, StType,
+        StVisibility, Sym, SymSection, SysvHash,
+    }},
+    error::{ElfError, Error},
+    utils::{fixture, ToKnown},
+};
 
 #[test]
 fn elf_loading() {
-    let elf_file = &mut Cursor::new(include_bytes!("../../../test.musl.elf"));
-    let parsed_elf = if let ElfParse::Elf64(elf64) = elf::parse::with(elf_file).unwrap() {
-        elf64
-    } else {
-        unreachable!();
+    let musl = elf64(fixture!("test.musl.elf"));
+    let symtab = musl.symtab.unwrap();
+    let sym_names = musl.sym_names.clone().unwrap();
+    let main = musl.lookup(b"main").unwrap();
+    assert!(symtab.iter().any(|s| core::ptr::eq(s, main)));
+    assert_eq!(main.name(&sym_names).unwrap(), b"main");
+}
+
+/// Parses one of the fixtures that `make elf` builds from src/test_elf.c and src/test_obj.c.
+fn elf64(buf: &'static [u8]) -> ElfFile64<'static> {
+    match elf::parse::with(buf).unwrap() {
+        ElfParse::Elf64(elf64) => elf64,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn elf32_loading() {
+    let elf32 = match elf::parse::with(fixture!("test.i386.elf")).unwrap() {
+        ElfParse::Elf32(elf32) => elf32,
+        _ => unreachable!(),
+    };
+    assert_eq!(elf32.eh.e_type().unwrap(), EType::Exec);
+    assert!(!elf32.phs.is_empty());
+    let symtab = elf32.symtab.unwrap();
+    let sym_names = elf32.sym_names.clone().unwrap();
+    for name in [&b"yukichan"[..], b"ystavam", b"pikachu", b"chomkero", b"sumikko", b"main"] {
+        let sym = elf32.lookup(name).unwrap();
+        assert!(symtab.iter().any(|s| core::ptr::eq(s, sym)));
+        assert_eq!(sym.name(&sym_names).unwrap(), name);
+        assert!(matches!(sym.st_type(), Ok(StType::Func)));
+    }
+}
+
+#[test]
+fn big_endian_loading() {
+    let names = [&b"yukichan"[..], b"ystavam", b"pikachu", b"chomkero", b"sumikko", b"main"];
+    let mips = match elf::parse::with(fixture!("test.mips.elf")).unwrap() {
+        ElfParse::Elf32Be(mips) => mips,
+        _ => unreachable!(),
+    };
+    assert_eq!(mips.eh.e_type().unwrap(), EType::Exec);
+    assert_eq!(mips.eh.machine().unwrap(), EMachine::Mips);
+    let sym_names = mips.sym_names.clone().unwrap();
+    for name in names {
+        let sym = mips.lookup(name).unwrap();
+        assert_eq!(sym.name(&sym_names).unwrap(), name);
+        assert!(matches!(sym.st_type(), Ok(StType::Func)));
+    }
+
+    let ppc64 = match elf::parse::with(fixture!("test.ppc64.elf")).unwrap() {
+        ElfParse::Elf64Be(ppc64) => ppc64,
+        _ => unreachable!(),
+    };
+    assert_eq!(ppc64.eh.e_type().unwrap(), EType::Exec);
+    assert_eq!(ppc64.eh.machine().unwrap(), EMachine::PowerPC64);
+    let sym_names = ppc64.sym_names.clone().unwrap();
+    for name in names {
+        let sym = ppc64.lookup(name).unwrap();
+        assert_eq!(sym.name(&sym_names).unwrap(), name);
+    }
+}
+
+#[test]
+fn foreign_machines() {
+    let aarch64 = elf64(fixture!("test.aarch64.elf"));
+    assert_eq!(aarch64.eh.machine().unwrap(), EMachine::Aarch64);
+    let dyn_names = aarch64.dyn_names.clone().unwrap();
+    let dynsym = aarch64.dynsym.unwrap();
+    assert!(aarch64.relocations().map(Result::unwrap).any(|r| {
+        r.aarch64_type().known() == Ok(RelocAarch64::JumpSlot) && dynsym[r.sym].name(&dyn_names).unwrap() == b"puts"
+    }));
+
+    let riscv64 = elf64(fixture!("test.riscv64.elf"));
+    assert_eq!(riscv64.eh.machine().unwrap(), EMachine::RiscV);
+    let dyn_names = riscv64.dyn_names.clone().unwrap();
+    let dynsym = riscv64.dynsym.unwrap();
+    assert!(riscv64.relocations().map(Result::unwrap).any(|r| {
+        r.riscv_type().known() == Ok(RelocRiscV::JumpSlot) && dynsym[r.sym].name(&dyn_names).unwrap() == b"puts"
+    }));
+}
+
+#[test]
+fn relocatable_object() {
+    let obj = elf64(fixture!("test.rel.o"));
+    assert_eq!(obj.eh.e_type().unwrap(), EType::Rel);
+    assert!(obj.phs.is_empty());
+    let shs = obj.shs.unwrap();
+    let sh_names = obj.sh_names.clone().unwrap();
+    let sym_names = obj.sym_names.clone().unwrap();
+
+    let symtab = obj.symtab.unwrap();
+    let index = |name: &[u8]| symtab.iter().position(|sym| sym.name(&sym_names).unwrap() == name).unwrap();
+    let counter = obj.lookup(b"counter").unwrap();
+    assert_eq!(counter.section(), SymSection::Common);
+    assert_eq!(counter.size(), 4);
+    assert_eq!(counter.value(), 4);
+    assert_eq!(obj.sym_offset(index(b"counter")), None);
+
+    assert_eq!(obj.lookup(b"puts").unwrap().section(), SymSection::Undef);
+
+    let hidden = obj.lookup(b"hidden_helper").unwrap();
+    assert_eq!(hidden.visibility(), StVisibility::Hidden);
+    assert!(hidden.size() > 0);
+    let text = match hidden.section() {
+        SymSection::Index(i) => &shs[i],
+        section => panic!("unexpected section {:?}", section),
+    };
+    assert_eq!(text.name(&sh_names).unwrap(), b".text");
+    assert_eq!(obj.sym_offset(index(b"hidden_helper")), Some(text.offset() + hidden.value()));
+    assert_eq!(obj.lookup(b"shared_answer").unwrap().visibility(), StVisibility::Protected);
+    assert_eq!(obj.lookup(b"main").unwrap().visibility(), StVisibility::Default);
+
+    let file = symtab.iter().find(|sym| sym.st_type() == Ok(StType::File)).unwrap();
+    assert_eq!(file.section(), SymSection::Abs);
+    assert_eq!(file.name(&sym_names).unwrap(), b"test_obj.c");
+}
+
+#[test]
+fn extended_section_numbering() {
+    let obj = elf64(fixture!("test.many-sections.o"));
+    assert_eq!(obj.eh.shnum(), 0);
+    let shs = obj.shs.unwrap();
+    assert_eq!(shs.len(), shs[0].size());
+    assert!(shs.len() > 0xff00);
+    let sh_names = obj.sh_names.clone().unwrap();
+    assert!(obj.symtab.is_some() && obj.symtab_shndx.is_some());
+    let symtab = obj.symtab.unwrap();
+    let sym_names = obj.sym_names.clone().unwrap();
+    for name in [&b"f0"[..], b"f65299"] {
+        let i = symtab.iter().position(|sym| sym.name(&sym_names).unwrap() == name).unwrap();
+        let sh = match obj.sym_section(i).unwrap() {
+            SymSection::Index(sh) => &shs[sh],
+            section => panic!("unexpected section {:?}", section),
+        };
+        assert_eq!(sh.name(&sh_names).unwrap(), [&b".text."[..], name].concat());
+        assert_eq!(obj.sym_offset(i), Some(sh.offset()));
+    }
+    assert_eq!(obj.lookup(b"f65299").unwrap().section(), SymSection::Xindex);
+}
+
+#[test]
+fn header_accessors() {
+    let elf64 = elf64(fixture!("test.gnu.elf"));
+    assert_eq!(elf64.eh.ehsize(), 64);
+    assert_eq!(elf64.eh.shentsize(), 64);
+    assert_eq!(elf64.eh.osabi().known(), Ok(EIOsAbi::SystemV));
+    let shs = elf64.shs.unwrap();
+    let sh_names = elf64.sh_names.clone().unwrap();
+    assert_eq!(shs[elf64.eh.shstrndx() as usize].name(&sh_names).unwrap(), b".shstrtab");
+    let text = shs.iter().find(|sh| sh.name(&sh_names).unwrap() == b".text").unwrap();
+    // SHF_ALLOC | SHF_EXECINSTR
+    assert_eq!(text.flags(), 0x6);
+    assert_eq!(text.addr(), text.offset());
+    let phdr = elf64.phs.iter().find(|ph| ph.p_type() == Ok(PType::Phdr)).unwrap();
+    assert_eq!(phdr.paddr(), phdr.vaddr());
+}
+
+#[test]
+fn dynamic_section() {
+    let elf64 = elf64(fixture!("test.gnu.elf"));
+    let dynamic = elf64.dynamic().unwrap().unwrap();
+    assert!(dynamic.needed().any(|n| n.unwrap() == b"libc.so.6"));
+    assert_eq!(dynamic.soname, None);
+    assert!(dynamic.strings.is_some());
+    assert!(dynamic.jmprel.is_some());
+}
+
+#[test]
+fn relocations() {
+    let elf64 = elf64(fixture!("test.gnu.elf"));
+    let sections: Vec<_> = elf64.reloc_sections().map(Result::unwrap).collect();
+    assert!(!sections.is_empty());
+    for sect in &sections {
+        assert_eq!(sect.symtab.unwrap().sh_type().unwrap(), ShType::Dynsym);
+    }
+    let jump_slots = elf64.relocations()
+        .map(Result::unwrap)
+        .filter(|r| r.x86_64_type().known() == Ok(RelocX86_64::JumpSlot))
+        .collect::<Vec<_>>();
+    assert!(!jump_slots.is_empty());
+    assert!(jump_slots.iter().all(|r| r.sym != 0 && r.addend == Some(0)));
+}
+
+#[test]
+fn dynamic_symbols() {
+    let elf64 = elf64(fixture!("test.gnu.elf"));
+    let dynsym = elf64.dynsym.unwrap();
+    let dyn_names = elf64.dyn_names.unwrap();
+    assert!(dynsym.iter().any(|sym| sym.name(&dyn_names).unwrap() == b"puts"));
+}
+
+#[test]
+fn hash_functions() {
+    assert_eq!(gnu_hash(b""), 0x00001505);
+    assert_eq!(gnu_hash(b"printf"), 0x156b2bb8);
+    assert_eq!(gnu_hash(b"syscall"), 0xbac212a0);
+    assert_eq!(sysv_hash(b""), 0);
+    assert_eq!(sysv_hash(b"printf"), 0x077905a6);
+    assert_eq!(sysv_hash(b"syscall"), 0x0b09985c);
+}
+
+#[test]
+fn hash_tables() {
+    let elf64 = elf64(fixture!("test.shared.elf"));
+    let shs = elf64.shs.unwrap();
+    let dynsym = elf64.dynsym.unwrap();
+    let dyn_names = elf64.dyn_names.clone().unwrap();
+    let find = |ty| shs.iter().find(|sh| sh.sh_type() == Ok(ty)).unwrap();
+    let gnu = GnuHash::<u64>::from(elf64.buf, find(ShType::GnuHash)).unwrap();
+    let sysv = <SysvHash>::from(elf64.buf, find(ShType::Hash)).unwrap();
+    for name in [&b"yukichan"[..], b"ystavam", b"pikachu", b"chomkero", b"sumikko", b"main"] {
+        let i = dynsym.iter().position(|sym| sym.name(&dyn_names).unwrap() == name).unwrap();
+        assert_eq!(gnu.find(name, dynsym, &dyn_names), Some(i));
+        assert_eq!(sysv.find(name, dynsym, &dyn_names), Some(i));
+        assert!(core::ptr::eq(elf64.lookup(name).unwrap(), &dynsym[i]));
+    }
+    // Both tables find the imports, which GNU hash tables leave out
+    let puts = dynsym.iter().position(|sym| sym.name(&dyn_names).unwrap() == b"puts").unwrap();
+    assert_eq!(dynsym[puts].section(), SymSection::Undef);
+    assert_eq!(gnu.find(b"puts", dynsym, &dyn_names), Some(puts));
+    assert_eq!(sysv.find(b"puts", dynsym, &dyn_names), Some(puts));
+    assert!(core::ptr::eq(elf64.lookup(b"puts").unwrap(), &dynsym[puts]));
+    assert_eq!(gnu.find(b"pokemon", dynsym, &dyn_names), None);
+    assert_eq!(sysv.find(b"pokemon", dynsym, &dyn_names), None);
+    assert!(elf64.lookup(b"pokemon").is_none());
+}
+
+#[test]
+fn lookup_falls_back_to_symtab() {
+    let elf64 = elf64(fixture!("test.gnu.elf"));
+    let sym_names = elf64.sym_names.clone().unwrap();
+    let main = elf64.lookup(b"main").unwrap();
+    assert_eq!(main.name(&sym_names).unwrap(), b"main");
+}
+
+#[test]
+fn symbol_versions() {
+    let elf64 = elf64(fixture!("test.gnu.elf"));
+    let versions = elf64.versions().unwrap().unwrap();
+    let dynsym = elf64.dynsym.unwrap();
+    let dyn_names = elf64.dyn_names.clone().unwrap();
+    assert_eq!(versions.versym.len(), dynsym.len());
+    assert!(versions.needs().all(|need| need.unwrap().file == b"libc.so.6"));
+
+    let puts = dynsym.iter().position(|sym| sym.name(&dyn_names).unwrap() == b"puts").unwrap();
+    let version = versions.version(puts).unwrap().unwrap();
+    assert_eq!(version.name, b"GLIBC_2.2.5");
+    assert_eq!(version.file, Some(&b"libc.so.6"[..]));
+    assert_eq!(version.separator(), "@");
+    assert_eq!(versions.version(0).unwrap(), None);
+
+    assert!(core::ptr::eq(elf64.lookup_versioned(b"puts", b"GLIBC_2.2.5").unwrap().unwrap(), &dynsym[puts]));
+    assert!(elf64.lookup_versioned(b"puts", b"GLIBC_2.0").unwrap().is_none());
+}
+
+#[test]
+fn build_id() {
+    let elf64 = elf64(fixture!("test.gnu.elf"));
+    assert_eq!(elf64.build_id().unwrap().unwrap().len(), 20);
+    assert!(elf64.notes().all(|note| note.is_ok()));
+}
+
+/// Parses `buf` from an 8-aligned copy and reads everything the parser exposes.
+fn parse_malformed(buf: &[u8]) -> Result<(), Error> {
+    let mut aligned = vec![0u8; buf.len() + 8];
+    let offset = aligned.as_ptr().align_offset(8);
+    aligned[offset..offset + buf.len()].copy_from_slice(buf);
+    let elf64 = match elf::parse::with(&aligned[offset..offset + buf.len()]) {
+        Ok(ElfParse::Elf64(elf64)) => elf64,
+        Ok(_) => return Ok(()),
+        Err(err) => return Err(err),
     };
-    assert_eq!(parsed_elf.symtab.unwrap().len(), 147);
+    let _ = elf64.dynamic();
+    let _ = elf64.build_id();
+    elf64.notes().count();
+    elf64.relocations().count();
+    if let Ok(Some(versions)) = elf64.versions() {
+        (0..elf64.dynsym.map_or(0, |dynsym| dynsym.len())).for_each(|i| { let _ = versions.version(i); });
+    }
+    let _ = elf64.lookup_versioned(b"puts", b"GLIBC_2.2.5");
+    for i in 0..elf64.symtab.map_or(0, |symtab| symtab.len()) {
+        let _ = elf64.sym_section(i);
+        let _ = elf64.sym_offset(i);
+    }
+    Ok(())
+}
+
+#[test]
+fn malformed_input() {
+    use rand::Rng;
+
+    let gnu: &[u8] = fixture!("test.gnu.elf");
+    assert_eq!(parse_malformed(gnu), Ok(()));
+    assert_eq!(parse_malformed(&gnu[..40]), Err(Error::Elf(ElfError::TruncatedHeader(40))));
+    for len in (0..gnu.len()).step_by(61) {
+        let _ = parse_malformed(&gnu[..len]);
+    }
+
+    let patched = |offset: usize, bytes: &[u8]| {
+        let mut buf = gnu.to_vec();
+        buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+        buf
+    };
+    let err = |buf: Vec<u8>| match parse_malformed(&buf) {
+        Err(Error::Elf(err)) => err,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(err(patched(0, b"\x7fELV")), ElfError::BadMagic(*b"\x7fELV"));
+    assert_eq!(err(patched(4, &[3])), ElfError::UnsupportedClass(3));
+    assert_eq!(err(patched(52, &[32])), ElfError::BadEhsize(32));
+    assert!(matches!(err(patched(32, &u64::MAX.to_le_bytes())), ElfError::ProgHeadersOutOfBounds { offset: usize::MAX, .. }));
+    assert!(matches!(err(patched(40, &u64::MAX.to_le_bytes())), ElfError::SectHeadersOutOfBounds { offset: usize::MAX, .. }));
+    assert!(matches!(err(patched(60, &0xffffu16.to_le_bytes())), ElfError::SectHeadersOutOfBounds { count: 0xffff, .. }));
+    assert_eq!(err(patched(62, &0xfffeu16.to_le_bytes())), ElfError::BadShstrndx(0xfffe));
+    let shoff = u64::from_le_bytes(gnu[40..48].try_into().unwrap()) as usize;
+    let shstrndx = u16::from_le_bytes([gnu[62], gnu[63]]) as usize;
+    assert_eq!(
+        err(patched(shoff + shstrndx * 64 + 4, &0x1234_5678u32.to_le_bytes())),
+        ElfError::UnknownSectionType { index: shstrndx, sh_type: 0x1234_5678 },
+    );
+
+    // The errors point at the symbol and string tables
+    let elf64 = elf64(gnu);
+    let symtab = elf64.shs.unwrap().iter().find(|sh| sh.sh_type() == Ok(ShType::Symtab)).unwrap();
+    let strtab = &elf64.shs.unwrap()[symtab.link()];
+    let count = elf64.symtab.unwrap().len();
+    assert_eq!(
+        elf64.sym_section(count),
+        Err(Error::Elf(ElfError::SymbolOutOfBounds { offset: symtab.offset(), index: count })),
+    );
+    assert_eq!(
+        elf64.sym_names.as_ref().unwrap().get_string(usize::MAX),
+        Err(Error::Elf(ElfError::BadStringOffset { table: strtab.offset(), offset: usize::MAX })),
+    );
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let mut buf = gnu.to_vec();
+        for _ in 0..rng.gen_range(1..32) {
+            let i = rng.gen_range(0..buf.len());
+            buf[i] = rng.gen();
+        }
+        let _ = parse_malformed(&buf);
+    }
 }
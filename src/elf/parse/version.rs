@@ -0,0 +1,254 @@
+use core::marker::PhantomData;
+
+use crate::{e, Error, error::ElfError, utils::TransmuteSafe};
+
+use super::{
+    bytes_at, sect_entries, Endian, Field, LittleEndian, SectHead, ShType, Strings,
+    ffi_types::{Verdaux, Verdef, Vernaux, Verneed},
+};
+
+/// `versym` value of symbols that are local to the object.
+pub const VER_NDX_LOCAL: u16 = 0;
+/// `versym` value of unversioned global symbols.
+pub const VER_NDX_GLOBAL: u16 = 1;
+/// Set in a `versym` value when the version isn't the default one.
+pub const VERSYM_HIDDEN: u16 = 0x8000;
+
+/// The definition that names the object itself rather than a version.
+pub const VER_FLG_BASE: u16 = 0x1;
+pub const VER_FLG_WEAK: u16 = 0x2;
+
+/// A version defined in `SHT_GNU_verdef`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionDef<'a> {
+    pub index: u16,
+    pub flags: u16,
+    pub name: &'a [u8],
+}
+
+/// A version required from another object in `SHT_GNU_verneed`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionNeed<'a> {
+    pub index: u16,
+    pub flags: u16,
+    pub name: &'a [u8],
+    pub file: &'a [u8],
+}
+
+/// The version of a single dynamic symbol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymVersion<'a> {
+    pub name: &'a [u8],
+    /// The object that the version is required from, for references to other objects.
+    pub file: Option<&'a [u8]>,
+    pub hidden: bool,
+}
+
+impl SymVersion<'_> {
+    /// Whether this is the version that unversioned references bind to.
+    pub fn is_default(&self) -> bool {
+        self.file.is_none() && !self.hidden
+    }
+
+    /// The separator between the symbol and the version name: `@@` for default versions, `@` otherwise.
+    pub fn separator(&self) -> &'static str {
+        if self.is_default() { "@@" } else { "@" }
+    }
+}
+
+fn entry<T: TransmuteSafe>(bytes: &[u8], offset: usize) -> Result<&T, Error> {
+    match bytes.get(offset..).map(T::from_buf) {
+        Some(Ok((t, _))) => Ok(t),
+        _ => e(ElfError::TruncatedVersion(offset)),
+    }
+}
+
+/// A verdef or verneed section: a chain of entries with the string table that `sh_link` points to.
+#[derive(Debug, Clone, PartialEq)]
+struct Chain<'a> {
+    bytes: &'a [u8],
+    /// Number of entries, from `sh_info`.
+    count: usize,
+    strings: Strings<'a>,
+}
+
+impl<'a> Chain<'a> {
+    fn from<T: SectHead>(buf: &'a [u8], shs: &[T], index: usize, sh: &T) -> Result<Chain<'a>, Error> {
+        let bytes = match bytes_at(buf, sh.offset(), sh.size()) {
+            Some(bytes) => bytes,
+            None => return e(ElfError::SectionOutOfBounds { index, offset: sh.offset(), size: sh.size() }),
+        };
+        let strings = match shs.get(sh.link()) {
+            Some(strtab) => Strings::from(buf, sh.link(), strtab)?,
+            None => return e(ElfError::BadLink { index, link: sh.link() }),
+        };
+        Ok(Chain { bytes, count: sh.info(), strings })
+    }
+}
+
+/// The decoded `SHT_GNU_versym`, `SHT_GNU_verdef` and `SHT_GNU_verneed` sections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Versions<'a, E: Endian = LittleEndian> {
+    /// The version index of each dynamic symbol.
+    pub versym: &'a [Field<u16, E>],
+    verdef: Option<Chain<'a>>,
+    verneed: Option<Chain<'a>>,
+}
+
+impl<'a, E: Endian> Versions<'a, E> {
+    pub(super) fn from<T: SectHead>(buf: &'a [u8], shs: &[T]) -> Result<Option<Versions<'a, E>>, Error> {
+        let find = |ty: ShType| shs.iter().enumerate().find(|(_, sh)| sh.sh_type() == Ok(ty));
+        let versym = match find(ShType::GnuVersym) {
+            Some((index, sh)) => sect_entries(buf, index, sh)?,
+            None => return Ok(None),
+        };
+        Ok(Some(Versions {
+            versym,
+            verdef: find(ShType::GnuVerdef).map(|(index, sh)| Chain::from(buf, shs, index, sh)).transpose()?,
+            verneed: find(ShType::GnuVerneed).map(|(index, sh)| Chain::from(buf, shs, index, sh)).transpose()?,
+        }))
+    }
+
+    pub fn defs(&self) -> VersionDefs<'a, E> {
+        VersionDefs {
+            endian: PhantomData,
+            chain: self.verdef.clone(),
+            offset: 0,
+            left: self.verdef.as_ref().map_or(0, |c| c.count),
+        }
+    }
+
+    pub fn needs(&self) -> VersionNeeds<'a, E> {
+        VersionNeeds {
+            endian: PhantomData,
+            chain: self.verneed.clone(),
+            offset: 0,
+            left: self.verneed.as_ref().map_or(0, |c| c.count),
+            file: &[],
+            aux_offset: 0,
+            aux_left: 0,
+        }
+    }
+
+    /// The version of the dynamic symbol at index `sym`, or `None` if it's unversioned.
+    pub fn version(&self, sym: usize) -> Result<Option<SymVersion<'a>>, Error> {
+        let ndx = match self.versym.get(sym) {
+            Some(ndx) => ndx.get(),
+            None => return e(ElfError::VersymOutOfBounds(sym)),
+        };
+        let hidden = ndx & VERSYM_HIDDEN != 0;
+        let ndx = ndx & !VERSYM_HIDDEN;
+        if ndx == VER_NDX_LOCAL || ndx == VER_NDX_GLOBAL {
+            return Ok(None);
+        }
+        for def in self.defs() {
+            let def = def?;
+            if def.index == ndx {
+                return Ok(Some(SymVersion { name: def.name, file: None, hidden }));
+            }
+        }
+        for need in self.needs() {
+            let need = need?;
+            if need.index == ndx {
+                return Ok(Some(SymVersion { name: need.name, file: Some(need.file), hidden }));
+            }
+        }
+        e(ElfError::UndefinedVersion(ndx))
+    }
+}
+
+pub struct VersionDefs<'a, E: Endian = LittleEndian> {
+    endian: PhantomData<E>,
+    chain: Option<Chain<'a>>,
+    offset: usize,
+    left: usize,
+}
+
+impl<'a, E: Endian> VersionDefs<'a, E> {
+    fn read(&self, chain: &Chain<'a>) -> Result<(VersionDef<'a>, u32), Error> {
+        let def: &Verdef<E> = entry(chain.bytes, self.offset)?;
+        // The first auxiliary entry names the version, the rest name its parents
+        let name = match def.vd_cnt.get() {
+            0 => return e(ElfError::UnnamedVersion(def.vd_ndx.get())),
+            _ => {
+                let aux: &Verdaux<E> = entry(chain.bytes, self.offset.saturating_add(def.vd_aux.get() as usize))?;
+                chain.strings.get_string(aux.vda_name.get() as usize)?
+            }
+        };
+        Ok((VersionDef { index: def.vd_ndx.get(), flags: def.vd_flags.get(), name }, def.vd_next.get()))
+    }
+}
+
+impl<'a, E: Endian> Iterator for VersionDefs<'a, E> {
+    type Item = Result<VersionDef<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chain = self.chain.as_ref()?;
+        if self.left == 0 {
+            return None;
+        }
+        match self.read(chain) {
+            Ok((def, next)) => {
+                self.left = if next == 0 { 0 } else { self.left - 1 };
+                self.offset = self.offset.saturating_add(next as usize);
+                Some(Ok(def))
+            }
+            Err(err) => {
+                self.left = 0;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// The version requirements of all the needed objects, flattened.
+pub struct VersionNeeds<'a, E: Endian = LittleEndian> {
+    endian: PhantomData<E>,
+    chain: Option<Chain<'a>>,
+    offset: usize,
+    left: usize,
+    file: &'a [u8],
+    aux_offset: usize,
+    aux_left: usize,
+}
+
+impl<'a, E: Endian> VersionNeeds<'a, E> {
+    fn read(&mut self, chain: &Chain<'a>) -> Result<Option<VersionNeed<'a>>, Error> {
+        while self.aux_left == 0 {
+            if self.left == 0 {
+                return Ok(None);
+            }
+            let need: &Verneed<E> = entry(chain.bytes, self.offset)?;
+            self.file = chain.strings.get_string(need.vn_file.get() as usize)?;
+            self.aux_offset = self.offset.saturating_add(need.vn_aux.get() as usize);
+            self.aux_left = need.vn_cnt.get() as usize;
+            self.left = if need.vn_next.get() == 0 { 0 } else { self.left - 1 };
+            self.offset = self.offset.saturating_add(need.vn_next.get() as usize);
+        }
+        let aux: &Vernaux<E> = entry(chain.bytes, self.aux_offset)?;
+        self.aux_left = if aux.vna_next.get() == 0 { 0 } else { self.aux_left - 1 };
+        self.aux_offset = self.aux_offset.saturating_add(aux.vna_next.get() as usize);
+        Ok(Some(VersionNeed {
+            index: aux.vna_other.get(),
+            flags: aux.vna_flags.get(),
+            name: chain.strings.get_string(aux.vna_name.get() as usize)?,
+            file: self.file,
+        }))
+    }
+}
+
+impl<'a, E: Endian> Iterator for VersionNeeds<'a, E> {
+    type Item = Result<VersionNeed<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chain = self.chain.clone()?;
+        match self.read(&chain) {
+            Ok(need) => need.map(Ok),
+            Err(err) => {
+                self.left = 0;
+                self.aux_left = 0;
+                Some(Err(err))
+            }
+        }
+    }
+}
@@ -8,12 +8,161 @@ pub enum Error {
     Fstat(i32),
     Fmt(fmt::Error),
     Mmap(i32),
-    Elf,
+    Mprotect(i32),
+    Elf(ElfError),
+    Macho(MachoError),
     Cli,
     Utf8Error,
     Transmute,
 }
 
+/// The error of reading a file into memory and parsing it, when not mapping it.
+/// Kept apart from `Error` so that its shape doesn't depend on the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum ReadError {
+    Io(std::io::Error),
+    Parse(Error),
+}
+
+/// What is wrong with an ELF file. Where the parser knows them, the variants carry
+/// the offending raw value and the file offset or section index it was found at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfError {
+    /// The file is shorter than the ELF header; holds the file size.
+    TruncatedHeader(usize),
+    BadMagic([u8; 4]),
+    UnsupportedClass(u8),
+    UnsupportedData(u8),
+    BadIdentVersion(u8),
+    BadPadding([u8; 7]),
+    BadType(u16),
+    UnknownMachine(u16),
+    BadVersion(u32),
+    BadEhsize(u16),
+    BadPhentsize(u16),
+    BadShentsize(u16),
+    BadShstrndx(usize),
+    ProgHeadersOutOfBounds { offset: usize, count: usize },
+    SectHeadersOutOfBounds { offset: usize, count: usize },
+    /// The program header at `index` has a `p_type` quack doesn't know.
+    UnknownSegmentType { index: usize, p_type: u32 },
+    /// The section header at `index` has an `sh_type` quack doesn't know.
+    UnknownSectionType { index: usize, sh_type: u32 },
+    UnknownSymbolType(u8),
+    UnknownSymbolBind(u8),
+    /// Section `index` lies, at least partly, outside of the file.
+    SectionOutOfBounds { index: usize, offset: usize, size: usize },
+    MisalignedSection { index: usize, offset: usize },
+    BadEntsize { index: usize, entsize: usize },
+    BadLink { index: usize, link: usize },
+    NotStringTable { index: usize, sh_type: u32 },
+    /// A string table at `offset` that doesn't start and end with a null byte.
+    UnterminatedStrings { offset: usize },
+    /// A string `offset` past the end of the string table at file offset `table`.
+    BadStringOffset { table: usize, offset: usize },
+    /// A symbol `index` past the end of the symbol table at file offset `offset`.
+    SymbolOutOfBounds { offset: usize, index: usize },
+    /// The symbol at this index is `SHN_XINDEX`, but the file has no `SHT_SYMTAB_SHNDX` entry for it.
+    MissingXindex(usize),
+    SegmentOutOfBounds { offset: usize, size: usize },
+    /// A virtual address that no `PT_LOAD` segment maps from the file.
+    UnmappedAddress(usize),
+    BadTableEntsize(usize),
+    /// A dynamic entry refers to the string table, but there's no `DT_STRTAB` or `DT_STRSZ`.
+    MissingStrtab,
+    BadPltRel(usize),
+    /// A note at this offset of its segment or section that runs past the end of it.
+    TruncatedNote(usize),
+    TruncatedAbiTag,
+    TruncatedProperty(usize),
+    /// A version entry at this offset of its section that runs past the end of it.
+    TruncatedVersion(usize),
+    UnnamedVersion(u16),
+    UndefinedVersion(u16),
+    VersymOutOfBounds(usize),
+    TruncatedHashTable { offset: usize, size: usize },
+    EmptyBloomFilter,
+    /// The file has neither a symbol table nor a dynamic symbol table to inspect.
+    NoSymbols,
+    NotExecutable(u16),
+    UnsupportedMachine(u16),
+    UnsupportedHost,
+    NoLoadSegments,
+    /// The file has a `PT_INTERP` segment, so it needs a dynamic linker to run.
+    NeedsInterpreter,
+    BadSegmentAlign(usize),
+    MemszBelowFilesz { vaddr: usize, memsz: usize, filesz: usize },
+    IncongruentSegment { offset: usize, vaddr: usize },
+    OverlapsLoader { start: usize, end: usize },
+    /// The program headers at this offset aren't loaded with any `PT_LOAD` segment.
+    ProgHeadersNotLoaded(usize),
+    /// The initial stack is too small for this many bytes of arguments and environment.
+    StackOverflow(usize),
+}
+
+/// What is wrong with a Mach-O file. The variants carry the offending raw value and,
+/// for load commands, the file offset of the command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MachoError {
+    /// The file is shorter than the Mach-O header; holds the file size.
+    TruncatedHeader(usize),
+    BadMagic(u32),
+    /// The magic of a 32-bit or big-endian Mach-O file, which quack doesn't parse.
+    UnsupportedMagic(u32),
+    UnknownCpuType(u32),
+    UnknownFileType(u32),
+    UnknownPlatform(u32),
+    /// `sizeofcmds` bytes of load commands don't fit in the file after the header.
+    CommandsOutOfBounds(usize),
+    /// A `cmdsize` that is smaller than 8, not a multiple of 8, or runs past `sizeofcmds`.
+    BadCommandSize { offset: usize, cmdsize: usize },
+    /// A load command too small for the structure or the array that its `cmd` says it holds.
+    TruncatedCommand { offset: usize, cmd: u32 },
+    /// A string of a load command that starts outside of it or isn't null-terminated in it.
+    BadCommandString { offset: usize },
+    SegmentOutOfBounds { offset: usize, size: usize },
+    SectionOutOfBounds { offset: usize, size: usize },
+    SymbolsOutOfBounds { offset: usize, count: usize },
+    StringsOutOfBounds { offset: usize, size: usize },
+    /// A string table at `offset` that doesn't end with a null byte.
+    UnterminatedStrings { offset: usize },
+    /// A range of `LC_DYSYMTAB` that isn't in the symbol table.
+    BadSymbolRange { first: usize, count: usize },
+    /// The file has no `LC_SYMTAB` to inspect.
+    NoSymbols,
+    /// `nfat_arch` slices don't fit in the file after the universal header.
+    FatArchsOutOfBounds(usize),
+    FatSliceOutOfBounds { offset: u64, size: u64 },
+    /// A universal file without a slice for the requested CPU type.
+    NoFatSlice(u32),
+    /// Opcodes of `LC_DYLD_INFO` or the data of `LC_DYLD_CHAINED_FIXUPS` that run out of the file.
+    FixupsOutOfBounds { offset: usize, size: usize },
+    /// An unknown or unsupported rebase or bind opcode, or one with truncated operands.
+    BadDyldOpcode { offset: usize, opcode: u8 },
+    /// A fixup in a segment that the file doesn't have.
+    BadFixupSegment(usize),
+    /// A fixup of a pointer that isn't in the file data of its segment.
+    FixupOutOfBounds { address: u64 },
+    /// A part of the chained fixups at `offset` that is out of their data, or an import that isn't there.
+    BadChainedFixups { offset: usize },
+    /// A version or a format of the chained fixups that quack doesn't decode.
+    UnsupportedChainedFormat(u32),
+    CantRun,
+}
+
+impl From<ElfError> for Error {
+    fn from(e: ElfError) -> Error {
+        Error::Elf(e)
+    }
+}
+
+impl From<MachoError> for Error {
+    fn from(e: MachoError) -> Error {
+        Error::Macho(e)
+    }
+}
+
 impl From<fmt::Error> for Error {
     fn from(e: fmt::Error) -> Error {
         Error::Fmt(e)
@@ -26,6 +175,20 @@ impl From<Error> for fmt::Error {
     }
 }
 
+#[cfg(feature = "std")]
+impl From<std::io::Error> for ReadError {
+    fn from(e: std::io::Error) -> ReadError {
+        ReadError::Io(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for ReadError {
+    fn from(e: Error) -> ReadError {
+        ReadError::Parse(e)
+    }
+}
+
 impl From<Utf8Error> for Error {
     fn from(_: Utf8Error) -> Error {
         Error::Utf8Error
@@ -40,12 +203,193 @@ impl Error {
             Error::Read(errno) => 2*16 + (errno % 16) as u8,
             Error::Fstat(errno) => 3*16 + (errno % 16) as u8,
             Error::Fmt(_) => 4*16,
-            Error::Elf => 5*16,
+            Error::Elf(_) => 5*16,
             Error::Cli => 6*16,
             Error::Utf8Error => 7*16,
             Error::Transmute => 8*16,
             Error::Mmap(errno) => 9*16 + (errno % 16) as u8,
+            Error::Mprotect(errno) => 10*16 + (errno % 16) as u8,
+            Error::Macho(_) => 12*16,
+        }
+    }
+}
+
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Open(errno) => write!(f, "couldn't open the file (errno {})", errno),
+            Error::Write(errno) => write!(f, "couldn't write (errno {})", errno),
+            Error::Read(errno) => write!(f, "couldn't read (errno {})", errno),
+            Error::Fstat(errno) => write!(f, "couldn't stat the file (errno {})", errno),
+            Error::Fmt(_) => write!(f, "formatting failed"),
+            Error::Mmap(errno) => write!(f, "couldn't map memory (errno {})", errno),
+            Error::Mprotect(errno) => write!(f, "couldn't change memory protection (errno {})", errno),
+            Error::Elf(e) => write!(f, "{}", e),
+            Error::Macho(e) => write!(f, "{}", e),
+            Error::Cli => write!(f, "invalid command line"),
+            Error::Utf8Error => write!(f, "invalid UTF-8"),
+            Error::Transmute => write!(f, "buffer too small or misaligned"),
+        }
+    }
+}
+
+impl fmt::Display for ElfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ElfError::*;
+        match *self {
+            TruncatedHeader(size) => write!(f, "a {} byte file is too small for an elf header", size),
+            BadMagic(magic) => write!(f, "invalid e_ident.ei_mag {:02x?}", magic),
+            UnsupportedClass(class) => write!(f, "unsupported e_ident.ei_class {}", class),
+            UnsupportedData(data) => write!(f, "unsupported e_ident.ei_data {}", data),
+            BadIdentVersion(version) => write!(f, "invalid e_ident.ei_version {}", version),
+            BadPadding(pad) => write!(f, "non-zero e_ident.ei_pad {:02x?}", pad),
+            BadType(e_type) => write!(f, "invalid e_type {:#x}", e_type),
+            UnknownMachine(machine) => write!(f, "unknown e_machine {:#x}", machine),
+            BadVersion(version) => write!(f, "invalid e_version {}", version),
+            BadEhsize(size) => write!(f, "invalid e_ehsize {}", size),
+            BadPhentsize(size) => write!(f, "invalid e_phentsize {}", size),
+            BadShentsize(size) => write!(f, "invalid e_shentsize {}", size),
+            BadShstrndx(index) => write!(f, "e_shstrndx {} is out of the section header table", index),
+            ProgHeadersOutOfBounds { offset, count } => {
+                write!(f, "{} program headers at {:#x} are truncated or misaligned", count, offset)
+            }
+            SectHeadersOutOfBounds { offset, count } => {
+                write!(f, "{} section headers at {:#x} are truncated or misaligned", count, offset)
+            }
+            UnknownSegmentType { index, p_type } => write!(f, "program header {} has an unknown p_type {:#x}", index, p_type),
+            UnknownSectionType { index, sh_type } => write!(f, "section {} has an unknown sh_type {:#x}", index, sh_type),
+            UnknownSymbolType(st_type) => write!(f, "unknown symbol type {}", st_type),
+            UnknownSymbolBind(st_bind) => write!(f, "unknown symbol binding {}", st_bind),
+            SectionOutOfBounds { index, offset, size } => {
+                write!(f, "section {} at {:#x} of size {:#x} is out of the file", index, offset, size)
+            }
+            MisalignedSection { index, offset } => write!(f, "section {} at {:#x} is misaligned", index, offset),
+            BadEntsize { index, entsize } => write!(f, "section {} has an invalid sh_entsize {}", index, entsize),
+            BadLink { index, link } => write!(f, "section {} has an invalid sh_link {}", index, link),
+            NotStringTable { index, sh_type } => {
+                write!(f, "section {} of sh_type {:#x} isn't a string table", index, sh_type)
+            }
+            UnterminatedStrings { offset } => write!(f, "string table at {:#x} isn't null-terminated", offset),
+            BadStringOffset { table, offset } => {
+                write!(f, "string offset {:#x} is out of the string table at {:#x}", offset, table)
+            }
+            SymbolOutOfBounds { offset, index } => {
+                write!(f, "symbol {} is out of the symbol table at {:#x}", index, offset)
+            }
+            MissingXindex(index) => write!(f, "SHN_XINDEX symbol {} has no SHT_SYMTAB_SHNDX entry", index),
+            SegmentOutOfBounds { offset, size } => {
+                write!(f, "segment at {:#x} of size {:#x} is out of the file", offset, size)
+            }
+            UnmappedAddress(vaddr) => write!(f, "address {:#x} isn't mapped from the file", vaddr),
+            BadTableEntsize(entsize) => write!(f, "invalid dynamic table entry size {}", entsize),
+            MissingStrtab => write!(f, "dynamic string entry without DT_STRTAB"),
+            BadPltRel(value) => write!(f, "invalid DT_PLTREL {}", value),
+            TruncatedNote(offset) => write!(f, "note at {:#x} is truncated", offset),
+            TruncatedAbiTag => write!(f, "truncated NT_GNU_ABI_TAG note"),
+            TruncatedProperty(offset) => write!(f, "GNU property at {:#x} is truncated", offset),
+            TruncatedVersion(offset) => write!(f, "version entry at {:#x} is truncated or misaligned", offset),
+            UnnamedVersion(index) => write!(f, "version definition {} has no name", index),
+            UndefinedVersion(index) => write!(f, "undefined symbol version {}", index),
+            VersymOutOfBounds(index) => write!(f, "symbol {} is out of the versym table", index),
+            TruncatedHashTable { offset, size } => {
+                write!(f, "hash table at {:#x} of size {:#x} is truncated or misaligned", offset, size)
+            }
+            EmptyBloomFilter => write!(f, "GNU hash table without a bloom filter"),
+            NoSymbols => write!(f, "no symbol table"),
+            NotExecutable(e_type) => write!(f, "can only load ET_EXEC and ET_DYN elfs, not e_type {:#x}", e_type),
+            UnsupportedMachine(machine) => write!(f, "can only run x86-64 elfs, not e_machine {:#x}", machine),
+            UnsupportedHost => write!(f, "can run elfs only on x86-64 Linux"),
+            NoLoadSegments => write!(f, "no PT_LOAD segments to load"),
+            NeedsInterpreter => write!(f, "can only run static elfs, not ones with a PT_INTERP interpreter"),
+            BadSegmentAlign(align) => write!(f, "PT_LOAD p_align {:#x} isn't a power of two", align),
+            MemszBelowFilesz { vaddr, memsz, filesz } => {
+                write!(f, "PT_LOAD segment at {:#x} has p_memsz {:#x} smaller than p_filesz {:#x}", vaddr, memsz, filesz)
+            }
+            IncongruentSegment { offset, vaddr } => {
+                write!(f, "PT_LOAD p_offset {:#x} and p_vaddr {:#x} are not congruent modulo page size", offset, vaddr)
+            }
+            OverlapsLoader { start, end } => write!(f, "PT_LOAD segment {:#x}..{:#x} overlaps quack's own image", start, end),
+            ProgHeadersNotLoaded(offset) => write!(f, "program headers at {:#x} aren't part of any PT_LOAD segment", offset),
+            StackOverflow(size) => write!(f, "the initial stack doesn't fit {} more bytes of arguments and environment", size),
+        }
+    }
+}
+
+impl fmt::Display for MachoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use MachoError::*;
+        match *self {
+            TruncatedHeader(size) => write!(f, "a {} byte file is too small for a mach-o header", size),
+            BadMagic(magic) => write!(f, "invalid mach-o magic {:#x}", magic),
+            UnsupportedMagic(magic) => write!(f, "unsupported 32-bit or big-endian mach-o magic {:#x}", magic),
+            UnknownCpuType(cputype) => write!(f, "unknown cputype {:#x}", cputype),
+            UnknownFileType(filetype) => write!(f, "unknown filetype {:#x}", filetype),
+            UnknownPlatform(platform) => write!(f, "unknown build version platform {}", platform),
+            CommandsOutOfBounds(size) => write!(f, "{:#x} bytes of load commands are out of the file", size),
+            BadCommandSize { offset, cmdsize } => write!(f, "load command at {:#x} has an invalid cmdsize {:#x}", offset, cmdsize),
+            TruncatedCommand { offset, cmd } => write!(f, "load command {:#x} at {:#x} is truncated", cmd, offset),
+            BadCommandString { offset } => write!(f, "load command at {:#x} has an invalid string", offset),
+            SegmentOutOfBounds { offset, size } => {
+                write!(f, "segment at {:#x} of size {:#x} is out of the file", offset, size)
+            }
+            SectionOutOfBounds { offset, size } => {
+                write!(f, "section at {:#x} of size {:#x} is out of the file", offset, size)
+            }
+            SymbolsOutOfBounds { offset, count } => {
+                write!(f, "{} symbols at {:#x} are truncated or misaligned", count, offset)
+            }
+            StringsOutOfBounds { offset, size } => {
+                write!(f, "string table at {:#x} of size {:#x} is out of the file", offset, size)
+            }
+            UnterminatedStrings { offset } => write!(f, "string table at {:#x} isn't null-terminated", offset),
+            BadSymbolRange { first, count } => {
+                write!(f, "{} symbols from {} are out of the symbol table", count, first)
+            }
+            NoSymbols => write!(f, "no symbol table"),
+            FatArchsOutOfBounds(count) => write!(f, "{} universal slices don't fit in the file", count),
+            FatSliceOutOfBounds { offset, size } => {
+                write!(f, "universal slice at {:#x} of size {:#x} is out of the file", offset, size)
+            }
+            NoFatSlice(cputype) => write!(f, "no universal slice for cpu type {:#x}", cputype),
+            FixupsOutOfBounds { offset, size } => {
+                write!(f, "fixups at {:#x} of size {:#x} are out of the file", offset, size)
+            }
+            BadDyldOpcode { offset, opcode } => write!(f, "bad dyld opcode {:#04x} at {:#x}", opcode, offset),
+            BadFixupSegment(index) => write!(f, "fixup in segment {} that doesn't exist", index),
+            FixupOutOfBounds { address } => write!(f, "fixup at {:#x} is out of its segment", address),
+            BadChainedFixups { offset } => write!(f, "bad chained fixups at {:#x}", offset),
+            UnsupportedChainedFormat(format) => write!(f, "unsupported chained fixups format {}", format),
+            CantRun => write!(f, "can only inspect mach-o files, not run them"),
         }
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ElfError {}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MachoError {}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "couldn't read the file: {}", e),
+            ReadError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Parse(e) => Some(e),
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! The zero-copy ELF and Mach-O parsers behind quack and its symbol demanglers, usable without `std` or quack's own runtime.
+
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+pub mod demangle;
+pub mod elf;
+pub mod error;
+pub mod macho;
+pub mod utils;
+
+use elf::e;
+use error::Error;
@@ -0,0 +1,331 @@
+use core::{arch::asm, fmt::Write, cmp::{min, max}, mem::size_of, ops::Range, ptr::{self, addr_of}};
+
+use quack::{
+    elf::parse::{Dynamic, EMachine, ElfFile64, ElfHead, EType, ProgHead, ProgHead64, PType, Reloc, Rela64, RelocX86_64, p_flags},
+    error::{self, ElfError, Error},
+    utils::ToKnown,
+};
+
+use crate::os::{self, STDERR, Args, AuxEntry, Fd, mmap_prot};
+
+fn e<T>(err: ElfError) -> Result<T, Error> {
+    Err(Error::Elf(err))
+}
+
+const PAGE_SIZE: usize = 0x1000;
+const STACK_SIZE: usize = 0x10_0000;
+
+
+pub mod auxv {
+    pub const AT_NULL: usize = 0;
+    pub const AT_PHDR: usize = 3;
+    pub const AT_PHENT: usize = 4;
+    pub const AT_PHNUM: usize = 5;
+    pub const AT_PAGESZ: usize = 6;
+    pub const AT_BASE: usize = 7;
+    pub const AT_FLAGS: usize = 8;
+    pub const AT_ENTRY: usize = 9;
+    pub const AT_RANDOM: usize = 25;
+    pub const AT_EXECFN: usize = 31;
+}
+
+/// Where the parts of a loaded image the program needs to know about ended up in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub entry: usize,
+    pub phdr: usize,
+    pub phent: usize,
+    pub phnum: usize,
+}
+
+pub fn probe() {
+
+    let x: u64;
+    unsafe { asm!("lea {}, [rip]", out(reg) x); }
+    let _ = writeln!(STDERR, "{:x?}", x);
+    let _ = writeln!(STDERR, "{:x?}", probe as fn() as u64);
+    let _ = writeln!(STDERR, "main: {:x?}", crate::main as fn(Args) -> Result<(), error::Error> as u64);
+}
+
+fn page_floor(addr: usize) -> usize {
+    addr & !(PAGE_SIZE - 1)
+}
+
+fn page_ceil(addr: usize) -> usize {
+    page_floor(addr + PAGE_SIZE - 1)
+}
+
+fn prot(p_flag: u32) -> u32 {
+    let mut prot = mmap_prot::PROT_NONE;
+    if p_flag & p_flags::PF_R != 0 {
+        prot |= mmap_prot::PROT_READ;
+    }
+    if p_flag & p_flags::PF_W != 0 {
+        prot |= mmap_prot::PROT_WRITE;
+    }
+    if p_flag & p_flags::PF_X != 0 {
+        prot |= mmap_prot::PROT_EXEC;
+    }
+    prot
+}
+
+/// The address range occupied by quack itself. Loaded segments must not clobber it.
+fn own_image() -> Range<usize> {
+    extern "C" {
+        static __ehdr_start: u8;
+        static _end: u8;
+    }
+    unsafe { addr_of!(__ehdr_start) as usize..addr_of!(_end) as usize }
+}
+
+/// The in-memory address of the program headers: either given by `PT_PHDR`
+/// or found inside the `PT_LOAD` segment that covers them in the file.
+fn phdr_addr(elf: &ElfFile64) -> Result<usize, Error> {
+    let phoff = elf.eh.phoff();
+    for ph in elf.phs {
+        if ph.p_type() == Ok(PType::Phdr) {
+            return Ok(ph.vaddr());
+        }
+    }
+    for ph in elf.phs {
+        if ph.p_type() == Ok(PType::Load) && (ph.offset()..ph.offset() + ph.filesz()).contains(&phoff) {
+            return Ok(ph.vaddr() + phoff - ph.offset());
+        }
+    }
+    e(ElfError::ProgHeadersNotLoaded(phoff))
+}
+
+fn load_segments<'a>(elf: &'a ElfFile64) -> impl Iterator<Item = &'a ProgHead64> {
+    elf.phs.iter().filter(|ph| ph.p_type() == Ok(PType::Load))
+}
+
+/// Picks the load bias: zero for `ET_EXEC`, and for `ET_DYN` the distance
+/// from the lowest segment address to a free, suitably aligned area large enough for the image.
+fn load_bias(elf: &ElfFile64) -> Result<usize, Error> {
+    match elf.eh.e_type()? {
+        EType::Exec => Ok(0),
+        EType::Dyn => {
+            let mut lo = usize::MAX;
+            let mut hi = 0;
+            let mut align = PAGE_SIZE;
+            for ph in load_segments(elf) {
+                lo = min(lo, page_floor(ph.vaddr()));
+                hi = max(hi, page_ceil(ph.vaddr() + ph.memsz()));
+                align = max(align, ph.align());
+            }
+            if lo >= hi {
+                return e(ElfError::NoLoadSegments);
+            }
+            if !align.is_power_of_two() {
+                return e(ElfError::BadSegmentAlign(align));
+            }
+            // The segments are mapped over this reservation with MAP_FIXED
+            let reservation = os::map_anon(hi - lo + align, mmap_prot::PROT_READ)?;
+            let start = reservation.as_slice().as_ptr() as usize;
+            Ok(((start + align - 1) & !(align - 1)) - lo)
+        }
+        e_type => e(ElfError::NotExecutable(e_type as u16)),
+    }
+}
+
+/// Applies the `R_X86_64_RELATIVE` relocations listed by `DT_RELA` in the dynamic table.
+/// Static-PIE startup code typically does this by itself too, which is harmless,
+/// as applying a relative relocation twice gives the same result.
+/// The other relocation types are left for the program to process.
+/// Fails on relocations whose target isn't inside one of the loaded segments.
+fn relocate(elf: &ElfFile64, bias: usize) -> Result<(), Error> {
+    let rela = match elf.dynamic()? {
+        Some(Dynamic { rela: Some(rela), .. }) => rela,
+        _ => return Ok(()),
+    };
+    for r in elf.table::<Rela64>(&rela)? {
+        if r.x86_64_type().known() == Ok(RelocX86_64::Relative) {
+            // Only write to the segments that were just mapped, never to quack itself or the unmapped gaps
+            let offset = r.offset();
+            let in_segment = load_segments(elf).any(|ph| {
+                offset >= ph.vaddr()
+                    && offset.checked_add(size_of::<u64>()).is_some_and(|end| end <= ph.vaddr().saturating_add(ph.memsz()))
+            });
+            let target = match bias.checked_add(offset) {
+                Some(target) if in_segment => target as *mut u64,
+                _ => return e(ElfError::UnmappedAddress(offset)),
+            };
+            let value = (bias as i64).wrapping_add(r.addend().unwrap_or(0));
+            unsafe { target.write_unaligned(value as u64) };
+        }
+    }
+    Ok(())
+}
+
+/// Maps every `PT_LOAD` segment of the file open in `fd` to its `vaddr`, offset by the load bias,
+/// zeroes the BSS part, applies relative relocations and finally the segment permissions.
+pub fn load(elf: &ElfFile64, fd: Fd) -> Result<Image, Error> {
+    match elf.eh.machine()? {
+        EMachine::X86_64 => (),
+        machine => return e(ElfError::UnsupportedMachine(machine as u16)),
+    }
+    // quack doesn't load the dynamic linker that these would need
+    if elf.phs.iter().any(|ph| ph.p_type() == Ok(PType::Interp)) {
+        return e(ElfError::NeedsInterpreter);
+    }
+    let own = own_image();
+    let rw = mmap_prot::PROT_READ | mmap_prot::PROT_WRITE;
+    let bias = load_bias(elf)?;
+    for ph in load_segments(elf) {
+        if ph.memsz() < ph.filesz() {
+            return e(ElfError::MemszBelowFilesz { vaddr: ph.vaddr(), memsz: ph.memsz(), filesz: ph.filesz() });
+        }
+        if ph.offset() % PAGE_SIZE != ph.vaddr() % PAGE_SIZE {
+            return e(ElfError::IncongruentSegment { offset: ph.offset(), vaddr: ph.vaddr() });
+        }
+        let vaddr = bias + ph.vaddr();
+        let start = page_floor(vaddr);
+        let file_end = vaddr + ph.filesz();
+        let mem_end = vaddr + ph.memsz();
+        let end = page_ceil(mem_end);
+        if start < own.end && own.start < end {
+            return e(ElfError::OverlapsLoader { start, end });
+        }
+
+        let anon_start = if ph.filesz() > 0 {
+            os::map_fixed(start, file_end - start, rw, fd, page_floor(ph.offset()))?;
+            page_ceil(file_end)
+        } else {
+            start
+        };
+        if anon_start < end {
+            os::map_anon_fixed(anon_start, end - anon_start, rw)?;
+        }
+        // The last file-backed page contains whatever follows the segment in the file
+        if ph.filesz() > 0 && mem_end > file_end {
+            let zero_end = min(anon_start, mem_end);
+            unsafe { ptr::write_bytes(file_end as *mut u8, 0, zero_end - file_end) };
+        }
+    }
+    relocate(elf, bias)?;
+    for ph in load_segments(elf) {
+        let start = page_floor(bias + ph.vaddr());
+        let end = page_ceil(bias + ph.vaddr() + ph.memsz());
+        os::protect(start, end - start, prot(ph.flags()))?;
+    }
+    Ok(Image {
+        entry: bias + elf.eh.entry(),
+        phdr: bias + phdr_addr(elf)?,
+        phent: elf.eh.phentsize(),
+        phnum: elf.phs.len(),
+    })
+}
+
+/// A SysV ABI initial process stack, built downwards from the top of an anonymous mapping.
+pub struct Stack {
+    bottom: usize,
+    sp: usize,
+}
+
+impl Stack {
+    pub fn new(size: usize) -> Result<Stack, Error> {
+        let mem = os::map_anon(size, mmap_prot::PROT_READ | mmap_prot::PROT_WRITE)?;
+        let bottom = mem.as_slice().as_ptr() as usize;
+        Ok(Stack { bottom, sp: bottom + size })
+    }
+
+    fn reserve(&mut self, n: usize) -> Result<usize, Error> {
+        if self.sp - self.bottom < n {
+            return e(ElfError::StackOverflow(n));
+        }
+        self.sp -= n;
+        Ok(self.sp)
+    }
+
+    /// Copies `bytes` to the stack and returns their address.
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        let addr = self.reserve(bytes.len())?;
+        unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), addr as *mut u8, bytes.len()) };
+        Ok(addr)
+    }
+
+    /// Lays out argc, the argv and envp pointer arrays and the auxiliary vector,
+    /// in the order the program expects to find them at its entry point.
+    /// Returns the stack pointer to start the program with.
+    pub fn finish(
+        mut self,
+        argv: &[*const u8],
+        envp: &[*const u8],
+        auxv: impl Iterator<Item = AuxEntry> + Clone,
+    ) -> Result<usize, Error> {
+        let auxc = auxv.clone().count();
+        let words = 1 + argv.len() + 1 + envp.len() + 1 + 2 * (auxc + 1);
+        let size = words * size_of::<usize>();
+        // Pad below the vectors so that the stack pointer ends up 16 byte aligned
+        let padding = self.sp.wrapping_sub(size) & 0xF;
+        let sp = self.reserve(size + padding)?;
+
+        let mut word = sp as *mut usize;
+        let mut push = |w: usize| unsafe {
+            word.write(w);
+            word = word.add(1);
+        };
+        push(argv.len());
+        argv.iter().for_each(|&a| push(a as usize));
+        push(0);
+        envp.iter().for_each(|&e| push(e as usize));
+        push(0);
+        for aux in auxv {
+            push(aux.key);
+            push(aux.val);
+        }
+        push(auxv::AT_NULL);
+        push(0);
+        Ok(sp)
+    }
+}
+
+/// Sets up a stack for the loaded program and jumps to its entry point.
+/// `argv` becomes the argument vector of the program; the environment is passed as-is,
+/// and the auxiliary vector is quack's own with the image-specific entries replaced.
+/// Doesn't return unless setting up the stack fails.
+pub fn run(image: &Image, args: &Args, argv: &[*const u8]) -> Result<(), Error> {
+    let mut stack = Stack::new(STACK_SIZE)?;
+
+    // Seed bytes for the stack protector and the like, copied from quack's own AT_RANDOM
+    let random = match args.aux(auxv::AT_RANDOM) {
+        Some(host_random) => {
+            let bytes = unsafe { core::slice::from_raw_parts(host_random as *const u8, 16) };
+            stack.push_bytes(bytes)?
+        }
+        None => stack.push_bytes(&[0; 16])?,
+    };
+
+    let overrides = [
+        AuxEntry { key: auxv::AT_PHDR, val: image.phdr },
+        AuxEntry { key: auxv::AT_PHENT, val: image.phent },
+        AuxEntry { key: auxv::AT_PHNUM, val: image.phnum },
+        AuxEntry { key: auxv::AT_PAGESZ, val: args.aux(auxv::AT_PAGESZ).unwrap_or(PAGE_SIZE) },
+        AuxEntry { key: auxv::AT_BASE, val: 0 }, // no interpreter
+        AuxEntry { key: auxv::AT_FLAGS, val: 0 },
+        AuxEntry { key: auxv::AT_ENTRY, val: image.entry },
+        AuxEntry { key: auxv::AT_RANDOM, val: random },
+        AuxEntry { key: auxv::AT_EXECFN, val: argv[0] as usize },
+    ];
+    let inherited = args.auxv().iter()
+        .filter(|a| !overrides.iter().any(|o| o.key == a.key))
+        .copied();
+    let auxv = overrides.iter().copied().chain(inherited);
+
+    let sp = stack.finish(argv, args.envp(), auxv)?;
+    jump(image.entry, sp)
+}
+
+fn jump(entry: usize, sp: usize) -> ! {
+    unsafe {
+        asm!(
+            "mov rsp, rsi",
+            "xor ebp, ebp", // mark the outermost frame
+            "xor edx, edx", // no rtld_fini function for the program to register
+            "jmp rdi",
+            in("rdi") entry,
+            in("rsi") sp,
+            options(noreturn),
+        )
+    }
+}
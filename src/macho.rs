@@ -0,0 +1,7 @@
+use crate::error::{Error, MachoError};
+
+pub mod parse;
+
+pub(crate) fn e<T>(err: MachoError) -> Result<T, Error> {
+    Err(Error::Macho(err))
+}
@@ -0,0 +1,763 @@
+use core::{fmt::{self, Debug, Display}, marker::PhantomData, mem::size_of};
+
+mod enum_impls;
+mod fat;
+mod ffi_types;
+mod fixups;
+mod symtab;
+#[cfg(test)]
+mod test;
+
+use crate::{
+    elf::parse::{Endian, LittleEndian, Strings},
+    error::MachoError,
+    macho::e,
+    utils::{ToKnown, TransmuteSafe},
+    Error,
+};
+
+pub use ffi_types::{
+    BuildToolVersion, BuildVersionCommand, CpuType, CpuTypeUnchecked, DyldInfoCommand, DylibCommand, DysymtabCommand,
+    EntryPointCommand, FatArch, FatArch64, FatHeader, FileType, FileTypeUnchecked, LinkeditDataCommand, LoadCmd,
+    LoadCmdUnchecked, LoadCommandHead, MachHeader64, Nlist64, Platform, PlatformUnchecked, Section64, SegmentCommand64,
+    SymtabCommand, UuidCommand,
+};
+pub use fat::{is_fat, with_fat, FatFile, FatSlice, FAT_MAGIC, FAT_MAGIC_64};
+pub use fixups::{bind_ordinal, Fixup, FixupKind, Fixups};
+pub use symtab::{n_desc, n_type, MachoSym, Symbols};
+
+pub const MH_MAGIC: u32 = 0xfeedface;
+pub const MH_CIGAM: u32 = 0xcefaedfe;
+pub const MH_MAGIC_64: u32 = 0xfeedfacf;
+pub const MH_CIGAM_64: u32 = 0xcffaedfe;
+
+pub mod mh_flags {
+    pub const MH_NOUNDEFS: u32 = 0x1;
+    pub const MH_DYLDLINK: u32 = 0x4;
+    pub const MH_TWOLEVEL: u32 = 0x80;
+    pub const MH_PIE: u32 = 0x20_0000;
+}
+
+pub mod vm_prot {
+    pub const VM_PROT_READ: u32 = 0x1;
+    pub const VM_PROT_WRITE: u32 = 0x2;
+    pub const VM_PROT_EXECUTE: u32 = 0x4;
+}
+
+const SECTION_TYPE: u32 = 0xff;
+const S_ZEROFILL: u32 = 0x1;
+const S_GB_ZEROFILL: u32 = 0xc;
+const S_THREAD_LOCAL_ZEROFILL: u32 = 0x12;
+
+/// The first four bytes of `buf` read as a little-endian `u32`.
+fn magic(buf: &[u8]) -> Option<u32> {
+    match *buf.get(..4)? {
+        [a, b, c, d] => Some(u32::from_le_bytes([a, b, c, d])),
+        _ => None,
+    }
+}
+
+/// Whether `buf` starts with the magic of a Mach-O file, of any word size and byte order.
+pub fn is_macho(buf: &[u8]) -> bool {
+    magic(buf).is_some_and(|magic| [MH_MAGIC, MH_CIGAM, MH_MAGIC_64, MH_CIGAM_64].contains(&magic))
+}
+
+/// A fixed size name like `segname`, without the null padding.
+fn name(bytes: &[u8; 16]) -> &[u8] {
+    let end = bytes.iter().position(|&b| b == b'\0').unwrap_or(bytes.len());
+    &bytes[..end]
+}
+
+struct Name<'a>(&'a [u8]);
+
+impl Debug for Name<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self.0.escape_ascii())
+    }
+}
+
+/// A version `X.Y.Z` packed into a `u32` as `xxxx.yy.zz`, like the minimum OS version and the dylib versions.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version(pub u32);
+
+impl Version {
+    pub fn major(self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+
+    pub fn minor(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    pub fn patch(self) -> u8 {
+        self.0 as u8
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major(), self.minor(), self.patch())
+    }
+}
+
+impl Debug for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl CpuType {
+    /// The name of the architecture, as `lipo` and `-arch` spell it.
+    pub fn arch_name(self) -> &'static str {
+        match self {
+            CpuType::X86 => "i386",
+            CpuType::Arm => "arm",
+            CpuType::PowerPC => "ppc",
+            CpuType::X86_64 => "x86_64",
+            CpuType::Arm64 => "arm64",
+            CpuType::PowerPC64 => "ppc64",
+            CpuType::Arm64_32 => "arm64_32",
+        }
+    }
+
+    /// The CPU type of an architecture named like `arch_name` returns.
+    pub fn from_arch_name(name: &[u8]) -> Option<CpuType> {
+        [
+            CpuType::X86,
+            CpuType::Arm,
+            CpuType::PowerPC,
+            CpuType::X86_64,
+            CpuType::Arm64,
+            CpuType::PowerPC64,
+            CpuType::Arm64_32,
+        ]
+        .into_iter()
+        .find(|cputype| cputype.arch_name().as_bytes() == name)
+    }
+}
+
+impl<E: Endian> MachHeader64<E> {
+    pub fn cputype(&self) -> Result<CpuType, Error> {
+        match self.cputype.get().known() {
+            Ok(o) => Ok(o),
+            Err(cputype) => e(MachoError::UnknownCpuType(cputype)),
+        }
+    }
+
+    pub fn cpusubtype(&self) -> u32 {
+        self.cpusubtype.get()
+    }
+
+    pub fn filetype(&self) -> Result<FileType, Error> {
+        match self.filetype.get().known() {
+            Ok(o) => Ok(o),
+            Err(filetype) => e(MachoError::UnknownFileType(filetype)),
+        }
+    }
+
+    pub fn ncmds(&self) -> usize {
+        self.ncmds.get() as usize
+    }
+
+    pub fn sizeofcmds(&self) -> usize {
+        self.sizeofcmds.get() as usize
+    }
+
+    /// The `mh_flags`.
+    pub fn flags(&self) -> u32 {
+        self.flags.get()
+    }
+}
+
+impl<E: Endian> SegmentCommand64<E> {
+    pub fn name(&self) -> &[u8] {
+        name(&self.segname)
+    }
+
+    pub fn vmaddr(&self) -> usize {
+        self.vmaddr.get() as usize
+    }
+
+    pub fn vmsize(&self) -> usize {
+        self.vmsize.get() as usize
+    }
+
+    pub fn fileoff(&self) -> usize {
+        self.fileoff.get() as usize
+    }
+
+    pub fn filesize(&self) -> usize {
+        self.filesize.get() as usize
+    }
+
+    /// The `vm_prot` bits that the segment can be given.
+    pub fn maxprot(&self) -> u32 {
+        self.maxprot.get()
+    }
+
+    /// The `vm_prot` bits that the segment is mapped with.
+    pub fn initprot(&self) -> u32 {
+        self.initprot.get()
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags.get()
+    }
+
+    /// The bytes of the segment in `buf`, the file that the segment is from.
+    pub fn data<'a>(&self, buf: &'a [u8]) -> Result<&'a [u8], Error> {
+        match bytes_at(buf, self.fileoff(), self.filesize()) {
+            Some(data) => Ok(data),
+            None => e(MachoError::SegmentOutOfBounds { offset: self.fileoff(), size: self.filesize() }),
+        }
+    }
+}
+
+impl<E: Endian> Debug for SegmentCommand64<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SegmentCommand64")
+            .field("segname", &Name(self.name()))
+            .field("vmaddr", &self.vmaddr)
+            .field("vmsize", &self.vmsize)
+            .field("fileoff", &self.fileoff)
+            .field("filesize", &self.filesize)
+            .field("maxprot", &self.maxprot)
+            .field("initprot", &self.initprot)
+            .field("nsects", &self.nsects)
+            .field("flags", &self.flags)
+            .finish()
+    }
+}
+
+impl<E: Endian> Section64<E> {
+    pub fn name(&self) -> &[u8] {
+        name(&self.sectname)
+    }
+
+    pub fn segname(&self) -> &[u8] {
+        name(&self.segname)
+    }
+
+    pub fn addr(&self) -> usize {
+        self.addr.get() as usize
+    }
+
+    pub fn size(&self) -> usize {
+        self.size.get() as usize
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset.get() as usize
+    }
+
+    /// The alignment as a power of two.
+    pub fn align(&self) -> u32 {
+        self.align.get()
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags.get()
+    }
+
+    /// Whether the section takes no space in the file, like `__bss`.
+    pub fn is_zerofill(&self) -> bool {
+        matches!(self.flags() & SECTION_TYPE, S_ZEROFILL | S_GB_ZEROFILL | S_THREAD_LOCAL_ZEROFILL)
+    }
+
+    /// The bytes of the section in `buf`, the file that the section is from. Zero filled sections have none.
+    pub fn data<'a>(&self, buf: &'a [u8]) -> Result<&'a [u8], Error> {
+        if self.is_zerofill() {
+            return Ok(&[]);
+        }
+        match bytes_at(buf, self.offset(), self.size()) {
+            Some(data) => Ok(data),
+            None => e(MachoError::SectionOutOfBounds { offset: self.offset(), size: self.size() }),
+        }
+    }
+}
+
+impl<E: Endian> Debug for Section64<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Section64")
+            .field("sectname", &Name(self.name()))
+            .field("segname", &Name(self.segname()))
+            .field("addr", &self.addr)
+            .field("size", &self.size)
+            .field("offset", &self.offset)
+            .field("align", &self.align)
+            .field("flags", &self.flags)
+            .finish()
+    }
+}
+
+impl<E: Endian> EntryPointCommand<E> {
+    /// The file offset of `main`.
+    pub fn entryoff(&self) -> usize {
+        self.entryoff.get() as usize
+    }
+
+    /// The stack size of the main thread, or 0 for the default.
+    pub fn stacksize(&self) -> usize {
+        self.stacksize.get() as usize
+    }
+}
+
+impl<E: Endian> UuidCommand<E> {
+    pub fn uuid(&self) -> [u8; 16] {
+        self.uuid
+    }
+}
+
+impl<E: Endian> BuildVersionCommand<E> {
+    pub fn platform(&self) -> Result<Platform, Error> {
+        match self.platform.get().known() {
+            Ok(o) => Ok(o),
+            Err(platform) => e(MachoError::UnknownPlatform(platform)),
+        }
+    }
+
+    pub fn minos(&self) -> Version {
+        Version(self.minos.get())
+    }
+
+    pub fn sdk(&self) -> Version {
+        Version(self.sdk.get())
+    }
+}
+
+impl<E: Endian> BuildToolVersion<E> {
+    /// The `TOOL_*` number of the tool, like 3 for ld.
+    pub fn tool(&self) -> u32 {
+        self.tool.get()
+    }
+
+    pub fn version(&self) -> Version {
+        Version(self.version.get())
+    }
+}
+
+impl<E: Endian> DylibCommand<E> {
+    pub fn cmd(&self) -> LoadCmdUnchecked {
+        self.cmd.get()
+    }
+
+    pub fn timestamp(&self) -> u32 {
+        self.timestamp.get()
+    }
+
+    pub fn current_version(&self) -> Version {
+        Version(self.current_version.get())
+    }
+
+    pub fn compatibility_version(&self) -> Version {
+        Version(self.compatibility_version.get())
+    }
+}
+
+impl<E: Endian> SymtabCommand<E> {
+    pub fn symoff(&self) -> usize {
+        self.symoff.get() as usize
+    }
+
+    pub fn nsyms(&self) -> usize {
+        self.nsyms.get() as usize
+    }
+
+    pub fn stroff(&self) -> usize {
+        self.stroff.get() as usize
+    }
+
+    pub fn strsize(&self) -> usize {
+        self.strsize.get() as usize
+    }
+}
+
+impl<E: Endian> DyldInfoCommand<E> {
+    /// The offset and the size of the rebase opcodes.
+    pub fn rebase(&self) -> (usize, usize) {
+        (self.rebase_off.get() as usize, self.rebase_size.get() as usize)
+    }
+
+    pub fn bind(&self) -> (usize, usize) {
+        (self.bind_off.get() as usize, self.bind_size.get() as usize)
+    }
+
+    pub fn weak_bind(&self) -> (usize, usize) {
+        (self.weak_bind_off.get() as usize, self.weak_bind_size.get() as usize)
+    }
+
+    pub fn lazy_bind(&self) -> (usize, usize) {
+        (self.lazy_bind_off.get() as usize, self.lazy_bind_size.get() as usize)
+    }
+
+    /// The offset and the size of the trie of the exported symbols.
+    pub fn export(&self) -> (usize, usize) {
+        (self.export_off.get() as usize, self.export_size.get() as usize)
+    }
+}
+
+impl<E: Endian> LinkeditDataCommand<E> {
+    pub fn cmd(&self) -> LoadCmdUnchecked {
+        self.cmd.get()
+    }
+
+    pub fn dataoff(&self) -> usize {
+        self.dataoff.get() as usize
+    }
+
+    pub fn datasize(&self) -> usize {
+        self.datasize.get() as usize
+    }
+}
+
+impl<E: Endian> DysymtabCommand<E> {
+    /// The range of the local symbols in the symbol table, as a first index and a count.
+    pub fn locals(&self) -> (usize, usize) {
+        (self.ilocalsym.get() as usize, self.nlocalsym.get() as usize)
+    }
+
+    /// The range of the defined external symbols.
+    pub fn extdefs(&self) -> (usize, usize) {
+        (self.iextdefsym.get() as usize, self.nextdefsym.get() as usize)
+    }
+
+    /// The range of the undefined symbols.
+    pub fn undefs(&self) -> (usize, usize) {
+        (self.iundefsym.get() as usize, self.nundefsym.get() as usize)
+    }
+
+    /// The offset and the count of the indirect symbol table, the `u32` symbol indices of the stubs and pointers.
+    pub fn indirect_syms(&self) -> (usize, usize) {
+        (self.indirectsymoff.get() as usize, self.nindirectsyms.get() as usize)
+    }
+}
+
+/// `buf[offset..offset + size]`, or `None` if the range isn't contained in `buf`.
+fn bytes_at(buf: &[u8], offset: usize, size: usize) -> Option<&[u8]> {
+    buf.get(offset..offset.checked_add(size)?)
+}
+
+/// An `LC_SEGMENT_64` command with its sections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment64<'a, E: Endian = LittleEndian> {
+    pub command: &'a SegmentCommand64<E>,
+    pub sections: &'a [Section64<E>],
+}
+
+/// An `LC_BUILD_VERSION` command with the versions of the tools that built the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildVersion<'a, E: Endian = LittleEndian> {
+    pub command: &'a BuildVersionCommand<E>,
+    pub tools: &'a [BuildToolVersion<E>],
+}
+
+/// A command that names a dylib, with the name read from the command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dylib<'a, E: Endian = LittleEndian> {
+    pub command: &'a DylibCommand<E>,
+    /// The install name of the dylib, without the terminating null byte.
+    pub name: &'a [u8],
+}
+
+/// The load commands that quack knows how to decode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadCommand<'a, E: Endian = LittleEndian> {
+    Segment64(Segment64<'a, E>),
+    Main(&'a EntryPointCommand<E>),
+    Uuid(&'a UuidCommand<E>),
+    BuildVersion(BuildVersion<'a, E>),
+    /// `LC_LOAD_DYLIB` and its weak, re-exported, lazy and upward variants.
+    LoadDylib(Dylib<'a, E>),
+    IdDylib(Dylib<'a, E>),
+    Symtab(&'a SymtabCommand<E>),
+    Dysymtab(&'a DysymtabCommand<E>),
+    /// `LC_DYLD_INFO` or `LC_DYLD_INFO_ONLY`.
+    DyldInfo(&'a DyldInfoCommand<E>),
+    DyldChainedFixups(&'a LinkeditDataCommand<E>),
+    /// Any other command, with all of its bytes.
+    Other { cmd: LoadCmdUnchecked, bytes: &'a [u8] },
+}
+
+/// The string that an `lc_str` at `str_offset` of the command `bytes` points to. `offset` is the file
+/// offset of the command, for errors.
+fn lc_str(bytes: &[u8], str_offset: usize, min: usize, offset: usize) -> Result<&[u8], Error> {
+    let string = bytes.get(str_offset..).filter(|_| str_offset >= min);
+    match string.and_then(|string| Some(&string[..string.iter().position(|&b| b == b'\0')?])) {
+        Some(string) => Ok(string),
+        None => e(MachoError::BadCommandString { offset }),
+    }
+}
+
+impl<'a, E: Endian> Dylib<'a, E> {
+    fn from(bytes: &'a [u8], offset: usize, cmd: u32) -> Result<Dylib<'a, E>, Error> {
+        let (command, _) = DylibCommand::<E>::from_buf(bytes)
+            .map_err(|_| Error::Macho(MachoError::TruncatedCommand { offset, cmd }))?;
+        let name = lc_str(bytes, command.name.get() as usize, size_of::<DylibCommand<E>>(), offset)?;
+        Ok(Dylib { command, name })
+    }
+}
+
+/// The load commands of a file, in the order they are in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadCommands<'a, E: Endian = LittleEndian> {
+    cmds: &'a [u8],
+    offset: usize,
+    left: usize,
+    endian: PhantomData<E>,
+}
+
+impl<'a, E: Endian> LoadCommands<'a, E> {
+    /// The command at `self.offset` and its size.
+    fn read(&self) -> Result<(LoadCommand<'a, E>, usize), Error> {
+        // The commands start right after the header
+        let offset = size_of::<MachHeader64<E>>() + self.offset;
+        let rest = &self.cmds[self.offset..];
+        let (head, _) = LoadCommandHead::<E>::from_buf(rest)
+            .map_err(|_| Error::Macho(MachoError::BadCommandSize { offset, cmdsize: rest.len() }))?;
+        let cmdsize = head.cmdsize.get() as usize;
+        // The 64-bit commands are padded to 8 bytes, which keeps all of them aligned
+        if cmdsize < size_of::<LoadCommandHead<E>>() || !cmdsize.is_multiple_of(8) || cmdsize > rest.len() {
+            return e(MachoError::BadCommandSize { offset, cmdsize });
+        }
+        let bytes = &rest[..cmdsize];
+        let cmd = head.cmd.get();
+        let truncated = |_| Error::Macho(MachoError::TruncatedCommand { offset, cmd: cmd.unknown() });
+        let command = match cmd.known() {
+            Ok(LoadCmd::Segment64) => {
+                let (command, rest) = SegmentCommand64::from_buf(bytes).map_err(truncated)?;
+                let (sections, _) = Section64::slice_from_buf(rest, command.nsects.get() as usize).map_err(truncated)?;
+                LoadCommand::Segment64(Segment64 { command, sections })
+            }
+            Ok(LoadCmd::Main) => LoadCommand::Main(EntryPointCommand::from_buf(bytes).map_err(truncated)?.0),
+            Ok(LoadCmd::Uuid) => LoadCommand::Uuid(UuidCommand::from_buf(bytes).map_err(truncated)?.0),
+            Ok(LoadCmd::BuildVersion) => {
+                let (command, rest) = BuildVersionCommand::from_buf(bytes).map_err(truncated)?;
+                let (tools, _) = BuildToolVersion::slice_from_buf(rest, command.ntools.get() as usize).map_err(truncated)?;
+                LoadCommand::BuildVersion(BuildVersion { command, tools })
+            }
+            Ok(
+                LoadCmd::LoadDylib
+                | LoadCmd::LoadWeakDylib
+                | LoadCmd::ReexportDylib
+                | LoadCmd::LazyLoadDylib
+                | LoadCmd::LoadUpwardDylib
+            ) => LoadCommand::LoadDylib(Dylib::from(bytes, offset, cmd.unknown())?),
+            Ok(LoadCmd::IdDylib) => LoadCommand::IdDylib(Dylib::from(bytes, offset, cmd.unknown())?),
+            Ok(LoadCmd::Symtab) => LoadCommand::Symtab(SymtabCommand::from_buf(bytes).map_err(truncated)?.0),
+            Ok(LoadCmd::Dysymtab) => LoadCommand::Dysymtab(DysymtabCommand::from_buf(bytes).map_err(truncated)?.0),
+            Ok(LoadCmd::DyldInfo | LoadCmd::DyldInfoOnly) => {
+                LoadCommand::DyldInfo(DyldInfoCommand::from_buf(bytes).map_err(truncated)?.0)
+            }
+            Ok(LoadCmd::DyldChainedFixups) => {
+                LoadCommand::DyldChainedFixups(LinkeditDataCommand::from_buf(bytes).map_err(truncated)?.0)
+            }
+            _ => LoadCommand::Other { cmd, bytes },
+        };
+        Ok((command, cmdsize))
+    }
+}
+
+impl<'a, E: Endian> Iterator for LoadCommands<'a, E> {
+    type Item = Result<LoadCommand<'a, E>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == 0 {
+            return None;
+        }
+        self.left -= 1;
+        match self.read() {
+            Ok((command, cmdsize)) => {
+                self.offset += cmdsize;
+                Some(Ok(command))
+            }
+            Err(err) => {
+                self.left = 0;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MachoFile64<'a, E: Endian = LittleEndian> {
+    pub buf: &'a [u8],
+    pub mh: &'a MachHeader64<E>,
+    /// The `sizeofcmds` bytes of load commands after the header.
+    pub cmds: &'a [u8],
+    pub symtab: Option<&'a [Nlist64<E>]>,
+    pub sym_names: Option<Strings<'a>>,
+    pub dysymtab: Option<&'a DysymtabCommand<E>>,
+}
+
+impl<'a, E: Endian> MachoFile64<'a, E> {
+    pub fn load_commands(&self) -> LoadCommands<'a, E> {
+        LoadCommands { cmds: self.cmds, offset: 0, left: self.mh.ncmds(), endian: PhantomData }
+    }
+
+    /// The first command that `f` picks out.
+    fn find_command<T>(&self, f: impl Fn(LoadCommand<'a, E>) -> Option<T>) -> Result<Option<T>, Error> {
+        for command in self.load_commands() {
+            if let Some(found) = f(command?) {
+                return Ok(Some(found));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = Result<Segment64<'a, E>, Error>> + 'a {
+        self.load_commands().filter_map(|command| match command {
+            Ok(LoadCommand::Segment64(segment)) => Some(Ok(segment)),
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+    }
+
+    /// Finds a segment by name, like `__TEXT`.
+    pub fn segment(&self, name: &[u8]) -> Result<Option<Segment64<'a, E>>, Error> {
+        self.find_command(|command| match command {
+            LoadCommand::Segment64(segment) if segment.command.name() == name => Some(segment),
+            _ => None,
+        })
+    }
+
+    /// Finds a section by the names of its segment and itself, like `__TEXT` and `__text`.
+    pub fn section(&self, segname: &[u8], sectname: &[u8]) -> Result<Option<&'a Section64<E>>, Error> {
+        self.find_command(|command| match command {
+            LoadCommand::Segment64(segment) => {
+                segment.sections.iter().find(|sect| sect.segname() == segname && sect.name() == sectname)
+            }
+            _ => None,
+        })
+    }
+
+    /// The `LC_MAIN` command of an executable.
+    pub fn entry(&self) -> Result<Option<&'a EntryPointCommand<E>>, Error> {
+        self.find_command(|command| match command {
+            LoadCommand::Main(main) => Some(main),
+            _ => None,
+        })
+    }
+
+    pub fn uuid(&self) -> Result<Option<[u8; 16]>, Error> {
+        self.find_command(|command| match command {
+            LoadCommand::Uuid(uuid) => Some(uuid.uuid()),
+            _ => None,
+        })
+    }
+
+    pub fn build_version(&self) -> Result<Option<BuildVersion<'a, E>>, Error> {
+        self.find_command(|command| match command {
+            LoadCommand::BuildVersion(build_version) => Some(build_version),
+            _ => None,
+        })
+    }
+
+    /// All the entries of the symbol table, the debugging ones included.
+    pub fn symbols(&self) -> Symbols<'a, E> {
+        Symbols::new(self.symtab.unwrap_or(&[]), self.segments())
+    }
+
+    /// The symbols in the `(first, count)` range of the symbol table, as `LC_DYSYMTAB` gives them.
+    fn sym_range(&self, (first, count): (usize, usize)) -> Option<Symbols<'a, E>> {
+        let nlists = self.symtab?.get(first..first.checked_add(count)?)?;
+        Some(Symbols::new(nlists, self.segments()))
+    }
+
+    /// The local symbols, if the file has an `LC_DYSYMTAB`.
+    pub fn local_symbols(&self) -> Option<Symbols<'a, E>> {
+        self.sym_range(self.dysymtab?.locals())
+    }
+
+    /// The external symbols that the file defines, if it has an `LC_DYSYMTAB`.
+    pub fn extdef_symbols(&self) -> Option<Symbols<'a, E>> {
+        self.sym_range(self.dysymtab?.extdefs())
+    }
+
+    /// The symbols that the file imports, if it has an `LC_DYSYMTAB`.
+    pub fn undef_symbols(&self) -> Option<Symbols<'a, E>> {
+        self.sym_range(self.dysymtab?.undefs())
+    }
+
+    pub fn dyld_info(&self) -> Result<Option<&'a DyldInfoCommand<E>>, Error> {
+        self.find_command(|command| match command {
+            LoadCommand::DyldInfo(dyld_info) => Some(dyld_info),
+            _ => None,
+        })
+    }
+
+    pub fn chained_fixups(&self) -> Result<Option<&'a LinkeditDataCommand<E>>, Error> {
+        self.find_command(|command| match command {
+            LoadCommand::DyldChainedFixups(chained_fixups) => Some(chained_fixups),
+            _ => None,
+        })
+    }
+
+    /// Every pointer that dyld rebases or binds when it loads the file, from `LC_DYLD_CHAINED_FIXUPS`
+    /// or else from the opcodes of `LC_DYLD_INFO`.
+    pub fn fixups(&self) -> Result<Fixups<'a, E>, Error> {
+        Fixups::new(self.buf, self.load_commands(), self.chained_fixups()?, self.dyld_info()?)
+    }
+
+    /// The dylibs that the file depends on, in the order of their ordinals.
+    pub fn dylibs(&self) -> impl Iterator<Item = Result<Dylib<'a, E>, Error>> + 'a {
+        self.load_commands().filter_map(|command| match command {
+            Ok(LoadCommand::LoadDylib(dylib)) => Some(Ok(dylib)),
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+    }
+}
+
+pub fn with(buf: &[u8]) -> Result<MachoFile64<'_>, Error> {
+    match magic(buf) {
+        Some(MH_MAGIC_64) => (),
+        Some(magic @ (MH_MAGIC | MH_CIGAM | MH_CIGAM_64)) => return e(MachoError::UnsupportedMagic(magic)),
+        Some(magic) => return e(MachoError::BadMagic(magic)),
+        None => return e(MachoError::TruncatedHeader(buf.len())),
+    }
+    let (mh, rest) = MachHeader64::from_buf(buf).map_err(|_| Error::Macho(MachoError::TruncatedHeader(buf.len())))?;
+    let cmds = match rest.get(..mh.sizeofcmds()) {
+        Some(cmds) => cmds,
+        None => return e(MachoError::CommandsOutOfBounds(mh.sizeofcmds())),
+    };
+    let mut macho = MachoFile64 { buf, mh, cmds, symtab: None, sym_names: None, dysymtab: None };
+    // Going through the commands once up front reports a broken one before anything is read from it
+    for command in macho.load_commands() {
+        match command? {
+            LoadCommand::Symtab(symtab) => {
+                macho.symtab = Some(symtab_entries(buf, symtab)?);
+                macho.sym_names = Some(sym_names(buf, symtab)?);
+            }
+            LoadCommand::Dysymtab(dysymtab) => macho.dysymtab = Some(dysymtab),
+            _ => (),
+        }
+    }
+    if let Some(dysymtab) = macho.dysymtab {
+        let nsyms = macho.symtab.map_or(0, |symtab| symtab.len());
+        for (first, count) in [dysymtab.locals(), dysymtab.extdefs(), dysymtab.undefs()] {
+            if first.checked_add(count).is_none_or(|end| end > nsyms) {
+                return e(MachoError::BadSymbolRange { first, count });
+            }
+        }
+    }
+    Ok(macho)
+}
+
+fn symtab_entries<'a, E: Endian>(buf: &'a [u8], symtab: &SymtabCommand<E>) -> Result<&'a [Nlist64<E>], Error> {
+    let (offset, count) = (symtab.symoff(), symtab.nsyms());
+    match buf.get(offset..).map(|rest| Nlist64::slice_from_buf(rest, count)) {
+        Some(Ok((nlists, _))) => Ok(nlists),
+        _ => e(MachoError::SymbolsOutOfBounds { offset, count }),
+    }
+}
+
+fn sym_names<'a, E: Endian>(buf: &'a [u8], symtab: &SymtabCommand<E>) -> Result<Strings<'a>, Error> {
+    let (offset, size) = (symtab.stroff(), symtab.strsize());
+    match bytes_at(buf, offset, size) {
+        Some(bytes) => match Strings::terminated(bytes, offset) {
+            Some(strings) => Ok(strings),
+            None => e(MachoError::UnterminatedStrings { offset }),
+        },
+        None => e(MachoError::StringsOutOfBounds { offset, size }),
+    }
+}
@@ -0,0 +1,196 @@
+use core::fmt::{self, Debug, Formatter};
+
+use crate::{
+    macho::parse::ffi_types::{CpuType, CpuTypeUnchecked, FileType, FileTypeUnchecked, LoadCmd, LoadCmdUnchecked, Platform, PlatformUnchecked},
+    utils::ToKnown,
+};
+
+impl ToKnown for CpuTypeUnchecked {
+    type Known = CpuType;
+    type Unknown = u32;
+
+    fn known(&self) -> Result<Self::Known, Self::Unknown> {
+        let u = self.unknown();
+        if [0x07, 0x0C, 0x12, 0x0100_0007, 0x0100_000C, 0x0100_0012, 0x0200_000C].contains(&u) {
+            Ok(unsafe { self.known })
+        } else {
+            Err(u)
+        }
+    }
+
+    fn unknown(&self) -> Self::Unknown {
+        unsafe { self.unknown }
+    }
+}
+
+impl ToKnown for FileTypeUnchecked {
+    type Known = FileType;
+    type Unknown = u32;
+
+    fn known(&self) -> Result<Self::Known, Self::Unknown> {
+        let u = self.unknown();
+        if (0x01..=0x0C).contains(&u) {
+            Ok(unsafe { self.known })
+        } else {
+            Err(u)
+        }
+    }
+
+    fn unknown(&self) -> Self::Unknown {
+        unsafe { self.unknown }
+    }
+}
+
+impl ToKnown for LoadCmdUnchecked {
+    type Known = LoadCmd;
+    type Unknown = u32;
+
+    fn known(&self) -> Result<Self::Known, Self::Unknown> {
+        let u = self.unknown();
+        if [
+            0x01, 0x02, 0x04, 0x05, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F, 0x19, 0x1B, 0x1D, 0x1E, 0x20, 0x21, 0x22, 0x24, 0x25,
+            0x26, 0x27, 0x29, 0x2A, 0x2C, 0x2D, 0x32, 0x8000_0018, 0x8000_001C, 0x8000_001F, 0x8000_0022, 0x8000_0023,
+            0x8000_0028, 0x8000_0033, 0x8000_0034,
+        ].contains(&u) {
+            Ok(unsafe { self.known })
+        } else {
+            Err(u)
+        }
+    }
+
+    fn unknown(&self) -> Self::Unknown {
+        unsafe { self.unknown }
+    }
+}
+
+impl ToKnown for PlatformUnchecked {
+    type Known = Platform;
+    type Unknown = u32;
+
+    fn known(&self) -> Result<Self::Known, Self::Unknown> {
+        let u = self.unknown();
+        if (0x01..=0x0C).contains(&u) {
+            Ok(unsafe { self.known })
+        } else {
+            Err(u)
+        }
+    }
+
+    fn unknown(&self) -> Self::Unknown {
+        unsafe { self.unknown }
+    }
+}
+
+impl Default for CpuTypeUnchecked {
+    fn default() -> Self {
+        Self { unknown: 0 }
+    }
+}
+
+impl Default for FileTypeUnchecked {
+    fn default() -> Self {
+        Self { unknown: 0 }
+    }
+}
+
+impl Default for LoadCmdUnchecked {
+    fn default() -> Self {
+        Self { unknown: 0 }
+    }
+}
+
+impl Default for PlatformUnchecked {
+    fn default() -> Self {
+        Self { unknown: 0 }
+    }
+}
+
+impl Debug for CpuTypeUnchecked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Ok(t) = self.known() {
+            t.fmt(f)
+        } else {
+            write!(f, "UnknownCpuType(0x{:X?})", self.unknown())
+        }
+    }
+}
+
+impl Debug for FileTypeUnchecked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Ok(t) = self.known() {
+            t.fmt(f)
+        } else {
+            write!(f, "UnknownFileType(0x{:X?})", self.unknown())
+        }
+    }
+}
+
+impl Debug for LoadCmdUnchecked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Ok(t) = self.known() {
+            t.fmt(f)
+        } else {
+            write!(f, "UnknownLoadCmd(0x{:X?})", self.unknown())
+        }
+    }
+}
+
+impl Debug for PlatformUnchecked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Ok(t) = self.known() {
+            t.fmt(f)
+        } else {
+            write!(f, "UnknownPlatform(0x{:X?})", self.unknown())
+        }
+    }
+}
+
+impl PartialEq for CpuTypeUnchecked {
+    fn eq(&self, other: &Self) -> bool {
+        self.unknown().eq(&other.unknown())
+    }
+}
+
+impl PartialEq for FileTypeUnchecked {
+    fn eq(&self, other: &Self) -> bool {
+        self.unknown().eq(&other.unknown())
+    }
+}
+
+impl PartialEq for LoadCmdUnchecked {
+    fn eq(&self, other: &Self) -> bool {
+        self.unknown().eq(&other.unknown())
+    }
+}
+
+impl PartialEq for PlatformUnchecked {
+    fn eq(&self, other: &Self) -> bool {
+        self.unknown().eq(&other.unknown())
+    }
+}
+
+#[test]
+fn miri_enum() {
+    use crate::utils::TransmuteSafe;
+
+    let mut cmd = LoadCmdUnchecked::default();
+    for i in (0..0x01FFu32).chain(0x8000_0000..0x8000_01FF) {
+        let bytes = cmd.as_bytes_mut();
+        bytes.copy_from_slice(&i.to_le_bytes());
+        assert_eq!(i, cmd.unknown());
+        match cmd.known() {
+            Ok(o) => assert_eq!(o as u32, i),
+            Err(e) => assert_eq!(e, i),
+        }
+    }
+    let mut cputype = CpuTypeUnchecked::default();
+    for i in (0..0x01FFu32).chain(0x0100_0000..0x0100_01FF).chain(0x0200_0000..0x0200_01FF) {
+        let bytes = cputype.as_bytes_mut();
+        bytes.copy_from_slice(&i.to_le_bytes());
+        assert_eq!(i, cputype.unknown());
+        match cputype.known() {
+            Ok(o) => assert_eq!(o as u32, i),
+            Err(e) => assert_eq!(e, i),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use core::fmt::{self, Debug};
+
+use crate::{error::MachoError, macho::e, utils::{ToKnown, TransmuteSafe}, Error};
+
+use super::{
+    ffi_types::{CpuType, CpuTypeUnchecked, FatArch, FatArch64, FatHeader},
+    with, MachoFile64,
+};
+
+pub const FAT_MAGIC: u32 = 0xcafebabe;
+pub const FAT_MAGIC_64: u32 = 0xcafebabf;
+
+/// Java class files start with `FAT_MAGIC` too, followed by their version, which is at least 45.
+/// No universal file has that many slices.
+const MAX_FAT_ARCHS: u32 = 45;
+
+/// Whether `buf` starts with the header of a universal file.
+pub fn is_fat(buf: &[u8]) -> bool {
+    match FatHeader::from_buf(buf) {
+        Ok((header, _)) => [FAT_MAGIC, FAT_MAGIC_64].contains(&header.magic()) && header.nfat_arch() < MAX_FAT_ARCHS,
+        Err(_) => false,
+    }
+}
+
+impl FatHeader {
+    pub fn magic(&self) -> u32 {
+        self.magic.get()
+    }
+
+    pub fn nfat_arch(&self) -> u32 {
+        self.nfat_arch.get()
+    }
+}
+
+/// The slice table, which holds 64-bit offsets and sizes with `FAT_MAGIC_64`.
+#[derive(Debug, Clone, PartialEq)]
+enum FatArchs<'a> {
+    Fat32(&'a [FatArch]),
+    Fat64(&'a [FatArch64]),
+}
+
+/// A universal file, which holds a Mach-O file for each architecture it runs on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FatFile<'a> {
+    pub buf: &'a [u8],
+    pub header: &'a FatHeader,
+    archs: FatArchs<'a>,
+}
+
+/// The Mach-O file of an architecture in a universal file.
+#[derive(Clone, Copy, PartialEq)]
+pub struct FatSlice<'a> {
+    cputype: CpuTypeUnchecked,
+    cpusubtype: u32,
+    offset: usize,
+    align: u32,
+    /// The bytes of the slice, `size` of them from `offset`.
+    pub data: &'a [u8],
+}
+
+impl<'a> FatSlice<'a> {
+    pub fn cputype(&self) -> Result<CpuType, Error> {
+        match self.cputype.known() {
+            Ok(o) => Ok(o),
+            Err(cputype) => e(MachoError::UnknownCpuType(cputype)),
+        }
+    }
+
+    pub fn cpusubtype(&self) -> u32 {
+        self.cpusubtype
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The alignment of the slice in the file, as a power of two.
+    pub fn align(&self) -> u32 {
+        self.align
+    }
+
+    /// Parses the Mach-O file of the slice.
+    pub fn parse(&self) -> Result<MachoFile64<'a>, Error> {
+        with(self.data)
+    }
+}
+
+impl Debug for FatSlice<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FatSlice")
+            .field("cputype", &self.cputype)
+            .field("cpusubtype", &format_args!("{:#x}", self.cpusubtype))
+            .field("offset", &format_args!("{:#x}", self.offset))
+            .field("size", &format_args!("{:#x}", self.size()))
+            .field("align", &format_args!("2^{}", self.align))
+            .finish()
+    }
+}
+
+/// `buf[offset..offset + size]` as a slice, or an error if it is out of the file.
+fn fat_slice(
+    buf: &[u8],
+    cputype: CpuTypeUnchecked,
+    cpusubtype: u32,
+    offset: u64,
+    size: u64,
+    align: u32,
+) -> Result<FatSlice<'_>, Error> {
+    let out_of_bounds = || Error::Macho(MachoError::FatSliceOutOfBounds { offset, size });
+    let offset = usize::try_from(offset).map_err(|_| out_of_bounds())?;
+    let size = usize::try_from(size).map_err(|_| out_of_bounds())?;
+    let data = super::bytes_at(buf, offset, size).ok_or_else(out_of_bounds)?;
+    Ok(FatSlice { cputype, cpusubtype, offset, align, data })
+}
+
+impl<'a> FatFile<'a> {
+    /// The slices in the order of the slice table.
+    pub fn slices(&self) -> impl Iterator<Item = Result<FatSlice<'a>, Error>> + '_ {
+        let len = match self.archs {
+            FatArchs::Fat32(archs) => archs.len(),
+            FatArchs::Fat64(archs) => archs.len(),
+        };
+        (0..len).map(move |i| match self.archs {
+            FatArchs::Fat32(archs) => {
+                let arch = &archs[i];
+                let (offset, size) = (arch.offset.get() as u64, arch.size.get() as u64);
+                fat_slice(self.buf, arch.cputype.get(), arch.cpusubtype.get(), offset, size, arch.align.get())
+            }
+            FatArchs::Fat64(archs) => {
+                let arch = &archs[i];
+                let (offset, size) = (arch.offset.get(), arch.size.get());
+                fat_slice(self.buf, arch.cputype.get(), arch.cpusubtype.get(), offset, size, arch.align.get())
+            }
+        })
+    }
+
+    /// The first slice for `cputype`, if there is one.
+    pub fn slice(&self, cputype: CpuType) -> Result<Option<FatSlice<'a>>, Error> {
+        for slice in self.slices() {
+            let slice = slice?;
+            if slice.cputype.known() == Ok(cputype) {
+                return Ok(Some(slice));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Parses the header and the slice table of a universal file.
+pub fn with_fat(buf: &[u8]) -> Result<FatFile<'_>, Error> {
+    let (header, rest) = match FatHeader::from_buf(buf) {
+        Ok(header) => header,
+        Err(_) => return e(MachoError::TruncatedHeader(buf.len())),
+    };
+    let nfat_arch = header.nfat_arch() as usize;
+    let out_of_bounds = |_| Error::Macho(MachoError::FatArchsOutOfBounds(nfat_arch));
+    let archs = match header.magic() {
+        FAT_MAGIC => FatArchs::Fat32(FatArch::slice_from_buf(rest, nfat_arch).map_err(out_of_bounds)?.0),
+        FAT_MAGIC_64 => FatArchs::Fat64(FatArch64::slice_from_buf(rest, nfat_arch).map_err(out_of_bounds)?.0),
+        magic => return e(MachoError::BadMagic(magic)),
+    };
+    Ok(FatFile { buf, header, archs })
+}
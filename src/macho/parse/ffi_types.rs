@@ -0,0 +1,401 @@
+use crate::{
+    elf::parse::{swap_unchecked, BigEndian, Endian, Field, LittleEndian, Swap},
+    utils::{ToKnown, TransmuteSafe},
+};
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct MachHeader64<E: Endian = LittleEndian> {
+    pub(super) magic: Field<u32, E>,
+    pub(super) cputype: Field<CpuTypeUnchecked, E>,
+    pub(super) cpusubtype: Field<u32, E>,
+    pub(super) filetype: Field<FileTypeUnchecked, E>,
+    pub(super) ncmds: Field<u32, E>,
+    pub(super) sizeofcmds: Field<u32, E>,
+    pub(super) flags: Field<u32, E>,
+    pub(super) reserved: Field<u32, E>,
+}
+
+/// The header of a universal file, which is big-endian on every platform.
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct FatHeader {
+    pub(super) magic: Field<u32, BigEndian>,
+    pub(super) nfat_arch: Field<u32, BigEndian>,
+}
+
+/// A slice of a universal file with `FAT_MAGIC`.
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct FatArch {
+    pub(super) cputype: Field<CpuTypeUnchecked, BigEndian>,
+    pub(super) cpusubtype: Field<u32, BigEndian>,
+    pub(super) offset: Field<u32, BigEndian>,
+    pub(super) size: Field<u32, BigEndian>,
+    pub(super) align: Field<u32, BigEndian>,
+}
+
+/// A slice of a universal file with `FAT_MAGIC_64`, for slices past 4GiB.
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct FatArch64 {
+    pub(super) cputype: Field<CpuTypeUnchecked, BigEndian>,
+    pub(super) cpusubtype: Field<u32, BigEndian>,
+    pub(super) offset: Field<u64, BigEndian>,
+    pub(super) size: Field<u64, BigEndian>,
+    pub(super) align: Field<u32, BigEndian>,
+    pub(super) reserved: Field<u32, BigEndian>,
+}
+
+#[allow(dead_code)] // These are actually constructed via type re-interpretation
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CpuType {
+    X86 = 0x07,
+    Arm = 0x0C,
+    PowerPC = 0x12,
+    X86_64 = 0x0100_0007,
+    Arm64 = 0x0100_000C,
+    PowerPC64 = 0x0100_0012,
+    Arm64_32 = 0x0200_000C,
+}
+
+#[derive(Copy, Clone)]
+pub union CpuTypeUnchecked {
+    pub(super) unknown: u32,
+    pub(super) known: CpuType,
+}
+
+#[allow(dead_code)] // These are actually constructed via type re-interpretation
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FileType {
+    Object = 0x01,
+    Execute = 0x02,
+    FvmLib = 0x03,
+    Core = 0x04,
+    Preload = 0x05,
+    Dylib = 0x06,
+    Dylinker = 0x07,
+    Bundle = 0x08,
+    DylibStub = 0x09,
+    Dsym = 0x0A,
+    KextBundle = 0x0B,
+    Fileset = 0x0C,
+}
+
+#[derive(Copy, Clone)]
+pub union FileTypeUnchecked {
+    pub(super) unknown: u32,
+    pub(super) known: FileType,
+}
+
+/// The `load_command` that every load command starts with.
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct LoadCommandHead<E: Endian = LittleEndian> {
+    pub(super) cmd: Field<LoadCmdUnchecked, E>,
+    pub(super) cmdsize: Field<u32, E>,
+}
+
+#[allow(dead_code)] // These are actually constructed via type re-interpretation
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LoadCmd {
+    Segment = 0x01,
+    Symtab = 0x02,
+    Thread = 0x04,
+    UnixThread = 0x05,
+    Dysymtab = 0x0B,
+    LoadDylib = 0x0C,
+    IdDylib = 0x0D,
+    LoadDylinker = 0x0E,
+    IdDylinker = 0x0F,
+    Segment64 = 0x19,
+    Uuid = 0x1B,
+    CodeSignature = 0x1D,
+    SegmentSplitInfo = 0x1E,
+    LazyLoadDylib = 0x20,
+    EncryptionInfo = 0x21,
+    DyldInfo = 0x22,
+    VersionMinMacosx = 0x24,
+    VersionMinIphoneos = 0x25,
+    FunctionStarts = 0x26,
+    DyldEnvironment = 0x27,
+    DataInCode = 0x29,
+    SourceVersion = 0x2A,
+    EncryptionInfo64 = 0x2C,
+    LinkerOption = 0x2D,
+    BuildVersion = 0x32,
+    LoadWeakDylib = 0x8000_0018,
+    Rpath = 0x8000_001C,
+    ReexportDylib = 0x8000_001F,
+    DyldInfoOnly = 0x8000_0022,
+    LoadUpwardDylib = 0x8000_0023,
+    Main = 0x8000_0028,
+    DyldExportsTrie = 0x8000_0033,
+    DyldChainedFixups = 0x8000_0034,
+}
+
+#[derive(Copy, Clone)]
+pub union LoadCmdUnchecked {
+    pub(super) unknown: u32,
+    pub(super) known: LoadCmd,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, PartialEq)]
+pub struct SegmentCommand64<E: Endian = LittleEndian> {
+    pub(super) cmd: Field<LoadCmdUnchecked, E>,
+    pub(super) cmdsize: Field<u32, E>,
+    pub(super) segname: [u8; 16],
+    pub(super) vmaddr: Field<u64, E>,
+    pub(super) vmsize: Field<u64, E>,
+    pub(super) fileoff: Field<u64, E>,
+    pub(super) filesize: Field<u64, E>,
+    pub(super) maxprot: Field<u32, E>,
+    pub(super) initprot: Field<u32, E>,
+    pub(super) nsects: Field<u32, E>,
+    pub(super) flags: Field<u32, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, PartialEq)]
+pub struct Section64<E: Endian = LittleEndian> {
+    pub(super) sectname: [u8; 16],
+    pub(super) segname: [u8; 16],
+    pub(super) addr: Field<u64, E>,
+    pub(super) size: Field<u64, E>,
+    pub(super) offset: Field<u32, E>,
+    pub(super) align: Field<u32, E>,
+    pub(super) reloff: Field<u32, E>,
+    pub(super) nreloc: Field<u32, E>,
+    pub(super) flags: Field<u32, E>,
+    pub(super) reserved1: Field<u32, E>,
+    pub(super) reserved2: Field<u32, E>,
+    pub(super) reserved3: Field<u32, E>,
+}
+
+/// `LC_MAIN`
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct EntryPointCommand<E: Endian = LittleEndian> {
+    pub(super) cmd: Field<LoadCmdUnchecked, E>,
+    pub(super) cmdsize: Field<u32, E>,
+    pub(super) entryoff: Field<u64, E>,
+    pub(super) stacksize: Field<u64, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct UuidCommand<E: Endian = LittleEndian> {
+    pub(super) cmd: Field<LoadCmdUnchecked, E>,
+    pub(super) cmdsize: Field<u32, E>,
+    pub(super) uuid: [u8; 16],
+}
+
+/// `LC_BUILD_VERSION`, followed by `ntools` `BuildToolVersion`s.
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct BuildVersionCommand<E: Endian = LittleEndian> {
+    pub(super) cmd: Field<LoadCmdUnchecked, E>,
+    pub(super) cmdsize: Field<u32, E>,
+    pub(super) platform: Field<PlatformUnchecked, E>,
+    pub(super) minos: Field<u32, E>,
+    pub(super) sdk: Field<u32, E>,
+    pub(super) ntools: Field<u32, E>,
+}
+
+#[allow(dead_code)] // These are actually constructed via type re-interpretation
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Platform {
+    MacOS = 0x01,
+    IOS = 0x02,
+    TvOS = 0x03,
+    WatchOS = 0x04,
+    BridgeOS = 0x05,
+    MacCatalyst = 0x06,
+    IOSSimulator = 0x07,
+    TvOSSimulator = 0x08,
+    WatchOSSimulator = 0x09,
+    DriverKit = 0x0A,
+    VisionOS = 0x0B,
+    VisionOSSimulator = 0x0C,
+}
+
+#[derive(Copy, Clone)]
+pub union PlatformUnchecked {
+    pub(super) unknown: u32,
+    pub(super) known: Platform,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct BuildToolVersion<E: Endian = LittleEndian> {
+    pub(super) tool: Field<u32, E>,
+    pub(super) version: Field<u32, E>,
+}
+
+/// `LC_LOAD_DYLIB` and the other commands that name a dylib. The name is stored
+/// in the command itself, `name` bytes from its start.
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct DylibCommand<E: Endian = LittleEndian> {
+    pub(super) cmd: Field<LoadCmdUnchecked, E>,
+    pub(super) cmdsize: Field<u32, E>,
+    pub(super) name: Field<u32, E>,
+    pub(super) timestamp: Field<u32, E>,
+    pub(super) current_version: Field<u32, E>,
+    pub(super) compatibility_version: Field<u32, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SymtabCommand<E: Endian = LittleEndian> {
+    pub(super) cmd: Field<LoadCmdUnchecked, E>,
+    pub(super) cmdsize: Field<u32, E>,
+    pub(super) symoff: Field<u32, E>,
+    pub(super) nsyms: Field<u32, E>,
+    pub(super) stroff: Field<u32, E>,
+    pub(super) strsize: Field<u32, E>,
+}
+
+/// `LC_DYSYMTAB`, which splits the symbol table into ranges and locates the tables that dyld uses.
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct DysymtabCommand<E: Endian = LittleEndian> {
+    pub(super) cmd: Field<LoadCmdUnchecked, E>,
+    pub(super) cmdsize: Field<u32, E>,
+    pub(super) ilocalsym: Field<u32, E>,
+    pub(super) nlocalsym: Field<u32, E>,
+    pub(super) iextdefsym: Field<u32, E>,
+    pub(super) nextdefsym: Field<u32, E>,
+    pub(super) iundefsym: Field<u32, E>,
+    pub(super) nundefsym: Field<u32, E>,
+    pub(super) tocoff: Field<u32, E>,
+    pub(super) ntoc: Field<u32, E>,
+    pub(super) modtaboff: Field<u32, E>,
+    pub(super) nmodtab: Field<u32, E>,
+    pub(super) extrefsymoff: Field<u32, E>,
+    pub(super) nextrefsyms: Field<u32, E>,
+    pub(super) indirectsymoff: Field<u32, E>,
+    pub(super) nindirectsyms: Field<u32, E>,
+    pub(super) extreloff: Field<u32, E>,
+    pub(super) nextrel: Field<u32, E>,
+    pub(super) locreloff: Field<u32, E>,
+    pub(super) nlocrel: Field<u32, E>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Nlist64<E: Endian = LittleEndian> {
+    pub(super) n_strx: Field<u32, E>,
+    pub(super) n_type: u8,
+    pub(super) n_sect: u8,
+    pub(super) n_desc: Field<u16, E>,
+    pub(super) n_value: Field<u64, E>,
+}
+
+/// `LC_DYLD_INFO` and `LC_DYLD_INFO_ONLY`, which locate the opcode streams that tell dyld what to rebase and bind.
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct DyldInfoCommand<E: Endian = LittleEndian> {
+    pub(super) cmd: Field<LoadCmdUnchecked, E>,
+    pub(super) cmdsize: Field<u32, E>,
+    pub(super) rebase_off: Field<u32, E>,
+    pub(super) rebase_size: Field<u32, E>,
+    pub(super) bind_off: Field<u32, E>,
+    pub(super) bind_size: Field<u32, E>,
+    pub(super) weak_bind_off: Field<u32, E>,
+    pub(super) weak_bind_size: Field<u32, E>,
+    pub(super) lazy_bind_off: Field<u32, E>,
+    pub(super) lazy_bind_size: Field<u32, E>,
+    pub(super) export_off: Field<u32, E>,
+    pub(super) export_size: Field<u32, E>,
+}
+
+/// The commands that locate a blob in `__LINKEDIT`, like `LC_DYLD_CHAINED_FIXUPS` and `LC_CODE_SIGNATURE`.
+#[repr(C)]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct LinkeditDataCommand<E: Endian = LittleEndian> {
+    pub(super) cmd: Field<LoadCmdUnchecked, E>,
+    pub(super) cmdsize: Field<u32, E>,
+    pub(super) dataoff: Field<u32, E>,
+    pub(super) datasize: Field<u32, E>,
+}
+
+swap_unchecked!(CpuTypeUnchecked, FileTypeUnchecked, LoadCmdUnchecked, PlatformUnchecked);
+
+// These unsafe implementations are sound, because each of the implementing types
+// - is repr(C)
+// - doesn't contain any gaps in its memory layout
+// - consists only of integers, byte arrays and unions whose `unknown` integer allows any byte pattern
+unsafe impl TransmuteSafe for FatHeader {}
+unsafe impl TransmuteSafe for FatArch {}
+unsafe impl TransmuteSafe for FatArch64 {}
+unsafe impl<E: Endian> TransmuteSafe for MachHeader64<E> {}
+unsafe impl<E: Endian> TransmuteSafe for LoadCommandHead<E> {}
+unsafe impl<E: Endian> TransmuteSafe for SegmentCommand64<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Section64<E> {}
+unsafe impl<E: Endian> TransmuteSafe for EntryPointCommand<E> {}
+unsafe impl<E: Endian> TransmuteSafe for UuidCommand<E> {}
+unsafe impl<E: Endian> TransmuteSafe for BuildVersionCommand<E> {}
+unsafe impl<E: Endian> TransmuteSafe for BuildToolVersion<E> {}
+unsafe impl<E: Endian> TransmuteSafe for DylibCommand<E> {}
+unsafe impl<E: Endian> TransmuteSafe for SymtabCommand<E> {}
+unsafe impl<E: Endian> TransmuteSafe for DysymtabCommand<E> {}
+unsafe impl<E: Endian> TransmuteSafe for Nlist64<E> {}
+unsafe impl<E: Endian> TransmuteSafe for DyldInfoCommand<E> {}
+unsafe impl<E: Endian> TransmuteSafe for LinkeditDataCommand<E> {}
+
+unsafe impl TransmuteSafe for CpuTypeUnchecked {}
+unsafe impl TransmuteSafe for FileTypeUnchecked {}
+unsafe impl TransmuteSafe for LoadCmdUnchecked {}
+unsafe impl TransmuteSafe for PlatformUnchecked {}
+
+// To ensure that there isn't any accidental padding etc.
+#[test]
+fn sizes_and_alignments() {
+    use std::mem::{align_of, size_of};
+    assert_eq!(align_of::<FatHeader>(), 4);
+    assert_eq!(align_of::<FatArch>(), 4);
+    assert_eq!(align_of::<FatArch64>(), 8);
+    assert_eq!(align_of::<MachHeader64>(), 4);
+    assert_eq!(align_of::<LoadCommandHead>(), 4);
+    assert_eq!(align_of::<SegmentCommand64>(), 8);
+    assert_eq!(align_of::<Section64>(), 8);
+    assert_eq!(align_of::<EntryPointCommand>(), 8);
+    assert_eq!(align_of::<UuidCommand>(), 4);
+    assert_eq!(align_of::<BuildVersionCommand>(), 4);
+    assert_eq!(align_of::<BuildToolVersion>(), 4);
+    assert_eq!(align_of::<DylibCommand>(), 4);
+    assert_eq!(align_of::<SymtabCommand>(), 4);
+    assert_eq!(align_of::<DysymtabCommand>(), 4);
+    assert_eq!(align_of::<Nlist64>(), 8);
+    assert_eq!(align_of::<DyldInfoCommand>(), 4);
+    assert_eq!(align_of::<LinkeditDataCommand>(), 4);
+
+    assert_eq!(size_of::<FatHeader>(), 8);
+    assert_eq!(size_of::<FatArch>(), 20);
+    assert_eq!(size_of::<FatArch64>(), 32);
+    assert_eq!(size_of::<MachHeader64>(), 32);
+    assert_eq!(size_of::<LoadCommandHead>(), 8);
+    assert_eq!(size_of::<SegmentCommand64>(), 72);
+    assert_eq!(size_of::<Section64>(), 80);
+    assert_eq!(size_of::<EntryPointCommand>(), 24);
+    assert_eq!(size_of::<UuidCommand>(), 24);
+    assert_eq!(size_of::<BuildVersionCommand>(), 24);
+    assert_eq!(size_of::<BuildToolVersion>(), 8);
+    assert_eq!(size_of::<DylibCommand>(), 24);
+    assert_eq!(size_of::<SymtabCommand>(), 24);
+    assert_eq!(size_of::<DysymtabCommand>(), 80);
+    assert_eq!(size_of::<Nlist64>(), 16);
+    assert_eq!(size_of::<DyldInfoCommand>(), 48);
+    assert_eq!(size_of::<LinkeditDataCommand>(), 16);
+
+    assert_eq!(size_of::<CpuTypeUnchecked>(), 4);
+    assert_eq!(size_of::<FileTypeUnchecked>(), 4);
+    assert_eq!(size_of::<LoadCmdUnchecked>(), 4);
+    assert_eq!(size_of::<PlatformUnchecked>(), 4);
+}
@@ -0,0 +1,676 @@
+use crate::{elf::parse::Endian, error::MachoError, macho::e, Error};
+
+use super::{bytes_at, DyldInfoCommand, LinkeditDataCommand, LoadCommand, LoadCommands, SegmentCommand64};
+
+/// The special library ordinals of binds, for symbols that aren't looked up in a dylib of the file.
+pub mod bind_ordinal {
+    /// The file itself.
+    pub const SELF: i32 = 0;
+    pub const MAIN_EXECUTABLE: i32 = -1;
+    /// Every loaded image, in load order.
+    pub const FLAT_LOOKUP: i32 = -2;
+    /// The images with weak definitions, for coalescing weak symbols.
+    pub const WEAK_LOOKUP: i32 = -3;
+}
+
+const OPCODE_MASK: u8 = 0xf0;
+const IMMEDIATE_MASK: u8 = 0x0f;
+
+const REBASE_OPCODE_DONE: u8 = 0x00;
+const REBASE_OPCODE_SET_TYPE_IMM: u8 = 0x10;
+const REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB: u8 = 0x20;
+const REBASE_OPCODE_ADD_ADDR_ULEB: u8 = 0x30;
+const REBASE_OPCODE_ADD_ADDR_IMM_SCALED: u8 = 0x40;
+const REBASE_OPCODE_DO_REBASE_IMM_TIMES: u8 = 0x50;
+const REBASE_OPCODE_DO_REBASE_ULEB_TIMES: u8 = 0x60;
+const REBASE_OPCODE_DO_REBASE_ADD_ADDR_ULEB: u8 = 0x70;
+const REBASE_OPCODE_DO_REBASE_ULEB_TIMES_SKIPPING_ULEB: u8 = 0x80;
+
+const BIND_OPCODE_DONE: u8 = 0x00;
+const BIND_OPCODE_SET_DYLIB_ORDINAL_IMM: u8 = 0x10;
+const BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB: u8 = 0x20;
+const BIND_OPCODE_SET_DYLIB_SPECIAL_IMM: u8 = 0x30;
+const BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM: u8 = 0x40;
+const BIND_OPCODE_SET_TYPE_IMM: u8 = 0x50;
+const BIND_OPCODE_SET_ADDEND_SLEB: u8 = 0x60;
+const BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB: u8 = 0x70;
+const BIND_OPCODE_ADD_ADDR_ULEB: u8 = 0x80;
+const BIND_OPCODE_DO_BIND: u8 = 0x90;
+const BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB: u8 = 0xa0;
+const BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED: u8 = 0xb0;
+const BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB: u8 = 0xc0;
+
+/// Both rebases and binds have types for 32-bit text fixups, but 64-bit files only use pointers.
+const TYPE_POINTER: u8 = 1;
+const BIND_SYMBOL_FLAGS_WEAK_IMPORT: u8 = 0x1;
+
+const DYLD_CHAINED_IMPORT: u32 = 1;
+const DYLD_CHAINED_IMPORT_ADDEND: u32 = 2;
+const DYLD_CHAINED_IMPORT_ADDEND64: u32 = 3;
+const DYLD_CHAINED_PTR_64: u16 = 2;
+const DYLD_CHAINED_PTR_64_OFFSET: u16 = 6;
+const DYLD_CHAINED_PTR_START_NONE: u16 = 0xffff;
+/// The size of `dyld_chained_fixups_header`.
+const CHAINED_FIXUPS_HEADER: usize = 28;
+/// The size of `dyld_chained_starts_in_segment` before its `page_start` array.
+const CHAINED_STARTS_IN_SEGMENT: usize = 22;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixupKind {
+    /// A pointer into the image, which dyld slides by the load address.
+    Rebase,
+    Bind,
+    /// A bind that dyld does on the first call through a stub, without chained fixups.
+    LazyBind,
+    /// A bind to a weak definition, which dyld coalesces over all the images.
+    WeakBind,
+}
+
+/// A pointer that dyld rewrites when it loads the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixup<'a> {
+    /// The address of the pointer, before sliding.
+    pub address: u64,
+    pub kind: FixupKind,
+    /// The symbol that a bind points to, empty for rebases.
+    pub symbol: &'a [u8],
+    /// The dylib of the symbol, counted from 1 in the order of the `LC_LOAD_DYLIB`s, or a `bind_ordinal`.
+    /// 0 for rebases.
+    pub ordinal: i32,
+    /// What a bind adds to the address of the symbol.
+    pub addend: i64,
+    /// A bind to a symbol that may be missing at runtime, which leaves the pointer null.
+    pub weak_import: bool,
+    /// The address that a rebase points to, before sliding. 0 for binds.
+    pub target: u64,
+}
+
+impl<'a> Fixup<'a> {
+    pub(super) fn rebase(address: u64, target: u64) -> Fixup<'a> {
+        Fixup { address, kind: FixupKind::Rebase, symbol: &[], ordinal: 0, addend: 0, weak_import: false, target }
+    }
+}
+
+fn uleb128(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        if shift >= 64 {
+            return None;
+        }
+        value |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+}
+
+fn sleb128(bytes: &[u8], pos: &mut usize) -> Option<i64> {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        if shift >= 64 {
+            return None;
+        }
+        value |= ((byte & 0x7f) as i64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if shift < 64 && byte & 0x40 != 0 {
+                value |= -1 << shift;
+            }
+            return Some(value);
+        }
+    }
+}
+
+fn u16_at<E: Endian>(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(E::u16(bytes_at(bytes, offset, 2)?.try_into().ok()?))
+}
+
+fn u32_at<E: Endian>(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(E::u32(bytes_at(bytes, offset, 4)?.try_into().ok()?))
+}
+
+fn u64_at<E: Endian>(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(E::u64(bytes_at(bytes, offset, 8)?.try_into().ok()?))
+}
+
+/// The string at `offset` of `bytes`, up to its null byte.
+fn c_str(bytes: &[u8], offset: usize) -> Option<&[u8]> {
+    let string = bytes.get(offset..)?;
+    Some(&string[..string.iter().position(|&b| b == b'\0')?])
+}
+
+/// The `LC_SEGMENT_64` with the index `index`, which is how the fixups refer to the segments.
+fn segment<'a, E: Endian>(commands: &LoadCommands<'a, E>, index: usize) -> Result<&'a SegmentCommand64<E>, Error> {
+    let mut segments = commands.clone().filter_map(|command| match command {
+        Ok(LoadCommand::Segment64(segment)) => Some(Ok(segment.command)),
+        Ok(_) => None,
+        Err(err) => Some(Err(err)),
+    });
+    match segments.nth(index) {
+        Some(segment) => segment,
+        None => e(MachoError::BadFixupSegment(index)),
+    }
+}
+
+/// One of the opcode streams of `LC_DYLD_INFO`. The opcodes set up the state of a fixup, and the `DO_`
+/// opcodes emit it at one or more addresses.
+#[derive(Debug, Clone)]
+struct Opcodes<'a, E: Endian> {
+    kind: FixupKind,
+    bytes: &'a [u8],
+    /// The file offset of `bytes`, for errors.
+    fileoff: usize,
+    pos: usize,
+    segment: Option<&'a SegmentCommand64<E>>,
+    /// The offset of the next fixup in `segment`.
+    offset: u64,
+    symbol: &'a [u8],
+    ordinal: i32,
+    addend: i64,
+    weak_import: bool,
+    /// The fixups left to emit for the last `DO_` opcode, and the bytes between them.
+    count: u64,
+    skip: u64,
+    /// Where the last opcode is in `bytes`, and the opcode, for errors about the fixups it emits.
+    last: (usize, u8),
+}
+
+impl<'a, E: Endian> Opcodes<'a, E> {
+    fn new(kind: FixupKind, buf: &'a [u8], (fileoff, size): (usize, usize)) -> Result<Opcodes<'a, E>, Error> {
+        let bytes = match bytes_at(buf, fileoff, size) {
+            Some(bytes) => bytes,
+            None => return e(MachoError::FixupsOutOfBounds { offset: fileoff, size }),
+        };
+        Ok(Opcodes {
+            kind,
+            bytes,
+            fileoff,
+            pos: 0,
+            segment: None,
+            offset: 0,
+            symbol: &[],
+            ordinal: 0,
+            addend: 0,
+            weak_import: false,
+            count: 0,
+            skip: 0,
+            last: (0, 0),
+        })
+    }
+
+    fn next(&mut self, buf: &'a [u8], commands: &LoadCommands<'a, E>) -> Option<Result<Fixup<'a>, Error>> {
+        loop {
+            // repeat() only sets count with a segment
+            if let (1.., Some(segment)) = (self.count, self.segment) {
+                self.count -= 1;
+                let fixup = self.fixup(buf, segment);
+                // An offset that wrapped around could emit the same fixups over and over
+                let next = self.offset.checked_add(8).and_then(|offset| offset.checked_add(self.skip));
+                return match (fixup, next) {
+                    (Ok(fixup), Some(next)) => {
+                        self.offset = next;
+                        Some(Ok(fixup))
+                    }
+                    (Ok(_), None) => {
+                        self.stop();
+                        let (at, opcode) = self.last;
+                        Some(e(MachoError::BadDyldOpcode { offset: self.fileoff + at, opcode }))
+                    }
+                    (Err(err), _) => {
+                        self.stop();
+                        Some(Err(err))
+                    }
+                };
+            }
+            let at = self.pos;
+            let opcode = *self.bytes.get(at)?;
+            self.pos += 1;
+            self.last = (at, opcode);
+            let more = if self.kind == FixupKind::Rebase {
+                self.rebase_opcode(opcode, commands)
+            } else {
+                self.bind_opcode(opcode, commands)
+            };
+            match more {
+                Ok(true) => (),
+                Ok(false) => {
+                    self.stop();
+                    return None;
+                }
+                Err(Some(err)) => {
+                    self.stop();
+                    return Some(Err(err));
+                }
+                Err(None) => {
+                    self.stop();
+                    return Some(e(MachoError::BadDyldOpcode { offset: self.fileoff + at, opcode }));
+                }
+            }
+        }
+    }
+
+    fn stop(&mut self) {
+        self.pos = self.bytes.len();
+        self.count = 0;
+    }
+
+    fn uleb128(&mut self) -> Result<u64, Option<Error>> {
+        uleb128(self.bytes, &mut self.pos).ok_or(None)
+    }
+
+    fn set_segment(&mut self, index: u8, commands: &LoadCommands<'a, E>) -> Result<bool, Option<Error>> {
+        self.segment = Some(segment(commands, index as usize)?);
+        self.offset = self.uleb128()?;
+        Ok(true)
+    }
+
+    /// Emits `count` fixups, `skip` bytes apart, which needs a segment.
+    fn repeat(&mut self, count: u64, skip: u64) -> Result<bool, Option<Error>> {
+        if self.segment.is_none() {
+            return Err(None);
+        }
+        (self.count, self.skip) = (count, skip);
+        Ok(true)
+    }
+
+    /// Applies a rebase opcode, and tells if there are more of them. `Err(None)` is a bad opcode.
+    fn rebase_opcode(&mut self, opcode: u8, commands: &LoadCommands<'a, E>) -> Result<bool, Option<Error>> {
+        let imm = opcode & IMMEDIATE_MASK;
+        match opcode & OPCODE_MASK {
+            REBASE_OPCODE_DONE => Ok(false),
+            REBASE_OPCODE_SET_TYPE_IMM if imm == TYPE_POINTER => Ok(true),
+            REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB => self.set_segment(imm, commands),
+            REBASE_OPCODE_ADD_ADDR_ULEB => {
+                self.offset = self.offset.wrapping_add(self.uleb128()?);
+                Ok(true)
+            }
+            REBASE_OPCODE_ADD_ADDR_IMM_SCALED => {
+                self.offset = self.offset.wrapping_add(imm as u64 * 8);
+                Ok(true)
+            }
+            REBASE_OPCODE_DO_REBASE_IMM_TIMES => self.repeat(imm as u64, 0),
+            REBASE_OPCODE_DO_REBASE_ULEB_TIMES => {
+                let count = self.uleb128()?;
+                self.repeat(count, 0)
+            }
+            REBASE_OPCODE_DO_REBASE_ADD_ADDR_ULEB => {
+                let skip = self.uleb128()?;
+                self.repeat(1, skip)
+            }
+            REBASE_OPCODE_DO_REBASE_ULEB_TIMES_SKIPPING_ULEB => {
+                let count = self.uleb128()?;
+                let skip = self.uleb128()?;
+                self.repeat(count, skip)
+            }
+            _ => Err(None),
+        }
+    }
+
+    /// Applies a bind opcode, and tells if there are more of them. `Err(None)` is a bad opcode.
+    fn bind_opcode(&mut self, opcode: u8, commands: &LoadCommands<'a, E>) -> Result<bool, Option<Error>> {
+        let imm = opcode & IMMEDIATE_MASK;
+        match opcode & OPCODE_MASK {
+            // The lazy binds are separate entries for each stub, each ending with DONE
+            BIND_OPCODE_DONE => Ok(self.kind == FixupKind::LazyBind),
+            BIND_OPCODE_SET_DYLIB_ORDINAL_IMM => {
+                self.ordinal = imm as i32;
+                Ok(true)
+            }
+            BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB => {
+                self.ordinal = i32::try_from(self.uleb128()?).map_err(|_| None)?;
+                Ok(true)
+            }
+            BIND_OPCODE_SET_DYLIB_SPECIAL_IMM => {
+                // The special ordinals are negative, and sign-extended from the immediate
+                self.ordinal = if imm == 0 { 0 } else { (OPCODE_MASK | imm) as i8 as i32 };
+                Ok(true)
+            }
+            BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM => {
+                self.symbol = c_str(self.bytes, self.pos).ok_or(None)?;
+                self.pos += self.symbol.len() + 1;
+                self.weak_import = imm & BIND_SYMBOL_FLAGS_WEAK_IMPORT != 0;
+                Ok(true)
+            }
+            BIND_OPCODE_SET_TYPE_IMM if imm == TYPE_POINTER => Ok(true),
+            BIND_OPCODE_SET_ADDEND_SLEB => {
+                self.addend = sleb128(self.bytes, &mut self.pos).ok_or(None)?;
+                Ok(true)
+            }
+            BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB => self.set_segment(imm, commands),
+            BIND_OPCODE_ADD_ADDR_ULEB => {
+                self.offset = self.offset.wrapping_add(self.uleb128()?);
+                Ok(true)
+            }
+            BIND_OPCODE_DO_BIND => self.repeat(1, 0),
+            BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB => {
+                let skip = self.uleb128()?;
+                self.repeat(1, skip)
+            }
+            BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED => self.repeat(1, imm as u64 * 8),
+            BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB => {
+                let count = self.uleb128()?;
+                let skip = self.uleb128()?;
+                self.repeat(count, skip)
+            }
+            // BIND_OPCODE_THREADED, which arm64e files had before chained fixups, isn't supported
+            _ => Err(None),
+        }
+    }
+
+    /// The fixup at `offset` of `segment`, which the pointer has to be in.
+    fn fixup(&self, buf: &'a [u8], segment: &SegmentCommand64<E>) -> Result<Fixup<'a>, Error> {
+        let address = (segment.vmaddr() as u64).wrapping_add(self.offset);
+        let out_of_bounds = || Error::Macho(MachoError::FixupOutOfBounds { address });
+        let fits = |size: usize| self.offset.checked_add(8).is_some_and(|end| end <= size as u64);
+        if self.kind == FixupKind::Rebase {
+            // Without chained fixups, the pointers hold their targets in the file
+            if !fits(segment.filesize()) {
+                return Err(out_of_bounds());
+            }
+            let target = u64_at::<E>(buf, segment.fileoff() + self.offset as usize).ok_or_else(out_of_bounds)?;
+            return Ok(Fixup::rebase(address, target));
+        }
+        if !fits(segment.vmsize()) {
+            return Err(out_of_bounds());
+        }
+        Ok(Fixup {
+            address,
+            kind: self.kind,
+            symbol: self.symbol,
+            ordinal: self.ordinal,
+            addend: self.addend,
+            weak_import: self.weak_import,
+            target: 0,
+        })
+    }
+}
+
+/// The chains of the segment that is being walked, from its `dyld_chained_starts_in_segment`.
+#[derive(Debug, Clone)]
+struct ChainedSegment<'a, E: Endian> {
+    command: &'a SegmentCommand64<E>,
+    /// The offset of the `page_start` array in the fixups data.
+    page_starts: usize,
+    page_size: u64,
+    pointer_format: u16,
+    page_count: usize,
+    /// The next page to walk.
+    page: usize,
+    /// The offset of the next pointer of the chain in the segment.
+    chain: Option<u64>,
+}
+
+/// The data of `LC_DYLD_CHAINED_FIXUPS`, in which the pointers in the file link to the next ones.
+#[derive(Debug, Clone)]
+struct ChainedFixups<'a, E: Endian> {
+    data: &'a [u8],
+    /// The file offset of `data`, for errors.
+    dataoff: usize,
+    imports_offset: usize,
+    imports_count: usize,
+    imports_format: u32,
+    symbols_offset: usize,
+    /// The offset of `dyld_chained_starts_in_image`, and the number of segments in it.
+    starts: usize,
+    seg_count: usize,
+    /// The next segment to walk.
+    seg_index: usize,
+    segment: Option<ChainedSegment<'a, E>>,
+    /// The address of the header, which `DYLD_CHAINED_PTR_64_OFFSET` targets are relative to.
+    base: u64,
+}
+
+impl<'a, E: Endian> ChainedFixups<'a, E> {
+    fn new(
+        buf: &'a [u8],
+        command: &LinkeditDataCommand<E>,
+        commands: &LoadCommands<'a, E>,
+    ) -> Result<ChainedFixups<'a, E>, Error> {
+        let (dataoff, size) = (command.dataoff(), command.datasize());
+        let data = match bytes_at(buf, dataoff, size) {
+            Some(data) if data.len() >= CHAINED_FIXUPS_HEADER => data,
+            _ => return e(MachoError::FixupsOutOfBounds { offset: dataoff, size }),
+        };
+        // The header is in bounds, so only the values can be wrong
+        let field = |i: usize| u32_at::<E>(data, i * 4).unwrap_or_default();
+        let [version, starts, imports_offset, symbols_offset, imports_count, imports_format, symbols_format] =
+            [0, 1, 2, 3, 4, 5, 6].map(field);
+        if version != 0 {
+            return e(MachoError::UnsupportedChainedFormat(version));
+        }
+        // Compressed symbol names are only in the format, not in any file
+        if symbols_format != 0 {
+            return e(MachoError::UnsupportedChainedFormat(symbols_format));
+        }
+        let import_size = match imports_format {
+            DYLD_CHAINED_IMPORT => 4,
+            DYLD_CHAINED_IMPORT_ADDEND => 8,
+            DYLD_CHAINED_IMPORT_ADDEND64 => 16,
+            _ => return e(MachoError::UnsupportedChainedFormat(imports_format)),
+        };
+        let (starts, imports_offset, imports_count) = (starts as usize, imports_offset as usize, imports_count as usize);
+        let imports_size = imports_count.checked_mul(import_size).ok_or(bad(dataoff + imports_offset))?;
+        bytes_at(data, imports_offset, imports_size).ok_or(bad(dataoff + imports_offset))?;
+        let seg_count = u32_at::<E>(data, starts).ok_or(bad(dataoff + starts))? as usize;
+        bytes_at(data, starts + 4, seg_count.saturating_mul(4)).ok_or(bad(dataoff + starts))?;
+
+        let mut base = 0;
+        for segment in commands.clone().filter_map(|command| match command {
+            Ok(LoadCommand::Segment64(segment)) => Some(segment.command),
+            _ => None,
+        }) {
+            if segment.fileoff() == 0 && segment.filesize() != 0 {
+                base = segment.vmaddr() as u64;
+                break;
+            }
+        }
+        Ok(ChainedFixups {
+            data,
+            dataoff,
+            imports_offset,
+            imports_count,
+            imports_format,
+            symbols_offset: symbols_offset as usize,
+            starts,
+            seg_count,
+            seg_index: 0,
+            segment: None,
+            base,
+        })
+    }
+
+    fn next(&mut self, buf: &'a [u8], commands: &LoadCommands<'a, E>) -> Option<Result<Fixup<'a>, Error>> {
+        let fixup = self.walk(buf, commands).transpose();
+        if let Some(Err(_)) = fixup {
+            self.seg_index = self.seg_count;
+            self.segment = None;
+        }
+        fixup
+    }
+
+    /// Goes on to the next pointer of the chain, the next page, or the next segment.
+    fn walk(&mut self, buf: &'a [u8], commands: &LoadCommands<'a, E>) -> Result<Option<Fixup<'a>>, Error> {
+        loop {
+            if let Some(segment) = &mut self.segment {
+                if let Some(offset) = segment.chain {
+                    return self.pointer(buf, offset).map(Some);
+                }
+                if segment.page < segment.page_count {
+                    let at = segment.page_starts + segment.page * 2;
+                    let start = u16_at::<E>(self.data, at).ok_or(bad(self.dataoff + at))?;
+                    if start != DYLD_CHAINED_PTR_START_NONE {
+                        segment.chain = Some(segment.page as u64 * segment.page_size + start as u64);
+                    }
+                    segment.page += 1;
+                    continue;
+                }
+                self.segment = None;
+            }
+            if self.seg_index == self.seg_count {
+                return Ok(None);
+            }
+            let index = self.seg_index;
+            self.seg_index += 1;
+            // new() checked that the whole seg_info_offset array is in bounds
+            let seg_info_offset = u32_at::<E>(self.data, self.starts + 4 + index * 4).unwrap_or_default() as usize;
+            if seg_info_offset == 0 {
+                continue;
+            }
+            self.segment = Some(self.chained_segment(commands, index, self.starts + seg_info_offset)?);
+        }
+    }
+
+    /// Reads the `dyld_chained_starts_in_segment` at `at` of the data, for the segment `index`.
+    fn chained_segment(
+        &self,
+        commands: &LoadCommands<'a, E>,
+        index: usize,
+        at: usize,
+    ) -> Result<ChainedSegment<'a, E>, Error> {
+        let header = bytes_at(self.data, at, CHAINED_STARTS_IN_SEGMENT).ok_or(bad(self.dataoff + at))?;
+        let (page_size, pointer_format) = (u16_at::<E>(header, 4), u16_at::<E>(header, 6));
+        let (page_size, pointer_format) = (page_size.unwrap_or_default(), pointer_format.unwrap_or_default());
+        let page_count = u16_at::<E>(header, 20).unwrap_or_default() as usize;
+        if !matches!(pointer_format, DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET) {
+            return e(MachoError::UnsupportedChainedFormat(pointer_format as u32));
+        }
+        let page_starts = at + CHAINED_STARTS_IN_SEGMENT;
+        bytes_at(self.data, page_starts, page_count * 2).ok_or(bad(self.dataoff + at))?;
+        Ok(ChainedSegment {
+            command: segment(commands, index)?,
+            page_starts,
+            page_size: page_size as u64,
+            pointer_format,
+            page_count,
+            page: 0,
+            chain: None,
+        })
+    }
+
+    /// Decodes the pointer at `offset` of the segment that is being walked, and follows its link.
+    fn pointer(&mut self, buf: &'a [u8], offset: u64) -> Result<Fixup<'a>, Error> {
+        let Some(segment) = &mut self.segment else {
+            return e(MachoError::BadFixupSegment(self.seg_index));
+        };
+        let command = segment.command;
+        let address = (command.vmaddr() as u64).wrapping_add(offset);
+        let out_of_bounds = || Error::Macho(MachoError::FixupOutOfBounds { address });
+        if offset.checked_add(8).is_none_or(|end| end > command.filesize() as u64) {
+            return Err(out_of_bounds());
+        }
+        let fileoff = command.fileoff() + offset as usize;
+        let raw = u64_at::<E>(buf, fileoff).ok_or_else(out_of_bounds)?;
+        // The links count 4 byte strides
+        segment.chain = match (raw >> 51) & 0xfff {
+            0 => None,
+            next => Some(offset + next * 4),
+        };
+        if raw >> 63 == 0 {
+            let mut target = (raw & 0xf_ffff_ffff) | ((raw >> 36) & 0xff) << 56;
+            if segment.pointer_format == DYLD_CHAINED_PTR_64_OFFSET {
+                target = target.wrapping_add(self.base);
+            }
+            return Ok(Fixup::rebase(address, target));
+        }
+        let (ordinal, weak_import, symbol, addend) = self.import((raw & 0xff_ffff) as usize, fileoff)?;
+        Ok(Fixup {
+            address,
+            kind: FixupKind::Bind,
+            symbol,
+            ordinal,
+            addend: addend.wrapping_add(((raw >> 24) & 0xff) as i64),
+            weak_import,
+            target: 0,
+        })
+    }
+
+    /// The library ordinal, the weak import flag, the name and the addend of the import `index`. `fileoff`
+    /// is the pointer that binds to it, for errors.
+    fn import(&self, index: usize, fileoff: usize) -> Result<(i32, bool, &'a [u8], i64), Error> {
+        if index >= self.imports_count {
+            return Err(bad(fileoff));
+        }
+        // new() checked that the whole imports table is in bounds
+        let (ordinal, weak_import, name_offset, addend) = match self.imports_format {
+            DYLD_CHAINED_IMPORT | DYLD_CHAINED_IMPORT_ADDEND => {
+                let size = if self.imports_format == DYLD_CHAINED_IMPORT { 4 } else { 8 };
+                let at = self.imports_offset + index * size;
+                let import = u32_at::<E>(self.data, at).unwrap_or_default();
+                let addend = if size == 8 { u32_at::<E>(self.data, at + 4).unwrap_or_default() as i32 as i64 } else { 0 };
+                // The ordinals from 0xf0 are the negative special ones
+                let ordinal = import as u8;
+                let ordinal = if ordinal >= 0xf0 { ordinal as i8 as i32 } else { ordinal as i32 };
+                (ordinal, import & 0x100 != 0, (import >> 9) as usize, addend)
+            }
+            _ => {
+                let at = self.imports_offset + index * 16;
+                let import = u64_at::<E>(self.data, at).unwrap_or_default();
+                let addend = u64_at::<E>(self.data, at + 8).unwrap_or_default() as i64;
+                let ordinal = import as u16;
+                let ordinal = if ordinal >= 0xfff0 { ordinal as i16 as i32 } else { ordinal as i32 };
+                (ordinal, import & 0x1_0000 != 0, (import >> 32) as usize, addend)
+            }
+        };
+        let name_at = self.symbols_offset.saturating_add(name_offset);
+        let symbol = c_str(self.data, name_at).ok_or(bad(self.dataoff.saturating_add(name_at)))?;
+        Ok((ordinal, weak_import, symbol, addend))
+    }
+}
+
+fn bad(offset: usize) -> Error {
+    Error::Macho(MachoError::BadChainedFixups { offset })
+}
+
+/// The pointers that dyld rewrites, from the chained fixups or else from the opcodes of `LC_DYLD_INFO`, which
+/// come as the rebases, the binds, the weak binds and then the lazy binds.
+#[derive(Debug, Clone)]
+pub struct Fixups<'a, E: Endian> {
+    buf: &'a [u8],
+    commands: LoadCommands<'a, E>,
+    chained: Option<ChainedFixups<'a, E>>,
+    opcodes: [Option<Opcodes<'a, E>>; 4],
+}
+
+impl<'a, E: Endian> Fixups<'a, E> {
+    pub(super) fn new(
+        buf: &'a [u8],
+        commands: LoadCommands<'a, E>,
+        chained: Option<&'a LinkeditDataCommand<E>>,
+        dyld_info: Option<&'a DyldInfoCommand<E>>,
+    ) -> Result<Fixups<'a, E>, Error> {
+        let chained = chained.map(|command| ChainedFixups::new(buf, command, &commands)).transpose()?;
+        let mut opcodes = [None, None, None, None];
+        if let Some(dyld_info) = dyld_info {
+            opcodes = [
+                Some(Opcodes::new(FixupKind::Rebase, buf, dyld_info.rebase())?),
+                Some(Opcodes::new(FixupKind::Bind, buf, dyld_info.bind())?),
+                Some(Opcodes::new(FixupKind::WeakBind, buf, dyld_info.weak_bind())?),
+                Some(Opcodes::new(FixupKind::LazyBind, buf, dyld_info.lazy_bind())?),
+            ];
+        }
+        Ok(Fixups { buf, commands, chained, opcodes })
+    }
+}
+
+impl<'a, E: Endian> Iterator for Fixups<'a, E> {
+    type Item = Result<Fixup<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(chained) = &mut self.chained {
+            return chained.next(self.buf, &self.commands);
+        }
+        for opcodes in self.opcodes.iter_mut().flatten() {
+            if let Some(fixup) = opcodes.next(self.buf, &self.commands) {
+                return Some(fixup);
+            }
+        }
+        None
+    }
+}
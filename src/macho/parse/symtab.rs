@@ -0,0 +1,208 @@
+use core::slice;
+
+use crate::{
+    elf::parse::{shn, Endian, LittleEndian, StBind, StType, StVisibility, Strings, Sym},
+    Error,
+};
+
+use super::{ffi_types::Nlist64, Segment64};
+
+/// The bits of `n_type`.
+pub mod n_type {
+    /// Any of these bits make the entry a debugging one, for dsymutil and the debuggers.
+    pub const N_STAB: u8 = 0xe0;
+    /// A private external, which the static linker turned into a local symbol.
+    pub const N_PEXT: u8 = 0x10;
+    /// The mask of the `N_UNDF`, `N_ABS`, `N_SECT`, `N_PBUD` and `N_INDR` type.
+    pub const N_TYPE: u8 = 0x0e;
+    pub const N_EXT: u8 = 0x01;
+
+    pub const N_UNDF: u8 = 0x0;
+    pub const N_ABS: u8 = 0x2;
+    pub const N_INDR: u8 = 0xa;
+    pub const N_PBUD: u8 = 0xc;
+    pub const N_SECT: u8 = 0xe;
+}
+
+/// The bits of `n_desc`.
+pub mod n_desc {
+    /// Set on symbols that `strip` must keep, like the `__mh_execute_header` the linker makes.
+    pub const REFERENCED_DYNAMICALLY: u16 = 0x10;
+    pub const N_WEAK_REF: u16 = 0x40;
+    pub const N_WEAK_DEF: u16 = 0x80;
+}
+
+const S_ATTR_PURE_INSTRUCTIONS: u32 = 0x8000_0000;
+const S_ATTR_SOME_INSTRUCTIONS: u32 = 0x400;
+const S_THREAD_LOCAL_REGULAR: u32 = 0x11;
+const S_THREAD_LOCAL_VARIABLES: u32 = 0x13;
+
+/// What the sections hold, as bit sets by `n_sect`, for telling functions from objects.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct SectKinds {
+    code: [u64; 4],
+    tls: [u64; 4],
+}
+
+impl SectKinds {
+    fn new<'a, E: Endian>(segments: impl Iterator<Item = Result<Segment64<'a, E>, Error>>) -> SectKinds {
+        let mut kinds = SectKinds::default();
+        // n_sect numbers the sections of all the segments from 1, up to 255
+        let sects = segments.flatten().flat_map(|segment| segment.sections).take(255);
+        for (i, sect) in (1..).zip(sects) {
+            let bit = 1 << (i % 64);
+            if sect.flags() & (S_ATTR_PURE_INSTRUCTIONS | S_ATTR_SOME_INSTRUCTIONS) != 0 {
+                kinds.code[i / 64] |= bit;
+            }
+            if (S_THREAD_LOCAL_REGULAR..=S_THREAD_LOCAL_VARIABLES).contains(&(sect.flags() & super::SECTION_TYPE)) {
+                kinds.tls[i / 64] |= bit;
+            }
+        }
+        kinds
+    }
+
+    fn st_type(&self, n_sect: u8) -> StType {
+        let (word, bit) = (n_sect as usize / 64, 1 << (n_sect % 64));
+        if self.code[word] & bit != 0 {
+            StType::Func
+        } else if self.tls[word] & bit != 0 {
+            StType::Tls
+        } else {
+            StType::Object
+        }
+    }
+}
+
+/// An entry of the symbol table, with the kind of the section it is defined in, which Mach-O
+/// symbols don't record themselves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MachoSym<'a, E: Endian = LittleEndian> {
+    pub nlist: &'a Nlist64<E>,
+    sect_type: StType,
+}
+
+impl<E: Endian> MachoSym<'_, E> {
+    pub fn n_type(&self) -> u8 {
+        self.nlist.n_type
+    }
+
+    /// The section ordinal, counted from 1 over all segments, or 0 for `NO_SECT`.
+    pub fn n_sect(&self) -> u8 {
+        self.nlist.n_sect
+    }
+
+    pub fn n_desc(&self) -> u16 {
+        self.nlist.n_desc.get()
+    }
+
+    fn is_stab(&self) -> bool {
+        self.n_type() & n_type::N_STAB != 0
+    }
+
+    /// An undefined external with a value is a tentative definition, whose value is its size.
+    fn is_common(&self) -> bool {
+        self.n_type() & (n_type::N_TYPE | n_type::N_EXT) == (n_type::N_UNDF | n_type::N_EXT)
+            && self.nlist.n_value.get() != 0
+    }
+}
+
+/// Mach-O symbols have no sizes, and their section numbers are the 1-based `n_sect` ordinals.
+impl<E: Endian> Sym for MachoSym<'_, E> {
+    fn name<'a>(&self, str: &Strings<'a>) -> Result<&'a [u8], Error> {
+        match self.nlist.n_strx.get() {
+            0 => Ok(&[]),
+            n_strx => str.get_string(n_strx as usize),
+        }
+    }
+
+    fn binding(&self) -> Result<StBind, Error> {
+        Ok(if self.n_type() & n_type::N_EXT == 0 {
+            StBind::Local
+        } else if self.n_desc() & (n_desc::N_WEAK_REF | n_desc::N_WEAK_DEF) != 0 {
+            StBind::Weak
+        } else {
+            StBind::Global
+        })
+    }
+
+    fn st_type(&self) -> Result<StType, Error> {
+        if self.is_stab() {
+            return Ok(StType::NoType);
+        }
+        Ok(match self.n_type() & n_type::N_TYPE {
+            // The linker's __mh_execute_header is in the first section but points at the header
+            n_type::N_SECT if self.n_desc() & n_desc::REFERENCED_DYNAMICALLY != 0 => StType::Object,
+            n_type::N_SECT => self.sect_type,
+            _ if self.is_common() => StType::Common,
+            _ => StType::NoType,
+        })
+    }
+
+    fn visibility(&self) -> StVisibility {
+        if self.n_type() & n_type::N_PEXT != 0 {
+            StVisibility::Hidden
+        } else {
+            StVisibility::Default
+        }
+    }
+
+    /// The alignment of common symbols, like in ELF, and the address of everything else.
+    fn value(&self) -> usize {
+        if self.is_common() {
+            1 << ((self.n_desc() >> 8) & 0x0f)
+        } else {
+            self.nlist.n_value.get() as usize
+        }
+    }
+
+    fn size(&self) -> usize {
+        if self.is_common() {
+            self.nlist.n_value.get() as usize
+        } else {
+            0
+        }
+    }
+
+    fn shndx(&self) -> u16 {
+        if self.is_stab() {
+            return self.n_sect() as u16;
+        }
+        match self.n_type() & n_type::N_TYPE {
+            n_type::N_SECT => self.n_sect() as u16,
+            n_type::N_ABS => shn::SHN_ABS,
+            _ if self.is_common() => shn::SHN_COMMON,
+            _ => shn::SHN_UNDEF,
+        }
+    }
+}
+
+/// Entries of the symbol table as `Sym`s.
+#[derive(Debug, Clone)]
+pub struct Symbols<'a, E: Endian = LittleEndian> {
+    nlists: slice::Iter<'a, Nlist64<E>>,
+    kinds: SectKinds,
+}
+
+impl<'a, E: Endian> Symbols<'a, E> {
+    pub(super) fn new(
+        nlists: &'a [Nlist64<E>],
+        segments: impl Iterator<Item = Result<Segment64<'a, E>, Error>>,
+    ) -> Symbols<'a, E> {
+        Symbols { nlists: nlists.iter(), kinds: SectKinds::new(segments) }
+    }
+}
+
+impl<'a, E: Endian> Iterator for Symbols<'a, E> {
+    type Item = MachoSym<'a, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let nlist = self.nlists.next()?;
+        Some(MachoSym { nlist, sect_type: self.kinds.st_type(nlist.n_sect) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nlists.size_hint()
+    }
+}
+
+impl<E: Endian> ExactSizeIterator for Symbols<'_, E> {}
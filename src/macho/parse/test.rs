@@ -0,0 +1,382 @@
+use crate::{
+    elf::parse::{shn, StBind, StType, Sym, SymSection},
+    error::{Error, MachoError},
+    macho::parse::{
+        self, mh_flags, n_type, vm_prot, CpuType, FileType, Fixup, FixupKind, LoadCmd, LoadCommand, MachoFile64,
+        Platform, Version,
+    },
+    utils::{include_aligned, ToKnown},
+};
+
+fn x86_64_macho() -> MachoFile64<'static> {
+    parse::with(include_aligned!("../../../test/test.x86_64.macho")).unwrap()
+}
+
+fn arm64_macho() -> MachoFile64<'static> {
+    parse::with(include_aligned!("../../../test/test.arm64.macho")).unwrap()
+}
+
+#[test]
+fn headers() {
+    let x86_64 = x86_64_macho();
+    assert_eq!(x86_64.mh.cputype(), Ok(CpuType::X86_64));
+    assert_eq!(x86_64.mh.filetype(), Ok(FileType::Execute));
+    assert_ne!(x86_64.mh.flags() & mh_flags::MH_PIE, 0);
+    assert_eq!(x86_64.load_commands().count(), x86_64.mh.ncmds());
+
+    let arm64 = arm64_macho();
+    assert_eq!(arm64.mh.cputype(), Ok(CpuType::Arm64));
+    assert_eq!(arm64.mh.filetype(), Ok(FileType::Execute));
+    // Unlike x86-64, arm64 executables have to be signed
+    assert!(arm64.load_commands().any(|command| matches!(
+        command,
+        Ok(LoadCommand::Other { cmd, .. }) if cmd.known() == Ok(LoadCmd::CodeSignature)
+    )));
+}
+
+#[test]
+fn segments_and_sections() {
+    let macho = x86_64_macho();
+    let names: Vec<_> = macho.segments().map(|segment| segment.unwrap().command.name()).collect();
+    assert_eq!(names[..2], [&b"__PAGEZERO"[..], b"__TEXT"]);
+    assert_eq!(names.last(), Some(&&b"__LINKEDIT"[..]));
+
+    let text = macho.segment(b"__TEXT").unwrap().unwrap();
+    assert_eq!(text.command.fileoff(), 0);
+    assert_eq!(text.command.initprot(), vm_prot::VM_PROT_READ | vm_prot::VM_PROT_EXECUTE);
+    assert!(text.sections.iter().all(|sect| sect.segname() == b"__TEXT"));
+    // The header is mapped at the start of __TEXT
+    assert_eq!(&text.command.data(macho.buf).unwrap()[..4], &0xfeedfacfu32.to_le_bytes());
+
+    let cstring = macho.section(b"__TEXT", b"__cstring").unwrap().unwrap();
+    let strings = cstring.data(macho.buf).unwrap();
+    assert!(strings.starts_with(b"test test test from yukichan\0"));
+    let ystavam = "test test test from ystäväm\0".as_bytes();
+    assert!(strings.windows(ystavam.len()).any(|w| w == ystavam));
+    assert_eq!(macho.section(b"__DATA", b"__text"), Ok(None));
+}
+
+#[test]
+fn entry_uuid_and_build_version() {
+    for macho in [x86_64_macho(), arm64_macho()] {
+        let text = macho.section(b"__TEXT", b"__text").unwrap().unwrap();
+        let entryoff = macho.entry().unwrap().unwrap().entryoff();
+        assert!((text.offset()..text.offset() + text.size()).contains(&entryoff));
+
+        assert_ne!(macho.uuid().unwrap(), Some([0; 16]));
+
+        let build_version = macho.build_version().unwrap().unwrap();
+        assert_eq!(build_version.command.platform(), Ok(Platform::MacOS));
+        assert_eq!(build_version.command.minos(), Version(0x000b_0000));
+        assert_eq!(build_version.command.minos().to_string(), "11.0.0");
+    }
+}
+
+#[test]
+fn dylibs() {
+    let macho = x86_64_macho();
+    let dylibs: Vec<_> = macho.dylibs().map(Result::unwrap).collect();
+    assert_eq!(dylibs.len(), 1);
+    assert_eq!(dylibs[0].name, b"/usr/lib/libSystem.B.dylib");
+    assert_eq!(dylibs[0].command.cmd().known(), Ok(LoadCmd::LoadDylib));
+    assert_eq!(dylibs[0].command.current_version().major(), 1311);
+}
+
+#[test]
+fn symbols() {
+    for macho in [x86_64_macho(), arm64_macho()] {
+        let sym_names = macho.sym_names.as_ref().unwrap();
+        let names = |symbols: parse::Symbols| {
+            // ld64 sorts the external symbols by name, lld doesn't
+            let mut names: Vec<_> = symbols.map(|sym| sym.name(sym_names).unwrap()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(macho.symbols().len(), macho.symtab.unwrap().len());
+        assert_eq!(names(macho.local_symbols().unwrap()), [b"__dyld_private"]);
+        assert_eq!(
+            names(macho.extdef_symbols().unwrap()),
+            [&b"__mh_execute_header"[..], b"_chomkero", b"_main", b"_pikachu", b"_sumikko", b"_ystavam", b"_yukichan"]
+        );
+        assert_eq!(names(macho.undef_symbols().unwrap()), [&b"_printf"[..], b"_puts", b"dyld_stub_binder"]);
+
+        let text = macho.section(b"__TEXT", b"__text").unwrap().unwrap();
+        for sym in macho.extdef_symbols().unwrap() {
+            assert_eq!(sym.binding(), Ok(StBind::Global));
+            if sym.name(sym_names).unwrap() == b"__mh_execute_header" {
+                // The linkers put it in the first section, though it is the address of the header
+                assert_eq!((sym.n_sect(), sym.value()), (1, text.addr() & !0xfff));
+                assert_eq!(sym.st_type(), Ok(StType::Object));
+                continue;
+            }
+            assert_eq!(sym.st_type(), Ok(StType::Func));
+            assert!((text.addr()..text.addr() + text.size()).contains(&sym.value()));
+        }
+        let dyld_private = macho.local_symbols().unwrap().next().unwrap();
+        assert_eq!((dyld_private.binding(), dyld_private.st_type()), (Ok(StBind::Local), Ok(StType::Object)));
+        for sym in macho.undef_symbols().unwrap() {
+            assert_eq!(sym.n_type() & n_type::N_TYPE, n_type::N_UNDF);
+            assert_eq!((sym.binding(), sym.st_type()), (Ok(StBind::Global), Ok(StType::NoType)));
+            assert_eq!(sym.section(), SymSection::Undef);
+            assert_eq!(sym.shndx(), shn::SHN_UNDEF);
+        }
+    }
+}
+
+#[test]
+fn fixups() {
+    let x86_64 = parse::with(include_aligned!("../../../test/test.fixups.x86_64.macho")).unwrap();
+    let arm64 = parse::with(include_aligned!("../../../test/test.fixups.arm64.macho")).unwrap();
+    assert!(x86_64.dyld_info().unwrap().is_some() && x86_64.chained_fixups().unwrap().is_none());
+    assert!(arm64.dyld_info().unwrap().is_none() && arm64.chained_fixups().unwrap().is_some());
+
+    for macho in [&x86_64, &arm64] {
+        let sym_names = macho.sym_names.as_ref().unwrap();
+        let address = |name: &[u8]| {
+            macho.symbols().find(|sym| sym.name(sym_names) == Ok(name)).unwrap().value() as u64
+        };
+        let (pointers, main) = (address(b"_pointers"), address(b"_main"));
+        let quack = macho.section(b"__TEXT", b"__cstring").unwrap().unwrap().addr() as u64;
+
+        let fixups: Vec<_> = macho.fixups().unwrap().map(Result::unwrap).collect();
+        let mut table: Vec<_> = fixups.iter().filter(|fixup| (pointers..pointers + 40).contains(&fixup.address)).collect();
+        table.sort_by_key(|fixup| fixup.address);
+        let bind = |i: u64, symbol: &'static [u8], addend: i64, weak_import: bool| Fixup {
+            address: pointers + i * 8,
+            kind: FixupKind::Bind,
+            symbol,
+            ordinal: 1,
+            addend,
+            weak_import,
+            target: 0,
+        };
+        assert_eq!(
+            table,
+            [
+                &Fixup::rebase(pointers, main),
+                &Fixup::rebase(pointers + 8, quack),
+                &bind(2, b"_puts", 0, false),
+                &bind(3, b"_printf", 16, false),
+                &bind(4, b"_exit", 0, true),
+            ]
+        );
+    }
+
+    // The stub of puts binds lazily, and dyld_stub_binder is what binds it
+    let kinds = |macho: &MachoFile64, symbol: &[u8]| -> Vec<_> {
+        let fixups = macho.fixups().unwrap().map(Result::unwrap);
+        fixups.filter(|fixup| fixup.symbol == symbol).map(|fixup| fixup.kind).collect()
+    };
+    assert_eq!(kinds(&x86_64, b"_puts"), [FixupKind::Bind, FixupKind::LazyBind]);
+    assert_eq!(kinds(&x86_64, b"dyld_stub_binder"), [FixupKind::Bind]);
+    // With chained fixups, puts is bound up front in __got
+    assert_eq!(kinds(&arm64, b"_puts"), [FixupKind::Bind, FixupKind::Bind]);
+    assert_eq!(kinds(&arm64, b"dyld_stub_binder"), []);
+
+    let patched = |macho: &MachoFile64, offset: usize, bytes: &[u8]| {
+        let mut buf = macho.buf.to_vec();
+        buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+        buf
+    };
+    let first_error = |buf: Vec<u8>| -> Result<(), Error> {
+        let mut aligned = vec![0u8; buf.len() + 8];
+        let offset = aligned.as_ptr().align_offset(8);
+        aligned[offset..offset + buf.len()].copy_from_slice(&buf);
+        let macho = parse::with(&aligned[offset..offset + buf.len()])?;
+        macho.fixups()?.take(100).try_for_each(|fixup| fixup.map(drop))
+    };
+    let (rebase_off, _) = x86_64.dyld_info().unwrap().unwrap().rebase();
+    assert_eq!(
+        first_error(patched(&x86_64, rebase_off, &[0xf0])),
+        Err(Error::Macho(MachoError::BadDyldOpcode { offset: rebase_off, opcode: 0xf0 }))
+    );
+    // REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB for a segment that isn't there
+    assert_eq!(
+        first_error(patched(&x86_64, rebase_off, &[0x11, 0x2f, 0x00])),
+        Err(Error::Macho(MachoError::BadFixupSegment(15)))
+    );
+    // BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB with a skip that wraps the offset around to where it was
+    let (bind_off, _) = x86_64.dyld_info().unwrap().unwrap().bind();
+    let skip_back = [0x71, 0x00, 0xc0, 0x02, 0xf8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+    assert_eq!(
+        first_error(patched(&x86_64, bind_off, &skip_back)),
+        Err(Error::Macho(MachoError::BadDyldOpcode { offset: bind_off + 2, opcode: 0xc0 }))
+    );
+    let dataoff = arm64.chained_fixups().unwrap().unwrap().dataoff();
+    assert_eq!(
+        first_error(patched(&arm64, dataoff, &[1])),
+        Err(Error::Macho(MachoError::UnsupportedChainedFormat(1)))
+    );
+}
+
+#[test]
+fn universal() {
+    let buf: &[u8] = include_aligned!("../../../test/test.universal.macho");
+    assert!(parse::is_fat(buf));
+    assert!(!parse::is_fat(include_aligned!("../../../test/test.x86_64.macho")));
+    // A Java class file, version 52.0
+    assert!(!parse::is_fat(b"\xca\xfe\xba\xbe\x00\x00\x00\x34"));
+
+    let fat = parse::with_fat(buf).unwrap();
+    assert_eq!((fat.header.magic(), fat.header.nfat_arch()), (parse::FAT_MAGIC, 2));
+    let slices: Vec<_> = fat.slices().map(Result::unwrap).collect();
+    assert_eq!(slices.iter().map(|slice| slice.cputype()).collect::<Vec<_>>(), [Ok(CpuType::X86_64), Ok(CpuType::Arm64)]);
+    for slice in &slices {
+        assert_eq!(slice.offset() % (1 << slice.align()), 0);
+        assert_eq!(slice.data, &buf[slice.offset()..slice.offset() + slice.size()]);
+    }
+    // The slices are the thin files as they are
+    assert_eq!(slices[0].data, include_aligned!("../../../test/test.x86_64.macho"));
+    assert_eq!(slices[1].data, include_aligned!("../../../test/test.arm64.macho"));
+
+    let arm64 = fat.slice(CpuType::Arm64).unwrap().unwrap().parse().unwrap();
+    assert_eq!(arm64.mh.cputype(), Ok(CpuType::Arm64));
+    assert_eq!(fat.slice(CpuType::PowerPC), Ok(None));
+    assert_eq!(CpuType::from_arch_name(b"arm64"), Some(CpuType::Arm64));
+    assert_eq!(CpuType::from_arch_name(CpuType::X86_64.arch_name().as_bytes()), Some(CpuType::X86_64));
+    assert_eq!(CpuType::from_arch_name(b"x86-64"), None);
+
+    let patched = |offset: usize, bytes: &[u8]| {
+        let mut buf = buf.to_vec();
+        buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+        buf
+    };
+    let slice_err = |buf: &[u8]| -> Result<(), Error> {
+        let mut aligned = vec![0u8; buf.len() + 8];
+        let offset = aligned.as_ptr().align_offset(8);
+        aligned[offset..offset + buf.len()].copy_from_slice(buf);
+        for slice in parse::with_fat(&aligned[offset..offset + buf.len()])?.slices() {
+            slice?;
+        }
+        Ok(())
+    };
+    assert_eq!(slice_err(&buf[..4]), Err(Error::Macho(MachoError::TruncatedHeader(4))));
+    assert_eq!(slice_err(&buf[..30]), Err(Error::Macho(MachoError::FatArchsOutOfBounds(2))));
+    assert_eq!(slice_err(&patched(0, b"\xca\xfe\xba\xba")), Err(Error::Macho(MachoError::BadMagic(0xcafebaba))));
+    // The size of the arm64 slice
+    assert_eq!(
+        slice_err(&patched(40, &u32::MAX.to_be_bytes())),
+        Err(Error::Macho(MachoError::FatSliceOutOfBounds { offset: 0x8000, size: u32::MAX as u64 }))
+    );
+}
+
+/// The file offset of the first `cmd` command of `buf`.
+fn command_offset(buf: &[u8], cmd: LoadCmd) -> usize {
+    let word = |offset: usize| u32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap());
+    let mut offset = 32;
+    while word(offset) != cmd as u32 {
+        offset += word(offset + 4) as usize;
+    }
+    offset
+}
+
+/// Parses `buf` from an 8-aligned copy and reads everything the parser exposes.
+fn parse_malformed(buf: &[u8]) -> Result<(), Error> {
+    let mut aligned = vec![0u8; buf.len() + 8];
+    let offset = aligned.as_ptr().align_offset(8);
+    aligned[offset..offset + buf.len()].copy_from_slice(buf);
+    let macho = parse::with(&aligned[offset..offset + buf.len()])?;
+    for segment in macho.segments() {
+        let segment = segment?;
+        let _ = segment.command.data(macho.buf);
+        for sect in segment.sections {
+            let _ = sect.data(macho.buf);
+        }
+    }
+    let _ = macho.entry();
+    let _ = macho.uuid();
+    let _ = macho.build_version().map(|build_version| build_version.map(|b| b.command.platform()));
+    macho.dylibs().count();
+    if let Some(sym_names) = &macho.sym_names {
+        for sym in macho.symbols() {
+            let _ = (sym.name(sym_names), sym.binding(), sym.st_type(), sym.value(), sym.section());
+        }
+    }
+    let _ = (macho.local_symbols(), macho.extdef_symbols(), macho.undef_symbols());
+    // A fuzzed count of a rebase can repeat it for ever, within a segment with a fuzzed size
+    macho.fixups()?.take(10000).count();
+    Ok(())
+}
+
+#[test]
+fn malformed_input() {
+    use rand::Rng;
+
+    let x86_64: &[u8] = include_aligned!("../../../test/test.x86_64.macho");
+    assert_eq!(parse_malformed(x86_64), Ok(()));
+    assert_eq!(parse_malformed(&x86_64[..20]), Err(Error::Macho(MachoError::TruncatedHeader(20))));
+    for len in (0..x86_64.len()).step_by(61) {
+        let _ = parse_malformed(&x86_64[..len]);
+    }
+
+    let patched = |offset: usize, bytes: &[u8]| {
+        let mut buf = x86_64.to_vec();
+        buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+        buf
+    };
+    let err = |buf: Vec<u8>| match parse_malformed(&buf) {
+        Err(Error::Macho(err)) => err,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(err(patched(0, b"\x7fELF")), MachoError::BadMagic(0x464c457f));
+    assert_eq!(err(patched(0, &parse::MH_MAGIC.to_le_bytes())), MachoError::UnsupportedMagic(parse::MH_MAGIC));
+    assert_eq!(err(patched(0, &parse::MH_CIGAM_64.to_le_bytes())), MachoError::UnsupportedMagic(parse::MH_CIGAM_64));
+    assert_eq!(err(patched(20, &u32::MAX.to_le_bytes())), MachoError::CommandsOutOfBounds(u32::MAX as usize));
+    // The cmdsize of the first command, __PAGEZERO
+    assert_eq!(err(patched(36, &4u32.to_le_bytes())), MachoError::BadCommandSize { offset: 32, cmdsize: 4 });
+    assert_eq!(err(patched(36, &76u32.to_le_bytes())), MachoError::BadCommandSize { offset: 32, cmdsize: 76 });
+    // The nsects of __PAGEZERO, whose command has room for none
+    assert_eq!(err(patched(96, &1u32.to_le_bytes())), MachoError::TruncatedCommand { offset: 32, cmd: 0x19 });
+    // One more command than there are
+    let ncmds = x86_64_macho().mh.ncmds() as u32;
+    assert!(matches!(err(patched(16, &(ncmds + 1).to_le_bytes())), MachoError::BadCommandSize { .. }));
+
+    let (symtab, dysymtab) = (command_offset(x86_64, LoadCmd::Symtab), command_offset(x86_64, LoadCmd::Dysymtab));
+    let stroff = u32::from_le_bytes(x86_64[symtab + 16..symtab + 20].try_into().unwrap()) as usize;
+    let len = x86_64.len();
+    assert_eq!(
+        err(patched(symtab + 8, &(len as u32).to_le_bytes())),
+        MachoError::SymbolsOutOfBounds { offset: len, count: 11 }
+    );
+    assert_eq!(
+        err(patched(symtab + 8, &1u32.to_le_bytes())),
+        MachoError::SymbolsOutOfBounds { offset: 1, count: 11 }
+    );
+    assert_eq!(
+        err(patched(symtab + 20, &u32::MAX.to_le_bytes())),
+        MachoError::StringsOutOfBounds { offset: stroff, size: u32::MAX as usize }
+    );
+    // Cut the string table right after the first name
+    assert!(matches!(err(patched(symtab + 20, &3u32.to_le_bytes())), MachoError::UnterminatedStrings { .. }));
+    // iundefsym past the end of the symbol table
+    assert_eq!(
+        err(patched(dysymtab + 24, &10u32.to_le_bytes())),
+        MachoError::BadSymbolRange { first: 10, count: 3 }
+    );
+
+    let mut rng = rand::thread_rng();
+    for buf in [
+        include_aligned!("../../../test/test.fixups.x86_64.macho"),
+        include_aligned!("../../../test/test.fixups.arm64.macho"),
+    ] {
+        assert_eq!(parse_malformed(buf), Ok(()));
+        let linkedit = parse::with(buf).unwrap().segment(b"__LINKEDIT").unwrap().unwrap().command.fileoff();
+        for _ in 0..200 {
+            let mut buf = buf.to_vec();
+            // The fixups are in __LINKEDIT
+            for _ in 0..rng.gen_range(1..8) {
+                let i = rng.gen_range(linkedit..buf.len());
+                buf[i] = rng.gen();
+            }
+            let _ = parse_malformed(&buf);
+        }
+    }
+    for _ in 0..200 {
+        let mut buf = x86_64.to_vec();
+        for _ in 0..rng.gen_range(1..32) {
+            let i = rng.gen_range(0..buf.len());
+            buf[i] = rng.gen();
+        }
+        let _ = parse_malformed(&buf);
+    }
+}
@@ -1,13 +1,18 @@
 #![cfg_attr(not(test), no_std)]
 #![cfg_attr(not(test), no_main)]
 
-use core::{fmt::Write};
+use core::fmt::{Debug, Write};
 
-mod error;
-mod os;
+use quack::{
+    demangle,
+    elf::parse::{Dyn, Dynamic, EIClass, EIData, ElfFile32, ElfFile64, ElfParse, Endian, LittleEndian, Strings, Sym, StType, Versions},
+    error::{ElfError, Error, MachoError},
+    macho::{self, parse::{CpuType, MachoFile64}},
+};
 
-use elf::e;
-use error::Error;
+#[cfg(all(target_os="linux", target_arch="x86_64"))]
+mod load;
+mod os;
 
 /*
 
@@ -34,59 +39,194 @@ pub fn main(args: os::Args) -> Result<(), Error> {
 
 */
 
-mod elf;
-mod utils;
-
-use crate::elf::parse::{ElfFile64, ElfParse, Sym, StType};
-
 // TODO:
-// Load and run ELF
 // Patch symbols
-// Rust demangling
-// Mach-O support
 
 fn main(args: os::Args) -> Result<(), Error> {
-    if args.len() < 2 {
+    let mut inspect = false;
+    let mut demangle = false;
+    // The slice of a universal Mach-O file to use
+    let mut arch = CpuType::X86_64;
+    let mut n = 1;
+    while n < args.len() && args.nth(n).starts_with(b"--") {
+        match args.nth(n) {
+            b"--inspect\0" => inspect = true,
+            b"--demangle\0" => demangle = true,
+            option if option.starts_with(b"--arch=") => {
+                let name = &option[b"--arch=".len()..option.len() - 1];
+                match CpuType::from_arch_name(name) {
+                    Some(cputype) => arch = cputype,
+                    None => {
+                        writeln!(os::STDERR, "Unknown architecture {}", core::str::from_utf8(name)?)?;
+                        return Err(Error::Cli)
+                    }
+                }
+            }
+            _ => {
+                writeln!(os::STDERR, "Unknown option {}", core::str::from_utf8(args.nth(n))?)?;
+                return Err(Error::Cli)
+            }
+        }
+        n += 1;
+    }
+    if args.len() <= n {
         writeln!(os::STDERR, "Provide a path to binary file as the first argument!")?;
+        writeln!(os::STDERR, "Usage: quack [--inspect [--demangle]] [--arch=<arch>] <path> [args...]")?;
         return Err(Error::Cli)
     }
-    let path = args.nth(1);
+    let path = args.nth(n);
 
     //#[cfg(all(target_os="linux", target_arch="x86_64"))]
     //runmem::maps();
 
-    let elf_fd = os::open_for_read(path)?;
-    let elf_file = os::map_file(elf_fd)?;
-    let elf = elf::parse::with(elf_file.as_slice())?;
+    let fd = os::open_for_read(path)?;
+    let file = os::map_file(fd)?;
+    let mut buf = file.as_slice();
+    if macho::parse::is_fat(buf) {
+        let fat = macho::parse::with_fat(buf)?;
+        if inspect {
+            for slice in fat.slices() {
+                writeln!(os::STDERR, "fat: {:?}", slice?)?;
+            }
+        }
+        match fat.slice(arch)? {
+            Some(slice) => buf = slice.data,
+            None => return Err(MachoError::NoFatSlice(arch as u32).into()),
+        }
+    }
+    // Mach-O files can only be inspected, everything else is parsed as an ELF
+    if macho::parse::is_macho(buf) {
+        let macho = macho::parse::with(buf)?;
+        return if inspect { inspect_macho(macho, demangle) } else { Err(MachoError::CantRun.into()) };
+    }
+    let elf = quack::elf::parse::with(buf)?;
+    if !inspect {
+        return run(elf, fd, &args, n);
+    }
     match elf {
-        ElfParse::Elf32(_) => unimplemented!(),
-        ElfParse::Elf64(ElfFile64 {
-            eh,
-            phs,
-            shs: Some(_shs),
-            sh_names: Some(_sh_names),
-            symtab: Some(symtab),
-            sym_names: Some(sym_names),
-        }) => {
-            writeln!(os::STDERR, "eh: {:?}", eh)?;
-            for ph in phs {
-                writeln!(os::STDERR, "ph: {:?}", ph)?;
+        ElfParse::Elf32(elf) => inspect32(elf, demangle),
+        ElfParse::Elf64(elf) => inspect64(elf, demangle),
+        ElfParse::Elf32Be(elf) => inspect32(elf, demangle),
+        ElfParse::Elf64Be(elf) => inspect64(elf, demangle),
+    }
+}
+
+fn inspect32<E: Endian>(elf: ElfFile32<E>, demangle: bool) -> Result<(), Error> {
+    print_headers(&elf.eh, elf.phs, elf.build_id()?, elf.dynamic()?)?;
+    let versions = elf.versions()?;
+    match elf {
+        ElfFile32 { symtab: Some(symtab), sym_names: Some(sym_names), .. } => {
+            print_functions(symtab, &sym_names, None::<Versions<E>>, demangle)
+        }
+        ElfFile32 { dynsym: Some(dynsym), dyn_names: Some(dyn_names), .. } => {
+            print_functions(dynsym, &dyn_names, versions, demangle)
+        }
+        _ => Err(ElfError::NoSymbols.into()),
+    }
+}
+
+fn inspect64<E: Endian>(elf: ElfFile64<E>, demangle: bool) -> Result<(), Error> {
+    print_headers(&elf.eh, elf.phs, elf.build_id()?, elf.dynamic()?)?;
+    let versions = elf.versions()?;
+    match elf {
+        ElfFile64 { symtab: Some(symtab), sym_names: Some(sym_names), .. } => {
+            print_functions(symtab, &sym_names, None::<Versions<E>>, demangle)
+        }
+        ElfFile64 { dynsym: Some(dynsym), dyn_names: Some(dyn_names), .. } => {
+            print_functions(dynsym, &dyn_names, versions, demangle)
+        }
+        _ => Err(ElfError::NoSymbols.into()),
+    }
+}
+
+fn print_headers(eh: &impl Debug, phs: &[impl Debug], build_id: Option<&[u8]>, dynamic: Option<Dynamic<impl Dyn>>) -> Result<(), Error> {
+    writeln!(os::STDERR, "eh: {:?}", eh)?;
+    for ph in phs {
+        writeln!(os::STDERR, "ph: {:?}", ph)?;
+    }
+    if let Some(build_id) = build_id {
+        write!(os::STDERR, "build-id: ")?;
+        for b in build_id {
+            write!(os::STDERR, "{:02x}", b)?;
+        }
+        writeln!(os::STDERR)?;
+    }
+    if let Some(dynamic) = dynamic {
+        if let Some(soname) = dynamic.soname {
+            writeln!(os::STDERR, "soname: {}", core::str::from_utf8(soname)?)?;
+        }
+        for needed in dynamic.needed() {
+            writeln!(os::STDERR, "needed: {}", core::str::from_utf8(needed?)?)?;
+        }
+    }
+    Ok(())
+}
+
+fn inspect_macho(macho: MachoFile64, demangle: bool) -> Result<(), Error> {
+    writeln!(os::STDERR, "mh: {:?}", macho.mh)?;
+    for segment in macho.segments() {
+        let segment = segment?;
+        writeln!(os::STDERR, "seg: {:?}", segment.command)?;
+        for sect in segment.sections {
+            writeln!(os::STDERR, "sect: {:?}", sect)?;
+        }
+    }
+    if let Some(uuid) = macho.uuid()? {
+        write!(os::STDERR, "uuid: ")?;
+        for (i, b) in uuid.iter().enumerate() {
+            let dash = if [4, 6, 8, 10].contains(&i) { "-" } else { "" };
+            write!(os::STDERR, "{}{:02X}", dash, b)?;
+        }
+        writeln!(os::STDERR)?;
+    }
+    if let Some(build_version) = macho.build_version()? {
+        let command = build_version.command;
+        writeln!(os::STDERR, "platform: {:?} {} sdk {}", command.platform()?, command.minos(), command.sdk())?;
+    }
+    if let Some(main) = macho.entry()? {
+        writeln!(os::STDERR, "entryoff: {:#x}", main.entryoff())?;
+    }
+    for dylib in macho.dylibs() {
+        writeln!(os::STDERR, "needed: {}", core::str::from_utf8(dylib?.name)?)?;
+    }
+    match &macho.sym_names {
+        Some(sym_names) => print_functions(macho.symbols(), sym_names, None::<Versions<LittleEndian>>, demangle),
+        None => Err(MachoError::NoSymbols.into()),
+    }
+}
+
+/// Prints the function symbols; `versions` is only given for the dynamic symbol table.
+/// With `demangle`, the Rust and C++ names are printed demangled, Rust names without their hashes.
+fn print_functions<E: Endian>(symtab: impl IntoIterator<Item = impl Sym>, sym_names: &Strings, versions: Option<Versions<E>>, demangle: bool) -> Result<(), Error> {
+    for (i, sym) in symtab.into_iter().enumerate() {
+        if let Ok(StType::Func) = sym.st_type() {
+            let name = sym.name(sym_names)?;
+            match demangle::demangle(name).filter(|_| demangle) {
+                Some(demangled) => write!(os::STDERR, "{:#}", demangled)?,
+                None => write!(os::STDERR, "{}", core::str::from_utf8(name)?)?,
             }
-        //    #[cfg(all(target_os="linux", target_arch="x86_64"))]
-        //    elf::load::probe();
-        //    #[cfg(all(target_os="linux", target_arch="x86_64"))]
-        //    elf::load::load(&phs, &mut reader);
-            for sym in symtab {
-                if let Ok(StType::Func) = sym.st_type() {
-                    writeln!(os::STDERR,
-                        "{} {:?}",
-                        core::str::from_utf8(sym.name(&sym_names)?)?,
-                        sym.binding()?
-                    )?;
-                }
+            if let Some(version) = versions.as_ref().map(|v| v.version(i)).transpose()?.flatten() {
+                write!(os::STDERR, "{}{}", version.separator(), core::str::from_utf8(version.name)?)?;
             }
+            writeln!(os::STDERR, " {:?}", sym.binding()?)?;
         }
-        _ => return e("no elf with symbol table etc."),
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(all(target_os="linux", target_arch="x86_64"))]
+fn run(elf: ElfParse, elf_fd: os::Fd, args: &os::Args, path_idx: usize) -> Result<(), Error> {
+    match elf {
+        ElfParse::Elf32(_) => Err(ElfError::UnsupportedClass(EIClass::Elf32Bit as u8).into()),
+        ElfParse::Elf32Be(_) | ElfParse::Elf64Be(_) => Err(ElfError::UnsupportedData(EIData::BigEndian as u8).into()),
+        ElfParse::Elf64(elf) => {
+            let image = load::load(&elf, elf_fd)?;
+            load::run(&image, args, args.argv_from(path_idx))
+        }
+    }
+}
+
+#[cfg(not(all(target_os="linux", target_arch="x86_64")))]
+fn run(_elf: ElfParse, _elf_fd: os::Fd, _args: &os::Args, _path_idx: usize) -> Result<(), Error> {
+    Err(ElfError::UnsupportedHost.into())
+}
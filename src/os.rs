@@ -1,10 +1,20 @@
 use core::{
     fmt::{self, Write},
-    panic::PanicInfo, slice, ptr::null,
+    panic::PanicInfo, slice, ptr::null, ffi::c_void,
 };
 
 use crate::Error;
 
+#[macro_export]
+macro_rules! dbg {
+    ($EXP: expr) => {
+        {
+            use core::fmt::Write;
+            let _ = writeln!(crate::os::STDERR, "{:?}", $EXP);
+        }
+    };
+}
+
 #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
 mod linux;
 #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
@@ -15,12 +25,22 @@ mod macos;
 #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
 use macos as inner;
 
+pub use inner::mmap_prot;
+
 #[no_mangle]
 #[allow(unused_unsafe)]
 unsafe extern "C" fn start2(argc: i64, argv: *const *const u8) -> ! {
     let args: &[*const u8] = unsafe { slice::from_raw_parts(argv, argc as usize) };
-    if let Err(e) = crate::main(Args(args)) {
-        let _ = writeln!(crate::os::STDERR, "Stopped because of {:?} error.", e);
+    // envp starts right after the null pointer terminating argv
+    let envp = unsafe { argv.add(argc as usize + 1) };
+    let mut envc = 0;
+    while !unsafe { *envp.add(envc) }.is_null() {
+        envc += 1;
+    }
+    let env: &[*const u8] = unsafe { slice::from_raw_parts(envp, envc) };
+    let auxv = unsafe { inner::auxv(envp.add(envc + 1) as *const usize) };
+    if let Err(e) = crate::main(Args { args, env, auxv }) {
+        let _ = writeln!(crate::os::STDERR, "Stopped because of an error: {}", e);
         inner::exit(e.to_ret())
     } else {
         inner::exit(0)
@@ -31,6 +51,7 @@ unsafe extern "C" fn start2(argc: i64, argv: *const *const u8) -> ! {
 pub struct Fd(u32);
 
 pub const STDERR: Fd = Fd(2);
+const NO_FD: Fd = Fd(u32::MAX); // -1, as expected by anonymous mmaps
 
 impl Write for Fd {
     fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
@@ -39,7 +60,7 @@ impl Write for Fd {
     }
 }
 
-#[cfg(not(test))]
+#[cfg(not(any(test, feature = "std")))]
 #[panic_handler]
 fn panic(pi: &PanicInfo) -> ! {
     if let Some(loc) = pi.location() {
@@ -71,6 +92,21 @@ pub unsafe extern "C" fn memcpy(mut dst: *mut u8, mut src: *const u8, count: usi
     dst
 }
 
+/// Like memcpy, but copies backwards when `dst` is after `src`, so that the ranges may overlap.
+#[no_mangle]
+pub unsafe extern "C" fn memmove(dst: *mut u8, src: *const u8, count: usize) -> *mut u8 {
+    if (dst as *const u8) <= src {
+        for i in 0..count {
+            *dst.add(i) = *src.add(i);
+        }
+    } else {
+        for i in (0..count).rev() {
+            *dst.add(i) = *src.add(i);
+        }
+    }
+    dst
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn memcmp(mut s1: *const u8, mut s2: *const u8, count: usize) -> i32 {
     let end = s2.add(count);
@@ -102,15 +138,46 @@ impl MappedFile {
     }
 }
 
-pub struct Args(&'static [*const u8]);
+/// An entry of the auxiliary vector the kernel passes to a new process.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AuxEntry {
+    pub key: usize,
+    pub val: usize,
+}
+
+pub struct Args {
+    args: &'static [*const u8],
+    env: &'static [*const u8],
+    auxv: &'static [AuxEntry],
+}
 
 impl Args {
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.args.len()
+    }
+
+    /// The raw argv pointers, starting from the `n`th one.
+    pub fn argv_from(&self, n: usize) -> &[*const u8] {
+        &self.args[n..]
+    }
+
+    /// The raw envp pointers, without the terminating null pointer.
+    pub fn envp(&self) -> &[*const u8] {
+        self.env
+    }
+
+    /// The auxiliary vector, without the terminating `AT_NULL` entry.
+    pub fn auxv(&self) -> &[AuxEntry] {
+        self.auxv
+    }
+
+    pub fn aux(&self, key: usize) -> Option<usize> {
+        self.auxv.iter().find(|a| a.key == key).map(|a| a.val)
     }
 
     pub fn nth(&self, n: usize) -> &[u8] {
-        let base = self.0[n];
+        let base = self.args[n];
         let mut ptr = base;
         loop {
             let c = unsafe { *ptr };
@@ -134,6 +201,40 @@ pub fn map_file(fd: Fd) -> Result<MappedFile, Error> {
         0)
 }
 
+pub fn map_fixed(addr: usize, len: usize, prot: u32, fd: Fd, offset: usize) -> Result<MappedFile, Error> {
+    inner::mmap(
+        addr as *const c_void,
+        len as i64,
+        prot,
+        inner::mmap_flags::MAP_PRIVATE | inner::mmap_flags::MAP_FIXED,
+        fd,
+        offset as u64)
+}
+
+pub fn map_anon_fixed(addr: usize, len: usize, prot: u32) -> Result<MappedFile, Error> {
+    inner::mmap(
+        addr as *const c_void,
+        len as i64,
+        prot,
+        inner::mmap_flags::MAP_PRIVATE | inner::mmap_flags::MAP_ANON | inner::mmap_flags::MAP_FIXED,
+        NO_FD,
+        0)
+}
+
+pub fn map_anon(len: usize, prot: u32) -> Result<MappedFile, Error> {
+    inner::mmap(
+        null(),
+        len as i64,
+        prot,
+        inner::mmap_flags::MAP_PRIVATE | inner::mmap_flags::MAP_ANON,
+        NO_FD,
+        0)
+}
+
+pub fn protect(addr: usize, len: usize, prot: u32) -> Result<(), Error> {
+    inner::mprotect(addr as *const c_void, len, prot)
+}
+
 pub fn open_for_log(path: impl AsRef<[u8]>) -> Result<Fd, Error> {
     inner::open(path.as_ref(),
     inner::OpenMode::CREAT | inner::OpenMode::WR_ONLY | inner::OpenMode::APPEND,
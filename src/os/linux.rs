@@ -2,7 +2,7 @@ use core::{
     arch::{asm, global_asm}, ffi::c_void, slice,
 };
 
-use crate::{os::{Fd, MappedFile}, Error};
+use crate::{os::{AuxEntry, Fd, MappedFile}, Error};
 
 global_asm!("
 .globl _start
@@ -13,6 +13,16 @@ _start:     # entry point of the binary, called by the loader
     call    start2"
 );
 
+/// Reads the auxiliary vector that the kernel places after the envp array.
+pub unsafe fn auxv(start: *const usize) -> &'static [AuxEntry] {
+    let start = start as *const AuxEntry;
+    let mut n = 0;
+    while unsafe { (*start.add(n)).key } != 0 { // AT_NULL
+        n += 1;
+    }
+    unsafe { slice::from_raw_parts(start, n) }
+}
+
 #[repr(u32)]
 enum Syscall {
     Read = 0,
@@ -21,6 +31,7 @@ enum Syscall {
     Close = 3,
     Fstat = 5,
     Mmap = 9,
+    Mprotect = 10,
     Exit = 60,
 }
 
@@ -179,7 +190,7 @@ pub fn mmap(addr: *const c_void, len: i64, prot: u32, flags: u32, fd: Fd, offset
             in("rdx") prot,
             in("r10") flags,
             in("r8") fd.0,
-            in("r9") 0,
+            in("r9") offset,
             out("rcx") _,
             out("r11") _,
             lateout("rax") ret,
@@ -198,4 +209,24 @@ pub fn mmap(addr: *const c_void, len: i64, prot: u32, flags: u32, fd: Fd, offset
             ))
         }
     }
-}
\ No newline at end of file
+}
+pub fn mprotect(addr: *const c_void, len: usize, prot: u32) -> Result<(), Error> {
+    let ret: i64;
+    unsafe {
+        asm!(
+            "syscall",
+            in("rax") Syscall::Mprotect as u32,
+            in("rdi") addr,
+            in("rsi") len,
+            in("rdx") prot,
+            out("rcx") _,
+            out("r11") _,
+            lateout("rax") ret,
+        );
+    }
+    if ret < 0 {
+        Err(Error::Mprotect(ret as i32))
+    } else {
+        Ok(())
+    }
+}
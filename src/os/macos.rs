@@ -2,7 +2,7 @@ use core::{
     arch::{asm, global_asm}, ffi::c_void, slice
 };
 
-use crate::{os::{Fd, MappedFile}, Error};
+use crate::{os::{AuxEntry, Fd, MappedFile}, Error};
 
 global_asm!("
 .globl start
@@ -13,6 +13,11 @@ start:      # entry point of the binary, called by the loader
     call    _start2"
 );
 
+/// macOS doesn't pass an auxiliary vector; the envp array is followed by the "apple" strings instead.
+pub unsafe fn auxv(_start: *const usize) -> &'static [AuxEntry] {
+    &[]
+}
+
 const AX_CARRY_BIT: u16 = 0x0100;
 
 #[repr(u32)]
@@ -21,6 +26,7 @@ enum Syscall {
     Read = 0x02000003,
     Write = 0x02000004,
     Open = 0x02000005,
+    Mprotect = 0x0200004A,
     Mmap = 0x020000C5,
     Fstat64 = 0x02000153,
 }
@@ -231,4 +237,28 @@ pub fn mmap(addr: *const c_void, len: i64, prot: u32, flags: u32, fd: Fd, offset
     } else {
         Err(Error::Mmap(ret as i32))
     }
-}
\ No newline at end of file
+}
+
+pub fn mprotect(addr: *const c_void, len: usize, prot: u32) -> Result<(), Error> {
+    let ret: i64;
+    let err_flags: u16;
+    unsafe {
+        asm!(
+            "syscall",
+            "mov rcx, rax", // move the return value away from rax
+            "lahf", // check the carry flag, which MacOS uses to report error status
+            in("rax") Syscall::Mprotect as u32,
+            in("rdi") addr,
+            in("rsi") len,
+            in("rdx") prot,
+            out("rcx") ret,
+            out("r11") _,
+            lateout("ax") err_flags,
+        )
+    }
+    if err_flags & AX_CARRY_BIT == 0 {
+        Ok(())
+    } else {
+        Err(Error::Mprotect(ret as i32))
+    }
+}
@@ -0,0 +1,13 @@
+; A table of pointers for the dyld fixture: two rebases, a bind, a bind with an addend and a weak import
+@.name = private unnamed_addr constant [6 x i8] c"quack\00"
+
+declare i32 @puts(ptr)
+declare i32 @printf(ptr, ...)
+declare extern_weak void @exit(i32)
+
+@pointers = global [5 x ptr] [ptr @main, ptr @.name, ptr @puts, ptr getelementptr (i8, ptr @printf, i64 16), ptr @exit]
+
+define i32 @main() {
+  call i32 @puts(ptr @.name)
+  ret i32 0
+}
@@ -0,0 +1,44 @@
+; The functions of test_elf.c, for building the Mach-O fixtures with llc where there's no macOS SDK for a C compiler
+@.s1 = private unnamed_addr constant [29 x i8] c"test test test from yukichan\00"
+@.s2 = private unnamed_addr constant [30 x i8] c"test test test from yst\C3\A4v\C3\A4m\00"
+@.s3 = private unnamed_addr constant [28 x i8] c"test test test from pikachu\00"
+@.s4 = private unnamed_addr constant [29 x i8] c"test test test from chomkero\00"
+@.s5 = private unnamed_addr constant [28 x i8] c"test test test from sumikko\00"
+@.fmt = private unnamed_addr constant [4 x i8] c"%d\0A\00"
+
+declare i32 @puts(ptr)
+declare i32 @printf(ptr, ...)
+
+define i32 @yukichan() {
+  call i32 @puts(ptr @.s1)
+  call i32 (ptr, ...) @printf(ptr @.fmt, i32 9)
+  ret i32 9
+}
+define i32 @ystavam() {
+  call i32 @puts(ptr @.s2)
+  call i32 (ptr, ...) @printf(ptr @.fmt, i32 3)
+  ret i32 3
+}
+define i32 @pikachu() {
+  call i32 @puts(ptr @.s3)
+  call i32 (ptr, ...) @printf(ptr @.fmt, i32 5)
+  ret i32 5
+}
+define i32 @chomkero() {
+  call i32 @puts(ptr @.s4)
+  call i32 (ptr, ...) @printf(ptr @.fmt, i32 7)
+  ret i32 7
+}
+define i32 @sumikko() {
+  call i32 @puts(ptr @.s5)
+  call i32 (ptr, ...) @printf(ptr @.fmt, i32 199)
+  ret i32 199
+}
+define i32 @main() {
+  call i32 @yukichan()
+  call i32 @ystavam()
+  call i32 @chomkero()
+  call i32 @pikachu()
+  call i32 @sumikko()
+  ret i32 0
+}
@@ -0,0 +1,17 @@
+#include "stdio.h"
+
+int counter;
+
+__attribute__((visibility("hidden"))) int hidden_helper() {
+    return 42;
+}
+
+__attribute__((visibility("protected"))) int shared_answer() {
+    return hidden_helper();
+}
+
+int main() {
+    counter++;
+    puts("test test test from an object");
+    return shared_answer();
+}
@@ -1,17 +1,7 @@
-use core::{mem::{size_of, align_of}, slice};
+use core::{mem::{size_of, align_of}, slice::{self, from_raw_parts_mut}};
 
 use crate::error::Error;
 
-#[macro_export]
-macro_rules! dbg {
-    ($EXP: expr) => {
-        {
-            use core::fmt::Write;
-            let _ = writeln!(crate::os::STDERR, "{:?}", $EXP);
-        }
-    };
-}
-
 pub trait ToKnown: TransmuteSafe {
     type Known;
     type Unknown;
@@ -33,17 +23,17 @@ pub unsafe trait TransmuteSafe: Default + Clone {
     }
 
     fn slice_from_buf(buf: &[u8], n: usize) -> Result<(&[Self], &[u8]), Error> {
-        if buf.len() < n * size_of::<Self>() {
-            return Err(Error::Transmute);
-        }
+        let size = match n.checked_mul(size_of::<Self>()) {
+            Some(size) if size <= buf.len() => size,
+            _ => return Err(Error::Transmute),
+        };
         if buf.as_ptr() as usize % align_of::<Self>() != 0 {
             return Err(Error::Transmute);
         }
-        let tail = &buf[n * size_of::<Self>()..];
+        let tail = &buf[size..];
         let us: &[Self] = unsafe { slice::from_raw_parts(buf.as_ptr() as *const Self, n) };
         Ok((us, tail))
     }
-    /*
     fn as_bytes_mut(&mut self) -> &mut [u8] {
         // This unsafe is sound because:
         // - Self is TransmuteSafe
@@ -53,11 +43,15 @@ pub unsafe trait TransmuteSafe: Default + Clone {
         // - The mutable access to the bytes of Self is constrained by the lifetime of &mut self
         // - Accepting &mut Self as an argument guarantees that its bytes are already initialized
         unsafe { from_raw_parts_mut(self as *mut Self as *mut u8, size_of::<Self>()) }
-    } */
+    }
 }
 
-/*
-pub fn _as_bytes_mut<T: TransmuteSafe>(vec: &mut Vec<T>, n: usize) -> &mut [u8] {
+unsafe impl TransmuteSafe for u16 {}
+unsafe impl TransmuteSafe for u32 {}
+unsafe impl TransmuteSafe for u64 {}
+
+#[cfg(any(test, feature = "std"))]
+pub fn vec_as_bytes_mut<T: TransmuteSafe>(vec: &mut Vec<T>, n: usize) -> &mut [u8] {
     vec.clear();
     vec.resize(n, T::default());
     // This unsafe is sound because:
@@ -69,4 +63,54 @@ pub fn _as_bytes_mut<T: TransmuteSafe>(vec: &mut Vec<T>, n: usize) -> &mut [u8]
     // - The mutable access to the bytes of Self is constrained by the lifetime of &mut self
     unsafe { from_raw_parts_mut(vec.as_mut_ptr() as *mut u8, n * size_of::<T>()) }
 }
-*/
\ No newline at end of file
+/// The fixtures that the tests include, aligned for `TransmuteSafe::from_buf`.
+#[cfg(test)]
+#[repr(C, align(8))]
+pub struct Aligned<T: ?Sized>(pub T);
+
+#[cfg(test)]
+macro_rules! include_aligned {
+    ($path: literal) => {
+        {
+            static ALIGNED: &$crate::utils::Aligned<[u8]> = &$crate::utils::Aligned(*include_bytes!($path));
+            &ALIGNED.0
+        }
+    };
+}
+#[cfg(test)]
+pub(crate) use include_aligned;
+
+/// Reads one of the ELF fixtures that `make elf` builds into test/, aligned like `include_aligned!`.
+/// They aren't checked in, so this is `None` until they have been built.
+#[cfg(test)]
+pub fn read_fixture(name: &str) -> Option<&'static [u8]> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test").join(name);
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            std::eprintln!("skipping, {} hasn't been built: run `make elf`", path.display());
+            return None;
+        }
+    };
+    let mut words = Vec::<u64>::new();
+    vec_as_bytes_mut(&mut words, bytes.len().div_ceil(8))[..bytes.len()].copy_from_slice(&bytes);
+    let words: &'static [u64] = words.leak();
+    // This unsafe is sound because:
+    // - the leaked words are initialized and never freed or mutated again
+    // - [u8] has alignment of 1 and allows any byte pattern
+    // - bytes.len() is at most the size of the words in bytes
+    Some(unsafe { slice::from_raw_parts(words.as_ptr() as *const u8, bytes.len()) })
+}
+
+/// The bytes of an ELF fixture, or returns from the test if it hasn't been built.
+#[cfg(test)]
+macro_rules! fixture {
+    ($name: literal) => {
+        match $crate::utils::read_fixture($name) {
+            Some(buf) => buf,
+            None => return,
+        }
+    };
+}
+#[cfg(test)]
+pub(crate) use fixture;
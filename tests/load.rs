@@ -0,0 +1,43 @@
+use std::{path::Path, process::Command};
+
+/// The path of one of the ELF fixtures that `make elf` builds, or `None` if it hasn't been built.
+fn fixture(name: &str) -> Option<String> {
+    let path = format!("{}/test/{}", env!("CARGO_MANIFEST_DIR"), name);
+    if Path::new(&path).exists() {
+        Some(path)
+    } else {
+        eprintln!("skipping, {} hasn't been built: run `make elf`", path);
+        None
+    }
+}
+
+#[test]
+fn static_pie() {
+    let path = match fixture("test.static-pie.elf") {
+        Some(path) => path,
+        None => return,
+    };
+    let out = Command::new(env!("CARGO_BIN_EXE_quack"))
+        .arg(path)
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.starts_with("test test test from yukichan\n9\n"));
+    assert!(stdout.ends_with("test test test from sumikko\n199\n"));
+}
+
+#[test]
+fn needs_interpreter() {
+    let path = match fixture("test.gnu.elf") {
+        Some(path) => path,
+        None => return,
+    };
+    let out = Command::new(env!("CARGO_BIN_EXE_quack"))
+        .arg(path)
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("PT_INTERP"));
+}